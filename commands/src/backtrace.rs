@@ -0,0 +1,20 @@
+use std::error::Error;
+
+use clap::Clap;
+
+use debug::Debugger;
+
+use crate::Command;
+
+///
+/// Show the stack of the current thread, innermost frame first
+///
+#[derive(Clap)]
+pub struct BacktraceCommand {}
+
+impl Command for BacktraceCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        dbg.backtrace()?;
+        Ok(())
+    }
+}
@@ -1,7 +1,13 @@
+use std::error::Error;
+use std::str::FromStr;
+
 use clap::Clap;
-use crate::Command;
+
+use debug::breakpoint::condition::Condition;
+use debug::breakpoint::location::Location;
 use debug::Debugger;
-use std::error::Error;
+
+use crate::Command;
 
 #[derive(Clap)]
 pub struct BreakCommand {
@@ -11,22 +17,148 @@ pub struct BreakCommand {
 
 #[derive(Clap)]
 pub enum BreakSubCommand {
-    New { addr: u64 },
-    Remove { which: usize },
-    Enable { which: usize },
-    Disable { which: usize },
+    /// Add a breakpoint at an address (`0x401136`), function (`main`) or
+    /// line (`loop.c:12`), optionally only stopping in one thread or one
+    /// inferior, or when a condition holds, e.g.
+    /// `break new main inferior 1 thread 2 if $rdi == 1`.
+    /// Breakpoints can be set before the program is running, and are
+    /// inserted once their location is loaded.
+    New {
+        location: Location,
+        qualifiers: Vec<String>,
+    },
+    /// Remove breakpoints, by id, range of ids (`2-4`), or `all`
+    Remove {
+        #[clap(required = true)]
+        which: Vec<IdSpec>,
+    },
+    /// Enable breakpoints, by id, range of ids (`2-4`), or `all`
+    Enable {
+        #[clap(required = true)]
+        which: Vec<IdSpec>,
+    },
+    /// Disable breakpoints, by id, range of ids (`2-4`), or `all`
+    Disable {
+        #[clap(required = true)]
+        which: Vec<IdSpec>,
+    },
+    /// Set the condition of a breakpoint, or clear it if none is given
+    Condition {
+        which: u32,
+        condition: Vec<String>,
+    },
     List,
 }
 
 impl Command for BreakCommand {
     fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
-        match self.cmd {
-            BreakSubCommand::New { addr } => dbg.add_breakpoint(addr)?,
-            BreakSubCommand::Remove { which } => dbg.remove_breakpoint(which)?,
+        match &self.cmd {
+            BreakSubCommand::New { location, qualifiers } => {
+                let (mut thread, mut inferior) = (None, None);
+                let mut rest = qualifiers.as_slice();
+                while let [keyword, id, remaining @ ..] = rest {
+                    let scope = match keyword.as_str() {
+                        "thread" => &mut thread,
+                        "inferior" => &mut inferior,
+                        _ => break,
+                    };
+                    *scope = Some(id.parse::<u32>().map_err(|_| format!("invalid {} id: {}", keyword, id))?);
+                    rest = remaining;
+                }
+                let condition = match rest.split_first() {
+                    Some((keyword, rest)) if keyword == "if" => Some(parse_condition(rest)?),
+                    Some(_) => return Err("expected `thread <id>`, `inferior <id>` or `if <condition>` after the location".into()),
+                    None => None
+                };
+                dbg.add_breakpoint(location.clone(), condition, thread, inferior)?;
+            }
+            BreakSubCommand::Remove { which } => {
+                for id in IdSpec::expand(which, dbg)? {
+                    dbg.remove_breakpoint(id)?;
+                }
+            }
+            BreakSubCommand::Enable { which } => {
+                for id in IdSpec::expand(which, dbg)? {
+                    dbg.enable_breakpoint(id)?;
+                }
+            }
+            BreakSubCommand::Disable { which } => {
+                for id in IdSpec::expand(which, dbg)? {
+                    dbg.disable_breakpoint(id)?;
+                }
+            }
+            BreakSubCommand::Condition { which, condition } => {
+                let condition = if condition.is_empty() {
+                    None
+                } else {
+                    Some(parse_condition(condition)?)
+                };
+                dbg.set_breakpoint_condition(*which, condition)?;
+            }
             BreakSubCommand::List => dbg.list_breakpoints(),
-            BreakSubCommand::Enable { which } => dbg.enable_breakpoint(which)?,
-            BreakSubCommand::Disable { which } => dbg.enable_breakpoint(which)?
         };
         Ok(())
     }
 }
+
+///
+/// Selects one or more breakpoints by their identifiers.
+///
+pub enum IdSpec {
+    /// A single breakpoint, which must exist.
+    One(u32),
+    /// Every existing breakpoint with an identifier in the inclusive range.
+    Range(u32, u32),
+    /// Every breakpoint.
+    All,
+}
+
+impl IdSpec {
+    ///
+    /// Expands the specs into the identifiers of the breakpoints they select,
+    /// in increasing order and without duplicates.
+    ///
+    pub fn expand(specs: &[IdSpec], dbg: &Debugger) -> Result<Vec<u32>, Box<dyn Error>> {
+        let existing = dbg.breakpoints().ids();
+        let mut ids = Vec::new();
+        for spec in specs {
+            match spec {
+                IdSpec::One(id) => {
+                    dbg.breakpoints().get(*id)?;
+                    ids.push(*id);
+                }
+                IdSpec::Range(start, end) => {
+                    ids.extend(existing.iter().filter(|id| (start..=end).contains(id)));
+                }
+                IdSpec::All => ids.extend(existing.iter()),
+            }
+        }
+        ids.sort_unstable();
+        ids.dedup();
+        Ok(ids)
+    }
+}
+
+impl FromStr for IdSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |id: &str| id.parse::<u32>().map_err(|_| format!("invalid breakpoint id: {}", s));
+
+        if s == "all" {
+            Ok(IdSpec::All)
+        } else if let Some((start, end)) = s.split_once('-') {
+            let (start, end) = (parse(start)?, parse(end)?);
+            if start > end {
+                return Err(format!("invalid breakpoint range: {}", s));
+            }
+            Ok(IdSpec::Range(start, end))
+        } else {
+            Ok(IdSpec::One(parse(s)?))
+        }
+    }
+}
+
+fn parse_condition(words: &[String]) -> Result<Condition, Box<dyn Error>> {
+    Ok(words.join(" ").parse::<Condition>()?)
+}
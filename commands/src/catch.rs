@@ -0,0 +1,49 @@
+use std::error::Error;
+
+use clap::Clap;
+
+use debug::breakpoint::Catch;
+use debug::syscalls::SyscallFilter;
+use debug::Debugger;
+
+use crate::Command;
+
+#[derive(Clap)]
+pub struct CatchCommand {
+    #[clap(subcommand)]
+    cmd: CatchSubCommand
+}
+
+#[derive(Clap)]
+pub enum CatchSubCommand {
+    /// Stop when a shared library is loaded, optionally only those
+    /// whose path contains the given name
+    Load {
+        name: Option<String>,
+    },
+    /// Stop when a shared library is unloaded, optionally only those
+    /// whose path contains the given name
+    Unload {
+        name: Option<String>,
+    },
+    /// Stop when a system call is entered or returns, optionally only the
+    /// given calls, by name or number, or groups of them (as group:NAME).
+    /// With detach-on-fork off, a program run from then on only stops on
+    /// the calls caught, but can't be detached from, as they would fail,
+    /// and can't gain privileges by running setuid programs
+    Syscall {
+        syscalls: Vec<String>,
+    },
+}
+
+impl Command for CatchCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        let catch = match &self.cmd {
+            CatchSubCommand::Load { name } => Catch::Load(name.clone()),
+            CatchSubCommand::Unload { name } => Catch::Unload(name.clone()),
+            CatchSubCommand::Syscall { syscalls } => Catch::Syscall(SyscallFilter::parse(syscalls)?),
+        };
+        dbg.add_catchpoint(catch)?;
+        Ok(())
+    }
+}
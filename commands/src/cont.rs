@@ -5,12 +5,22 @@ use std::error::Error;
 use clap::Clap;
 
 #[derive(Clap)]
-pub struct ContinueCommand;
+pub struct ContinueCommand {
+    /// Only resume the thread with the given id (non-stop mode)
+    #[clap(short = 't')]
+    thread: Option<u32>,
+    /// Resume every stopped thread (non-stop mode)
+    #[clap(short = 'a', conflicts_with = "thread")]
+    all: bool,
+}
 
 impl Command for ContinueCommand {
     fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
-        dbg.proceed()?;
+        match (self.thread, self.all) {
+            (Some(id), _) => dbg.proceed_threads(Some(id))?,
+            (None, true) => dbg.proceed_threads(None)?,
+            (None, false) => dbg.proceed()?,
+        }
         Ok(())
     }
 }
-
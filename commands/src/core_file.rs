@@ -0,0 +1,26 @@
+use std::error::Error;
+
+use clap::Clap;
+
+use debug::Debugger;
+
+use crate::Command;
+
+///
+/// Examine a core file in place of a process, or stop examining the
+/// current one if none is given
+///
+#[derive(Clap)]
+pub struct CoreFileCommand {
+    path: Option<String>,
+}
+
+impl Command for CoreFileCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        match &self.path {
+            Some(path) => dbg.load_core(path)?,
+            None => dbg.drop_core(),
+        }
+        Ok(())
+    }
+}
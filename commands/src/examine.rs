@@ -0,0 +1,32 @@
+use std::error::Error;
+
+use clap::Clap;
+
+use debug::Debugger;
+
+use crate::Command;
+
+///
+/// Show the memory at an address (`0x7ffe1000`), register (`$rsp`) or
+/// variable (`&counter`), as words or strings
+///
+#[derive(Clap)]
+pub struct ExamineCommand {
+    address: String,
+    /// How many words or strings to show
+    #[clap(short = 'n', default_value = "1")]
+    count: usize,
+    /// The size of each word, in bytes
+    #[clap(short = 's', default_value = "8", possible_values = &["1", "2", "4", "8"])]
+    size: usize,
+    /// Show nul terminated strings rather than words
+    #[clap(long)]
+    strings: bool,
+}
+
+impl Command for ExamineCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        dbg.examine(&self.address, self.count, self.size, self.strings)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,28 @@
+use std::error::Error;
+
+use clap::Clap;
+
+use debug::Debugger;
+use debug::signals::{parse_signal, Signal, SignalAction};
+
+use crate::Command;
+
+///
+/// Change what happens when the process receives a signal, given by name
+/// (`SIGUSR1`, `usr1`) or number, e.g. `handle SIGUSR1 nostop noprint pass`.
+/// Without any actions, shows what currently happens.
+///
+#[derive(Clap)]
+pub struct HandleCommand {
+    #[clap(parse(try_from_str = parse_signal))]
+    signal: Signal,
+    /// Any of stop, nostop, print, noprint, pass or nopass
+    actions: Vec<SignalAction>,
+}
+
+impl Command for HandleCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        dbg.handle_signal(self.signal, &self.actions);
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use std::error::Error;
+use std::str::FromStr;
+
+use clap::Clap;
+
+use debug::Debugger;
+
+use crate::Command;
+
+///
+/// List the inferiors (`inferior list`), or switch to another one
+/// (`inferior 2`)
+///
+#[derive(Clap)]
+pub struct InferiorCommand {
+    which: InferiorSpec,
+}
+
+impl Command for InferiorCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        match self.which {
+            InferiorSpec::List => dbg.list_inferiors(),
+            InferiorSpec::One(id) => dbg.select_inferior(id)?,
+        };
+        Ok(())
+    }
+}
+
+///
+/// What the `inferior` command acts on.
+///
+pub enum InferiorSpec {
+    List,
+    One(u32),
+}
+
+impl FromStr for InferiorSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "list" {
+            Ok(InferiorSpec::List)
+        } else {
+            s.parse::<u32>().map(InferiorSpec::One).map_err(|_| format!("invalid inferior id: {}", s))
+        }
+    }
+}
+
+///
+/// Add a new inferior, optionally running the given file
+///
+#[derive(Clap)]
+pub struct AddInferiorCommand {
+    #[clap(short = 'f')]
+    file: Option<String>,
+}
+
+impl Command for AddInferiorCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        dbg.add_inferior(self.file.clone());
+        Ok(())
+    }
+}
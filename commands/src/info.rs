@@ -0,0 +1,46 @@
+use std::error::Error;
+
+use clap::Clap;
+
+use debug::Debugger;
+use debug::signals::{parse_signal, Signal};
+
+use crate::Command;
+
+#[derive(Clap)]
+pub struct InfoCommand {
+    #[clap(subcommand)]
+    cmd: InfoSubCommand
+}
+
+#[derive(Clap)]
+pub enum InfoSubCommand {
+    /// List the shared libraries loaded into the process
+    Sharedlibrary,
+    /// List the threads of the process, marking the current one
+    Threads,
+    /// Show the details of the signal the current thread is stopped with
+    Signal,
+    /// Show what happens when the process receives each signal, or just
+    /// the given one
+    Signals {
+        #[clap(parse(try_from_str = parse_signal))]
+        signal: Option<Signal>,
+    },
+    /// Show the arguments, environment changes, working directory and
+    /// redirections the program is run with
+    Launch,
+}
+
+impl Command for InfoCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        match &self.cmd {
+            InfoSubCommand::Sharedlibrary => dbg.list_shared_libraries(),
+            InfoSubCommand::Threads => dbg.list_threads()?,
+            InfoSubCommand::Signal => dbg.show_signal()?,
+            InfoSubCommand::Signals { signal } => dbg.list_signals(*signal),
+            InfoSubCommand::Launch => dbg.show_launch(),
+        };
+        Ok(())
+    }
+}
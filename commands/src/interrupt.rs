@@ -0,0 +1,31 @@
+use std::error::Error;
+
+use clap::Clap;
+
+use debug::Debugger;
+
+use crate::Command;
+
+///
+/// Stop running threads (non-stop mode)
+///
+#[derive(Clap)]
+pub struct InterruptCommand {
+    /// Only stop the thread with the given id
+    #[clap(short = 't')]
+    thread: Option<u32>,
+    /// Stop every running thread
+    #[clap(short = 'a', conflicts_with = "thread")]
+    all: bool,
+}
+
+impl Command for InterruptCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        match (self.thread, self.all) {
+            (Some(id), _) => dbg.interrupt_threads(Some(id))?,
+            (None, true) => dbg.interrupt_threads(None)?,
+            (None, false) => dbg.interrupt_threads(Some(dbg.current_thread()?))?,
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,20 @@
 mod cont;
 mod run;
+mod backtrace;
 mod breakpoints;
+mod catch;
+mod core_file;
+mod examine;
+mod handle;
+mod info;
+mod inferior;
+mod interrupt;
+mod print;
+mod set;
+mod step;
+mod thread;
+mod trace;
+mod unset;
 
 extern crate clap;
 extern crate debug;
@@ -9,6 +23,7 @@ use clap::Clap;
 
 use log::error;
 
+use common::words;
 use debug::Debugger;
 use std::error::Error;
 
@@ -33,18 +48,87 @@ pub enum Sub {
     #[clap(version = "1.0")]
     Cont(cont::ContinueCommand),
     Run(run::RunCommand),
+    Start(run::StartCommand),
+    Starti(run::StartiCommand),
+    Kill(run::KillCommand),
+    Restart(run::RestartCommand),
     Break(breakpoints::BreakCommand),
+    Catch(catch::CatchCommand),
+    Info(info::InfoCommand),
+    Thread(thread::ThreadCommand),
+    Interrupt(interrupt::InterruptCommand),
+    Set(set::SetCommand),
+    Unset(unset::UnsetCommand),
+    Stepi(step::StepiCommand),
+    Inferior(inferior::InferiorCommand),
+    AddInferior(inferior::AddInferiorCommand),
+    Handle(handle::HandleCommand),
+    Print(print::PrintCommand),
+    Trace(trace::TraceCommand),
+    Backtrace(backtrace::BacktraceCommand),
+    Examine(examine::ExamineCommand),
+    CoreFile(core_file::CoreFileCommand),
+}
+
+impl Sub {
+    ///
+    /// For the commands that take the program's command line, how many
+    /// words it is and where the line itself goes. The debugger splits it
+    /// up again, so that quoting can tell redirections from arguments.
+    ///
+    fn command_line(&mut self) -> Option<(usize, &mut String)> {
+        match self {
+            Sub::Run(c) => Some((c.args.len(), &mut c.line)),
+            Sub::Start(c) => Some((c.args.len(), &mut c.line)),
+            Sub::Starti(c) => Some((c.args.len(), &mut c.line)),
+            Sub::Restart(c) => Some((c.args.len(), &mut c.line)),
+            Sub::Set(c) => c.command_line(),
+            _ => None,
+        }
+    }
 }
 
 impl Commands {
     pub fn parse_line(line: String) -> Option<Box<dyn Command>> {
-        match Commands::try_parse_from(line.split_whitespace()) {
-            Ok(commands) => {
+        let words = match words::split_words(&line) {
+            Ok(words) => words,
+            Err(e) => {
+                error!("{}", e);
+                return None;
+            }
+        };
+        match Commands::try_parse_from(words.iter().map(|word| word.text.as_str())) {
+            Ok(mut commands) => {
+                if let Some((count, args)) = commands.cmd.command_line() {
+                    if count > 0 {
+                        *args = line[words[words.len() - count].start..].to_string();
+                    }
+                }
+
                 use Sub::*;
                 match commands.cmd {
                     Cont(c) => Some(Box::new(c)),
                     Run(c) => Some(Box::new(c)),
-                    Break(c) => Some(Box::new(c))
+                    Start(c) => Some(Box::new(c)),
+                    Starti(c) => Some(Box::new(c)),
+                    Kill(c) => Some(Box::new(c)),
+                    Restart(c) => Some(Box::new(c)),
+                    Break(c) => Some(Box::new(c)),
+                    Catch(c) => Some(Box::new(c)),
+                    Info(c) => Some(Box::new(c)),
+                    Thread(c) => Some(Box::new(c)),
+                    Interrupt(c) => Some(Box::new(c)),
+                    Set(c) => Some(Box::new(c)),
+                    Unset(c) => Some(Box::new(c)),
+                    Stepi(c) => Some(Box::new(c)),
+                    Inferior(c) => Some(Box::new(c)),
+                    AddInferior(c) => Some(Box::new(c)),
+                    Handle(c) => Some(Box::new(c)),
+                    Print(c) => Some(Box::new(c)),
+                    Trace(c) => Some(Box::new(c)),
+                    Backtrace(c) => Some(Box::new(c)),
+                    Examine(c) => Some(Box::new(c)),
+                    CoreFile(c) => Some(Box::new(c)),
                 }
             }
             Err(e) => {
@@ -53,4 +137,4 @@ impl Commands {
             }
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,24 @@
+use std::error::Error;
+
+use clap::Clap;
+
+use debug::Debugger;
+
+use crate::Command;
+
+///
+/// Print a register of the current thread (`$rip`, `$xmm0`), the details
+/// of the signal it is stopped with (`$_siginfo`, `$_siginfo.si_addr`), or
+/// a variable (`counter`)
+///
+#[derive(Clap)]
+pub struct PrintCommand {
+    value: String,
+}
+
+impl Command for PrintCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        dbg.print_value(&self.value)?;
+        Ok(())
+    }
+}
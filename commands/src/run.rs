@@ -1,20 +1,124 @@
 use std::error::Error;
 
-use clap::Clap;
+use clap::{AppSettings, Clap};
 
 use debug::Debugger;
 
 use crate::Command;
 
+///
+/// Run the program, with the given arguments and redirections
+/// (`< in.txt`, `> out.txt`, `2> err.txt`), or the ones it was last run
+/// with if none are given
+///
 #[derive(Clap)]
+#[clap(setting = AppSettings::TrailingVarArg, setting = AppSettings::AllowLeadingHyphen)]
 pub struct RunCommand {
-    #[clap(short = 'a')]
-    args: Vec<String>
+    #[clap(allow_hyphen_values = true)]
+    pub(crate) args: Vec<String>,
+    /// The arguments as they were typed, quotes and all.
+    #[clap(skip)]
+    pub(crate) line: String,
 }
 
 impl Command for RunCommand {
     fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
-        dbg.run(self.args.clone(), vec![])?;
+        if !self.args.is_empty() {
+            dbg.set_args(&self.line)?;
+        }
+        dbg.run()?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+///
+/// Run the program up to the start of `main`, with the given arguments
+/// and redirections, or the ones it was last run with if none are given
+///
+#[derive(Clap)]
+#[clap(setting = AppSettings::TrailingVarArg, setting = AppSettings::AllowLeadingHyphen)]
+pub struct StartCommand {
+    #[clap(allow_hyphen_values = true)]
+    pub(crate) args: Vec<String>,
+    /// The arguments as they were typed, quotes and all.
+    #[clap(skip)]
+    pub(crate) line: String,
+}
+
+impl Command for StartCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        if !self.args.is_empty() {
+            dbg.set_args(&self.line)?;
+        }
+        dbg.start()?;
+        Ok(())
+    }
+}
+
+///
+/// Run the program, stopping at its very first instruction, with the given
+/// arguments and redirections, or the ones it was last run with if none
+/// are given
+///
+#[derive(Clap)]
+#[clap(setting = AppSettings::TrailingVarArg, setting = AppSettings::AllowLeadingHyphen)]
+pub struct StartiCommand {
+    /// Stop at the program's entry point instead, once the dynamic linker
+    /// has loaded it
+    #[clap(short = 'e', long)]
+    entry: bool,
+    #[clap(allow_hyphen_values = true)]
+    pub(crate) args: Vec<String>,
+    /// The arguments as they were typed, quotes and all.
+    #[clap(skip)]
+    pub(crate) line: String,
+}
+
+impl Command for StartiCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        if !self.args.is_empty() {
+            dbg.set_args(&self.line)?;
+        }
+        dbg.start_instruction(self.entry)?;
+        Ok(())
+    }
+}
+
+///
+/// Kill the program's process
+///
+#[derive(Clap)]
+pub struct KillCommand {}
+
+impl Command for KillCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        dbg.kill()?;
+        Ok(())
+    }
+}
+
+///
+/// Kill the program's process, if it has one, and run it again, with the
+/// given arguments and redirections, or the ones it was last run with if
+/// none are given. Breakpoints, catchpoints, syscall tracing and signal
+/// handling carry over to the new process
+///
+#[derive(Clap)]
+#[clap(setting = AppSettings::TrailingVarArg, setting = AppSettings::AllowLeadingHyphen)]
+pub struct RestartCommand {
+    #[clap(allow_hyphen_values = true)]
+    pub(crate) args: Vec<String>,
+    /// The arguments as they were typed, quotes and all.
+    #[clap(skip)]
+    pub(crate) line: String,
+}
+
+impl Command for RestartCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        if !self.args.is_empty() {
+            dbg.set_args(&self.line)?;
+        }
+        dbg.restart()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::{AppSettings, Clap};
+
+use debug::Debugger;
+use debug::launch::{parse_limit, Limit};
+use debug::settings::{FollowForkMode, SchedulerLocking};
+
+use crate::Command;
+
+#[derive(Clap)]
+pub struct SetCommand {
+    #[clap(subcommand)]
+    cmd: SetSubCommand
+}
+
+#[derive(Clap)]
+pub enum SetSubCommand {
+    /// Only stop the thread that hit an event, leaving the others running
+    /// (`on`), or stop every thread together (`off`). Can only be changed
+    /// while there is no process.
+    NonStop {
+        #[clap(parse(try_from_str = parse_on_off))]
+        value: bool,
+    },
+    /// Keep the other threads stopped while the current thread runs
+    /// (`on`), only while it is being stepped (`step`), or never (`off`).
+    SchedulerLocking {
+        value: SchedulerLocking,
+    },
+    /// Keep debugging the `parent` or the `child` when the process forks
+    FollowForkMode {
+        value: FollowForkMode,
+    },
+    /// Detach from the process that isn't followed after a fork (`on`),
+    /// or hold it stopped (`off`). While it's on, processes stop on every
+    /// syscall when any are caught or traced, which is slower, since they
+    /// can't be filtered without failing the calls in detached children.
+    /// A filtered program can't gain privileges by running setuid programs
+    DetachOnFork {
+        #[clap(parse(try_from_str = parse_on_off))]
+        value: bool,
+    },
+    /// Kill the processes we launched when the debugger exits (`on`), or
+    /// detach from them (`off`). Processes attached to are always detached
+    /// from
+    KillOnExit {
+        #[clap(parse(try_from_str = parse_on_off))]
+        value: bool,
+    },
+    /// Set the arguments and redirections the program is run with, e.g.
+    /// `-v input.txt > out.txt`, or clear them if none are given
+    #[clap(setting = AppSettings::TrailingVarArg, setting = AppSettings::AllowLeadingHyphen)]
+    Args {
+        #[clap(allow_hyphen_values = true)]
+        args: Vec<String>,
+        /// The arguments as they were typed, quotes and all.
+        #[clap(skip)]
+        line: String,
+    },
+    /// Set a variable in the environment the program is run with, on top
+    /// of the debugger's own, as `NAME=VALUE` or `NAME VALUE`
+    Env {
+        name: String,
+        value: Option<String>,
+    },
+    /// Set the directory the program is run in, or go back to the
+    /// debugger's own if none is given. Files the program's streams are
+    /// redirected to are relative to it
+    Cwd {
+        dir: Option<PathBuf>,
+    },
+    /// Run the program with address space randomization turned off (`on`),
+    /// so its addresses stay the same between runs, or left on (`off`)
+    DisableRandomization {
+        #[clap(parse(try_from_str = parse_on_off))]
+        value: bool,
+    },
+    /// Set a resource limit the program is run with: `core`, `stack` or
+    /// `as` (address space), to a size in bytes (with an optional K, M or G
+    /// suffix) or `unlimited`
+    Limit {
+        limit: Limit,
+        #[clap(parse(try_from_str = parse_limit))]
+        value: u64,
+    },
+}
+
+impl SetCommand {
+    ///
+    /// How many words the program's command line is, for `set args`, and
+    /// where the line itself goes.
+    ///
+    pub(crate) fn command_line(&mut self) -> Option<(usize, &mut String)> {
+        match &mut self.cmd {
+            SetSubCommand::Args { args, line } => Some((args.len(), line)),
+            _ => None,
+        }
+    }
+}
+
+impl Command for SetCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        match &self.cmd {
+            SetSubCommand::NonStop { value } => dbg.set_non_stop(*value)?,
+            SetSubCommand::SchedulerLocking { value } => dbg.set_scheduler_locking(*value),
+            SetSubCommand::FollowForkMode { value } => dbg.set_follow_fork_mode(*value),
+            SetSubCommand::DetachOnFork { value } => dbg.set_detach_on_fork(*value),
+            SetSubCommand::KillOnExit { value } => dbg.set_kill_on_exit(*value),
+            SetSubCommand::Args { line, .. } => dbg.set_args(line)?,
+            SetSubCommand::Env { name, value } => match (name.split_once('='), value) {
+                (Some((name, value)), None) => dbg.set_env(name, value),
+                (None, Some(value)) => dbg.set_env(name, value),
+                _ => return Err(String::from("expected NAME=VALUE or NAME VALUE").into()),
+            },
+            SetSubCommand::Cwd { dir } => dbg.set_cwd(dir.clone()),
+            SetSubCommand::DisableRandomization { value } => dbg.set_disable_randomization(*value),
+            SetSubCommand::Limit { limit, value } => dbg.set_limit(*limit, Some(*value)),
+        };
+        Ok(())
+    }
+}
+
+///
+/// Parses the value of a setting that can be turned on or off.
+///
+pub(crate) fn parse_on_off(s: &str) -> Result<bool, String> {
+    match s {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        _ => Err(format!("expected `on` or `off`, found {}", s)),
+    }
+}
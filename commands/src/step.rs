@@ -0,0 +1,18 @@
+use crate::Command;
+use debug::Debugger;
+use std::error::Error;
+
+use clap::Clap;
+
+///
+/// Step the current thread by a single instruction
+///
+#[derive(Clap)]
+pub struct StepiCommand;
+
+impl Command for StepiCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        dbg.step_instruction()?;
+        Ok(())
+    }
+}
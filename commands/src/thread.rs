@@ -0,0 +1,22 @@
+use std::error::Error;
+
+use clap::Clap;
+
+use debug::Debugger;
+
+use crate::Command;
+
+///
+/// Switch the thread that registers are read from
+///
+#[derive(Clap)]
+pub struct ThreadCommand {
+    id: u32,
+}
+
+impl Command for ThreadCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        dbg.select_thread(self.id)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use std::error::Error;
+use std::path::PathBuf;
+
+use clap::Clap;
+
+use debug::Debugger;
+use debug::syscalls::SyscallFilter;
+
+use crate::Command;
+
+///
+/// Log events as the process runs, without stopping it
+///
+#[derive(Clap)]
+pub struct TraceCommand {
+    #[clap(subcommand)]
+    cmd: TraceSubCommand
+}
+
+#[derive(Clap)]
+pub enum TraceSubCommand {
+    /// Log system calls with their arguments and what they return,
+    /// optionally only the given calls, by name or number, or groups of
+    /// them (as group:NAME). With detach-on-fork off, a program run from
+    /// then on only stops on the calls traced, but can't be detached from,
+    /// as they would fail, and can't gain privileges by running setuid
+    /// programs
+    Syscalls {
+        /// Write the calls to the given file instead of the console
+        #[clap(short = 'o', long)]
+        output: Option<PathBuf>,
+        syscalls: Vec<String>,
+    },
+    /// Stop logging system calls
+    Off,
+}
+
+impl Command for TraceCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        match &self.cmd {
+            TraceSubCommand::Syscalls { output, syscalls } => {
+                dbg.trace_syscalls(SyscallFilter::parse(syscalls)?, output.clone())?
+            }
+            TraceSubCommand::Off => dbg.stop_tracing_syscalls()?,
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use std::error::Error;
+
+use clap::Clap;
+
+use debug::Debugger;
+use debug::launch::Limit;
+
+use crate::Command;
+
+#[derive(Clap)]
+pub struct UnsetCommand {
+    #[clap(subcommand)]
+    cmd: UnsetSubCommand
+}
+
+#[derive(Clap)]
+pub enum UnsetSubCommand {
+    /// Remove a variable from the environment the program is run with,
+    /// even if the debugger has it set
+    Env {
+        name: String,
+    },
+    /// Go back to the resource limit inherited from the debugger: `core`,
+    /// `stack` or `as`
+    Limit {
+        limit: Limit,
+    },
+}
+
+impl Command for UnsetCommand {
+    fn run(&self, dbg: &mut Debugger) -> Result<(), Box<dyn Error>> {
+        match &self.cmd {
+            UnsetSubCommand::Env { name } => dbg.unset_env(name),
+            UnsetSubCommand::Limit { limit } => dbg.set_limit(*limit, None),
+        }
+        Ok(())
+    }
+}
@@ -9,4 +9,13 @@ pub struct CommandLine {
 
     #[clap(short, long, about = "The PID of the process to attach to.")]
     pub pid: Option<i32>,
+
+    #[clap(long, conflicts_with_all = &["pid", "wait-for"], about = "The name of a running process to attach to.")]
+    pub attach_name: Option<String>,
+
+    #[clap(long, conflicts_with = "pid", about = "The name of a process to attach to as soon as it starts.")]
+    pub wait_for: Option<String>,
+
+    #[clap(short, long, conflicts_with_all = &["pid", "attach-name", "wait-for"], about = "A core file to examine.")]
+    pub core: Option<String>,
 }
@@ -2,18 +2,26 @@ use crate::cli::CommandLine;
 
 pub struct Config {
     pub should_attach: bool,
+    /// Whether to wait for a process with the given name to start, and
+    /// attach to it.
     pub await_start: bool,
     pub pid: Option<i32>,
+    /// The name of the process to wait for.
+    pub name: Option<String>,
     pub file: Option<String>,
+    /// A core file to examine, in place of a running process.
+    pub core: Option<String>,
 }
 
 impl From<CommandLine> for Config {
     fn from(c: CommandLine) -> Self {
         Self {
             should_attach: c.pid.is_some(),
-            await_start: c.file.is_some() && c.pid.is_none(),
+            await_start: c.wait_for.is_some(),
+            name: c.wait_for,
             file: c.file,
             pid: c.pid,
+            core: c.core,
         }
     }
-}
\ No newline at end of file
+}
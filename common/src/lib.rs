@@ -1,4 +1,5 @@
 extern crate clap;
 
 pub mod cli;
-pub mod config;
\ No newline at end of file
+pub mod config;
+pub mod words;
\ No newline at end of file
@@ -0,0 +1,129 @@
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+///
+/// A word of a command line, with its quotes and escapes removed.
+///
+pub struct Word {
+    pub text: String,
+    /// Where the word starts in the line, in bytes.
+    pub start: usize,
+}
+
+///
+/// A piece of a program's command line, split up the way a shell would.
+///
+#[derive(Debug, PartialEq, Eq)]
+pub enum Token {
+    Word(String),
+    /// A redirection of one of the standard streams, which is only one
+    /// when it isn't quoted: `<`, `>`, `>>`, `2>` or `2>>`.
+    Redirect(&'static str),
+}
+
+///
+/// Splits a command line into words the way a shell would: at whitespace,
+/// except within single or double quotes, or where it's escaped with a
+/// backslash.
+///
+pub fn split_words(line: &str) -> Result<Vec<Word>, String> {
+    Ok(scan(line, false)?.into_iter()
+        .filter_map(|(start, token)| match token {
+            Token::Word(text) => Some(Word { text, start }),
+            Token::Redirect(_) => None,
+        })
+        .collect())
+}
+
+///
+/// Splits a program's command line into words and redirections, as with
+/// `split_words`. Redirections needn't be separated from their file, or
+/// from the words around them, e.g. `-v>out.txt`.
+///
+pub fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    Ok(scan(line, true)?.into_iter().map(|(_, token)| token).collect())
+}
+
+fn scan(line: &str, redirections: bool) -> Result<Vec<(usize, Token)>, String> {
+    let mut tokens = Vec::new();
+    let mut word: Option<(usize, String)> = None;
+    // whether any of the current word was quoted or escaped
+    let mut quoted = false;
+    let mut chars = line.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                tokens.extend(word.take().map(|(start, text)| (start, Token::Word(text))));
+                quoted = false;
+            }
+            '<' | '>' if redirections => {
+                // a 2 on its own, right before the operator, says which
+                // stream it's for
+                let stderr = c == '>' && !quoted && word.as_ref().is_some_and(|(_, text)| text == "2");
+                let start = match word.take() {
+                    Some((start, _)) if stderr => start,
+                    Some((start, text)) => {
+                        tokens.push((start, Token::Word(text)));
+                        idx
+                    }
+                    None => idx,
+                };
+                quoted = false;
+                let append = c == '>' && chars.next_if(|(_, next)| *next == '>').is_some();
+                let op = match (c, stderr, append) {
+                    ('<', _, _) => "<",
+                    (_, false, false) => ">",
+                    (_, false, true) => ">>",
+                    (_, true, false) => "2>",
+                    (_, true, true) => "2>>",
+                };
+                tokens.push((start, Token::Redirect(op)));
+            }
+            '\\' => {
+                quoted = true;
+                word.get_or_insert_with(|| (idx, String::new())).1.extend(chars.next().map(|(_, c)| c));
+            }
+            '\'' => {
+                quoted = true;
+                let word = &mut word.get_or_insert_with(|| (idx, String::new())).1;
+                loop {
+                    match chars.next() {
+                        Some((_, '\'')) => break,
+                        Some((_, c)) => word.push(c),
+                        None => return Err(String::from("unterminated single quote")),
+                    }
+                }
+            }
+            '"' => {
+                quoted = true;
+                let word = &mut word.get_or_insert_with(|| (idx, String::new())).1;
+                double_quoted(&mut chars, word)?;
+            }
+            c => word.get_or_insert_with(|| (idx, String::new())).1.push(c),
+        }
+    }
+    tokens.extend(word.map(|(start, text)| (start, Token::Word(text))));
+    Ok(tokens)
+}
+
+///
+/// Reads the rest of a double quoted string onto the end of a word.
+///
+fn double_quoted(chars: &mut Peekable<CharIndices>, word: &mut String) -> Result<(), String> {
+    loop {
+        match chars.next().map(|(_, c)| c) {
+            Some('"') => return Ok(()),
+            // only the characters special within double quotes can be escaped
+            Some('\\') => match chars.next().map(|(_, c)| c) {
+                Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                Some(c) => {
+                    word.push('\\');
+                    word.push(c);
+                }
+                None => return Err(String::from("unterminated double quote")),
+            },
+            Some(c) => word.push(c),
+            None => return Err(String::from("unterminated double quote")),
+        }
+    }
+}
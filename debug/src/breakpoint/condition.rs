@@ -0,0 +1,167 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::error::{DebugError, Reason};
+use crate::registers;
+use crate::trace::Registers;
+
+///
+/// The comparison made by a breakpoint condition.
+///
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    const ALL: [(&'static str, Op); 6] = [
+        ("==", Op::Eq),
+        ("!=", Op::Ne),
+        ("<=", Op::Le),
+        (">=", Op::Ge),
+        ("<", Op::Lt),
+        (">", Op::Gt),
+    ];
+
+    fn apply(self, lhs: u64, rhs: u64) -> bool {
+        match self {
+            Op::Eq => lhs == rhs,
+            Op::Ne => lhs != rhs,
+            Op::Lt => lhs < rhs,
+            Op::Le => lhs <= rhs,
+            Op::Gt => lhs > rhs,
+            Op::Ge => lhs >= rhs,
+        }
+    }
+}
+
+impl Display for Op {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (symbol, _) = Op::ALL.iter().find(|(_, op)| op == self).unwrap();
+        write!(f, "{}", symbol)
+    }
+}
+
+///
+/// A condition that must hold for a breakpoint to stop the process,
+/// comparing a register against a value, e.g. `$rdi == 0x10`.
+///
+#[derive(Clone)]
+pub struct Condition {
+    register: String,
+    op: Op,
+    value: u64,
+}
+
+impl Condition {
+    ///
+    /// Evaluates the condition against the registers of the stopped process.
+    ///
+    pub fn holds(&self, regs: &Registers) -> bool {
+        // the register name is checked on parse
+        let lhs = registers::get(regs, &self.register).unwrap_or_default();
+        self.op.apply(lhs, self.value)
+    }
+}
+
+impl FromStr for Condition {
+    type Err = DebugError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || DebugError::InvalidOperation(Reason::InvalidCondition(s.to_string()));
+
+        let (symbol, op) = Op::ALL.iter()
+            .find(|(symbol, _)| s.contains(symbol))
+            .ok_or_else(invalid)?;
+        let (lhs, rhs) = s.split_at(s.find(symbol).unwrap());
+        let rhs = &rhs[symbol.len()..];
+
+        let register = lhs.trim().trim_start_matches('$').to_string();
+        if !registers::exists(&register) {
+            return Err(invalid());
+        }
+
+        let rhs = rhs.trim();
+        let value = if let Some(hex) = rhs.strip_prefix("0x") {
+            u64::from_str_radix(hex, 16)
+        } else if let Some(negative) = rhs.strip_prefix('-') {
+            negative.parse::<i64>().map(|n| n.wrapping_neg() as u64)
+        } else {
+            rhs.parse::<u64>()
+        }.map_err(|_| invalid())?;
+
+        Ok(Self {
+            register,
+            op: *op,
+            value,
+        })
+    }
+}
+
+impl Display for Condition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "${} {} 0x{:x}", self.register, self.op, self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regs(rdi: u64) -> Registers {
+        let mut regs: Registers = unsafe { std::mem::zeroed() };
+        regs.rdi = rdi;
+        regs
+    }
+
+    fn parse(s: &str) -> Condition {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn operators() {
+        let cases = [
+            ("$rdi == 5", [false, true, false]),
+            ("$rdi != 5", [true, false, true]),
+            ("$rdi < 5", [true, false, false]),
+            ("$rdi <= 5", [true, true, false]),
+            ("$rdi > 5", [false, false, true]),
+            ("$rdi >= 5", [false, true, true]),
+        ];
+        for (condition, expected) in cases {
+            let condition = parse(condition);
+            let holds: Vec<bool> = [4, 5, 6].iter().map(|rdi| condition.holds(&regs(*rdi))).collect();
+            assert_eq!(holds, expected, "{}", condition);
+        }
+    }
+
+    #[test]
+    fn values() {
+        assert_eq!(parse("$rdi==0x1f").to_string(), "$rdi == 0x1f");
+        assert_eq!(parse("rdi >= 31").to_string(), "$rdi >= 0x1f");
+        assert_eq!(parse("$rdi == -1").to_string(), "$rdi == 0xffffffffffffffff");
+        assert!(parse("$rdi == -1").holds(&regs(u64::MAX)));
+    }
+
+    #[test]
+    fn unknown_register() {
+        assert!("$foo == 1".parse::<Condition>().is_err());
+        assert!("1 == $rdi".parse::<Condition>().is_err());
+        assert!("== 1".parse::<Condition>().is_err());
+    }
+
+    #[test]
+    fn malformed() {
+        for condition in ["", "$rdi", "$rdi = 1", "$rdi => 1", "$rdi ==", "$rdi == x", "$rdi == 0xg",
+                          "$rdi == 1 2", "$rdi == 18446744073709551616"] {
+            let err = condition.parse::<Condition>().err();
+            assert!(matches!(err, Some(DebugError::InvalidOperation(Reason::InvalidCondition(_)))), "{:?}", condition);
+        }
+    }
+}
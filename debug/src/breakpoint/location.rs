@@ -0,0 +1,72 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::module::Module;
+
+///
+/// Where a breakpoint should be placed, as given by the user. Locations are
+/// resolved into addresses once the module they refer to has been loaded.
+///
+#[derive(Clone)]
+pub enum Location {
+    /// An absolute address, e.g. `0x401136` or `*0x401136`.
+    Address(u64),
+    /// The start of a function, e.g. `main`.
+    Symbol(String),
+    /// The first instruction for a line of source, e.g. `loop.c:12`.
+    Line { file: String, line: u64 },
+}
+
+impl Location {
+    ///
+    /// Tries to resolve the location against a single loaded module.
+    ///
+    pub fn resolve(&self, module: &Module) -> Option<u64> {
+        match self {
+            Location::Address(addr) if module.contains(*addr) => Some(*addr),
+            Location::Address(_) => None,
+            Location::Symbol(name) => module.symbol(name),
+            Location::Line { file, line } => module.line_address(file, *line),
+        }
+    }
+}
+
+impl FromStr for Location {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(String::from("empty location"));
+        }
+
+        let address = s.strip_prefix('*').unwrap_or(s);
+        if let Some(hex) = address.strip_prefix("0x") {
+            return u64::from_str_radix(hex, 16)
+                .map(Location::Address)
+                .map_err(|_| format!("invalid address: {}", s));
+        }
+        if let Ok(addr) = address.parse::<u64>() {
+            return Ok(Location::Address(addr));
+        }
+
+        if let Some((file, line)) = s.rsplit_once(':') {
+            if let Ok(line) = line.parse::<u64>() {
+                return Ok(Location::Line { file: file.to_string(), line });
+            }
+        }
+
+        Ok(Location::Symbol(s.to_string()))
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Address(addr) => write!(f, "*0x{:x}", addr),
+            Location::Symbol(name) => write!(f, "{}", name),
+            Location::Line { file, line } => write!(f, "{}:{}", file, line),
+        }
+    }
+}
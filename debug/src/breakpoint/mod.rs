@@ -0,0 +1,392 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use crate::error::{DebugError, Reason, Result};
+use crate::inferior::Inferiors;
+use crate::module::Module;
+use crate::process::Process;
+use crate::syscalls::SyscallFilter;
+
+use self::condition::Condition;
+use self::location::Location;
+
+pub mod condition;
+pub mod location;
+pub mod site;
+
+///
+/// What a breakpoint is watching for.
+///
+pub enum Kind {
+    /// Stops the process when it executes the instruction at `location`.
+    /// The location is resolved separately in each inferior, keyed by their
+    /// identifiers, and is pending in those where it hasn't been found in
+    /// any of the loaded modules yet.
+    Breakpoint { location: Location, addrs: BTreeMap<u32, u64> },
+    /// Stops the process when an event happens to it, rather than at an address.
+    Catchpoint(Catch),
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Kind::Breakpoint { .. } => write!(f, "breakpoint"),
+            Kind::Catchpoint(_) => write!(f, "catchpoint"),
+        }
+    }
+}
+
+///
+/// The events a catchpoint can stop on.
+///
+pub enum Catch {
+    /// A shared library being loaded, optionally only those whose
+    /// path contains the given name.
+    Load(Option<String>),
+    /// A shared library being unloaded, optionally only those whose
+    /// path contains the given name.
+    Unload(Option<String>),
+    /// A thread entering or returning from one of the given system calls.
+    Syscall(SyscallFilter),
+}
+
+impl Catch {
+    ///
+    /// Whether the catchpoint is interested in the given library being
+    /// loaded (or unloaded, if `loaded` is false).
+    ///
+    pub fn matches_library(&self, path: &str, loaded: bool) -> bool {
+        match self {
+            Catch::Load(name) if loaded => name.as_ref().is_none_or(|n| path.contains(n.as_str())),
+            Catch::Unload(name) if !loaded => name.as_ref().is_none_or(|n| path.contains(n.as_str())),
+            _ => false,
+        }
+    }
+
+    ///
+    /// Whether the catchpoint is interested in the system call with the
+    /// given number.
+    ///
+    pub fn matches_syscall(&self, number: u64) -> bool {
+        match self {
+            Catch::Syscall(filter) => filter.matches(number),
+            _ => false,
+        }
+    }
+}
+
+impl Display for Catch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Catch::Load(None) => write!(f, "load of library"),
+            Catch::Load(Some(name)) => write!(f, "load of library matching {}", name),
+            Catch::Unload(None) => write!(f, "unload of library"),
+            Catch::Unload(Some(name)) => write!(f, "unload of library matching {}", name),
+            Catch::Syscall(filter) => write!(f, "{}", filter),
+        }
+    }
+}
+
+///
+/// Encapsulates metadata about a breakpoint. It does not have ownership
+/// over the process in which it is relevant, and that relationship
+/// should be managed at a higher level (in the `Debugger`)
+///
+/// The breakpoint instruction itself is owned by the process' breakpoint
+/// sites, so several breakpoints can share the same address safely.
+///
+pub struct Breakpoint {
+    /// The stable identifier of the breakpoint, assigned by the `Breakpoints` table.
+    pub id: u32,
+    /// What the breakpoint is watching for.
+    pub kind: Kind,
+    /// If set, the breakpoint only stops the process while this holds.
+    pub condition: Option<Condition>,
+    /// If set, the breakpoint only stops this thread, by the identifier
+    /// shown to the user, and other threads pass over it.
+    pub thread: Option<u32>,
+    /// If set, the breakpoint only applies to this inferior, and isn't
+    /// inserted into the others at all.
+    pub inferior: Option<u32>,
+    /// The number of times the breakpoint has stopped the process.
+    pub hits: u64,
+    /// Whether or not this breakpoint is enabled.
+    pub enabled: bool,
+    /// Whether the breakpoint is deleted once it has stopped the process.
+    pub temporary: bool,
+}
+
+impl Breakpoint {
+    pub fn new(location: Location) -> Self {
+        Self::with_kind(Kind::Breakpoint { location, addrs: BTreeMap::new() })
+    }
+
+    pub fn catchpoint(catch: Catch) -> Self {
+        Self::with_kind(Kind::Catchpoint(catch))
+    }
+
+    fn with_kind(kind: Kind) -> Self {
+        Self {
+            id: 0,
+            kind,
+            condition: None,
+            thread: None,
+            inferior: None,
+            hits: 0,
+            enabled: false,
+            temporary: false,
+        }
+    }
+
+    ///
+    /// The address of the breakpoint in the given inferior's process, if it
+    /// has been resolved there.
+    ///
+    pub fn addr(&self, inferior: u32) -> Option<u64> {
+        match &self.kind {
+            Kind::Breakpoint { addrs, .. } => addrs.get(&inferior).copied(),
+            Kind::Catchpoint(_) => None,
+        }
+    }
+
+    ///
+    /// Every inferior the breakpoint has been resolved in, alongside its
+    /// address there.
+    ///
+    pub fn addrs(&self) -> Vec<(u32, u64)> {
+        match &self.kind {
+            Kind::Breakpoint { addrs, .. } => addrs.iter().map(|(id, addr)| (*id, *addr)).collect(),
+            Kind::Catchpoint(_) => Vec::new(),
+        }
+    }
+
+    ///
+    /// Whether the breakpoint applies to the given inferior.
+    ///
+    pub fn applies_to(&self, inferior: u32) -> bool {
+        self.inferior.is_none_or(|id| id == inferior)
+    }
+
+    ///
+    /// Describes what the breakpoint is watching for, e.g. the location it
+    /// was asked to be placed at.
+    ///
+    pub fn what(&self) -> String {
+        match &self.kind {
+            Kind::Breakpoint { location, .. } => location.to_string(),
+            Kind::Catchpoint(catch) => catch.to_string(),
+        }
+    }
+
+    ///
+    /// Whether the breakpoint is waiting for its location to be loaded.
+    ///
+    pub fn is_pending(&self) -> bool {
+        match &self.kind {
+            Kind::Breakpoint { addrs, .. } => addrs.is_empty(),
+            Kind::Catchpoint(_) => false,
+        }
+    }
+
+    ///
+    /// Enables the breakpoint. Wherever it has been resolved, this takes a
+    /// reference on the breakpoint site at its address in the process. If
+    /// that fails in one of them, the sites already taken in the others are
+    /// released again, leaving the breakpoint disabled everywhere.
+    ///
+    pub fn enable(&mut self, inferiors: &mut Inferiors) -> Result<()> {
+        if self.enabled {
+            return Ok(());
+        }
+
+        let addrs = self.addrs();
+        for (done, (id, addr)) in addrs.iter().enumerate() {
+            let inserted = match inferiors.get_mut(*id)?.process.as_mut() {
+                Some(process) => process.insert_site(*addr),
+                None => Ok(()),
+            };
+            if let Err(e) = inserted {
+                for (id, addr) in &addrs[..done] {
+                    if let Ok(Some(process)) = inferiors.get_mut(*id).map(|inferior| inferior.process.as_mut()) {
+                        // nothing more can be done about a site that won't
+                        // come out, and the original error matters more
+                        let _ = process.remove_site(*addr);
+                    }
+                }
+                return Err(e);
+            }
+        }
+        self.enabled = true;
+        Ok(())
+    }
+
+    ///
+    /// Disables the breakpoint. Wherever it has been resolved, this releases
+    /// its reference on the breakpoint site in the process.
+    ///
+    pub fn disable(&mut self, inferiors: &mut Inferiors) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        for (id, addr) in self.addrs() {
+            if let Some(process) = inferiors.get_mut(id)?.process.as_mut() {
+                process.remove_site(addr)?;
+            }
+        }
+        self.enabled = false;
+        Ok(())
+    }
+
+    ///
+    /// Tries to resolve a breakpoint which is pending in the given inferior
+    /// against a module newly loaded into it, inserting it into the process
+    /// if it is enabled. Returns whether the breakpoint was resolved.
+    ///
+    pub fn resolve(&mut self, inferior: u32, module: &Module, process: &mut Process) -> Result<bool> {
+        if !self.applies_to(inferior) {
+            return Ok(false);
+        }
+
+        let enabled = self.enabled;
+        match &mut self.kind {
+            Kind::Breakpoint { location, addrs } if !addrs.contains_key(&inferior) => {
+                let addr = match location.resolve(module) {
+                    Some(addr) => addr,
+                    None => return Ok(false),
+                };
+
+                if enabled {
+                    process.insert_site(addr)?;
+                }
+                addrs.insert(inferior, addr);
+                Ok(true)
+            }
+            _ => Ok(false)
+        }
+    }
+
+    ///
+    /// Forgets the address the breakpoint resolved to in the given inferior,
+    /// making it pending there again, e.g. once its process has gone away.
+    /// It does not touch the process itself.
+    ///
+    pub fn unresolve(&mut self, inferior: u32) {
+        if let Kind::Breakpoint { addrs, .. } = &mut self.kind {
+            addrs.remove(&inferior);
+        }
+    }
+
+    ///
+    /// Makes the breakpoint pending again in the given inferior if it was
+    /// resolved into a module which has been unloaded, occupying the given
+    /// range. Returns whether it was.
+    ///
+    pub fn unload(&mut self, inferior: u32, start: u64, end: u64) -> bool {
+        match self.addr(inferior) {
+            Some(addr) if start <= addr && addr < end => {
+                self.unresolve(inferior);
+                true
+            }
+            _ => false
+        }
+    }
+
+    ///
+    /// Resolves the breakpoint in a new inferior to the same address as in
+    /// another, for when the new one's process is a fork of the other's, and
+    /// so already has the breakpoint inserted.
+    ///
+    pub fn copy_resolution(&mut self, from: u32, to: u32) {
+        if let Kind::Breakpoint { addrs, .. } = &mut self.kind {
+            if let Some(addr) = addrs.get(&from).copied() {
+                addrs.insert(to, addr);
+            }
+        }
+    }
+}
+
+///
+/// The table of every breakpoint known to the debugger. Identifiers are
+/// handed out in increasing order and are never reused, so they stay valid
+/// for as long as the breakpoint exists.
+///
+#[derive(Default)]
+pub struct Breakpoints {
+    last_id: u32,
+    entries: BTreeMap<u32, Breakpoint>,
+}
+
+impl Breakpoints {
+    ///
+    /// Adds a breakpoint to the table, returning its newly assigned identifier.
+    ///
+    pub fn add(&mut self, mut bp: Breakpoint) -> u32 {
+        self.last_id += 1;
+        bp.id = self.last_id;
+        self.entries.insert(bp.id, bp);
+        self.last_id
+    }
+
+    ///
+    /// Removes a breakpoint from the table, returning it.
+    ///
+    pub fn remove(&mut self, id: u32) -> Result<Breakpoint> {
+        self.entries.remove(&id).ok_or(DebugError::InvalidOperation(Reason::NoSuchBreakpoint))
+    }
+
+    pub fn get(&self, id: u32) -> Result<&Breakpoint> {
+        self.entries.get(&id).ok_or(DebugError::InvalidOperation(Reason::NoSuchBreakpoint))
+    }
+
+    pub fn get_mut(&mut self, id: u32) -> Result<&mut Breakpoint> {
+        self.entries.get_mut(&id).ok_or(DebugError::InvalidOperation(Reason::NoSuchBreakpoint))
+    }
+
+    ///
+    /// Iterates over every breakpoint, in order of identifier.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item=&Breakpoint> {
+        self.entries.values()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item=&mut Breakpoint> {
+        self.entries.values_mut()
+    }
+
+    ///
+    /// The identifiers of every breakpoint, in increasing order.
+    ///
+    pub fn ids(&self) -> Vec<u32> {
+        self.entries.keys().copied().collect()
+    }
+
+    ///
+    /// Iterates over the enabled catchpoints which apply to the given inferior.
+    ///
+    pub fn catchpoints_mut(&mut self, inferior: u32) -> impl Iterator<Item=&mut Breakpoint> {
+        self.entries.values_mut()
+            .filter(move |bp| bp.enabled && bp.applies_to(inferior) && matches!(bp.kind, Kind::Catchpoint(_)))
+    }
+
+    ///
+    /// The system calls enabled catchpoints in the given inferior stop on.
+    ///
+    pub fn syscall_filters(&self, inferior: u32) -> impl Iterator<Item=&SyscallFilter> {
+        self.entries.values()
+            .filter(move |bp| bp.enabled && bp.applies_to(inferior))
+            .filter_map(|bp| match &bp.kind {
+                Kind::Catchpoint(Catch::Syscall(filter)) => Some(filter),
+                _ => None,
+            })
+    }
+
+    ///
+    /// Iterates over the enabled breakpoints at the given address in the
+    /// given inferior.
+    ///
+    pub fn at_mut(&mut self, inferior: u32, addr: u64) -> impl Iterator<Item=&mut Breakpoint> {
+        self.entries.values_mut().filter(move |bp| bp.enabled && bp.addr(inferior) == Some(addr))
+    }
+}
@@ -0,0 +1,314 @@
+use std::collections::BTreeMap;
+
+use crate::error::{DebugError, Reason, Result};
+use crate::trace;
+
+cfg_if! {
+    if #[cfg(target_arch = "x86_64")] {
+        /// `int3`, the single byte trap instruction.
+        pub const BREAKPOINT_INSTRUCTION: u8 = 0xcc;
+    }
+}
+
+///
+/// A single address in the traced process where the breakpoint instruction
+/// has been written. Any number of logical breakpoints may share a site, and
+/// the instruction is only removed once the last of them lets go of it.
+///
+#[derive(Clone)]
+pub struct Site {
+    /// The absolute address of the site.
+    pub addr: u64,
+    /// The byte the breakpoint instruction replaced.
+    original: u8,
+    /// The number of logical breakpoints using this site.
+    refs: usize,
+}
+
+impl Site {
+    ///
+    /// The byte that was in the process text before the breakpoint
+    /// instruction was inserted.
+    ///
+    pub fn original(&self) -> u8 {
+        self.original
+    }
+}
+
+///
+/// Owns every breakpoint site in a single process. It is the only thing
+/// that should write breakpoint instructions into the process, so that
+/// the original bytes are always known and can be restored exactly.
+///
+#[derive(Default, Clone)]
+pub struct Sites {
+    sites: BTreeMap<u64, Site>,
+}
+
+impl Sites {
+    ///
+    /// Takes a reference to the site at the given address, writing the
+    /// breakpoint instruction into the process if this is the first one.
+    ///
+    pub fn insert(&mut self, pid: i32, addr: u64) -> Result<()> {
+        if let Some(site) = self.sites.get_mut(&addr) {
+            site.refs += 1;
+            return Ok(());
+        }
+
+        let original = read_byte(pid, addr)?;
+        write_byte(pid, addr, BREAKPOINT_INSTRUCTION)?;
+
+        self.sites.insert(addr, Site {
+            addr,
+            original,
+            refs: 1,
+        });
+        Ok(())
+    }
+
+    ///
+    /// Drops a reference to the site at the given address, restoring the
+    /// original byte once nothing else is using it.
+    ///
+    pub fn remove(&mut self, pid: i32, addr: u64) -> Result<()> {
+        match self.sites.get_mut(&addr) {
+            Some(site) if site.refs > 1 => {
+                site.refs -= 1;
+                Ok(())
+            }
+            Some(site) => {
+                write_byte(pid, addr, site.original)?;
+                self.sites.remove(&addr);
+                Ok(())
+            }
+            None => Err(DebugError::InvalidOperation(Reason::NoSuchBreakpoint))
+        }
+    }
+
+    ///
+    /// Removes every site from the process regardless of how many breakpoints
+    /// are using it, e.g. before detaching. Sites which could not be removed
+    /// are returned alongside the reason, and are forgotten about either way.
+    ///
+    pub fn remove_all(&mut self, pid: i32) -> Vec<(u64, DebugError)> {
+        let sites = std::mem::take(&mut self.sites);
+        sites.into_iter()
+            .filter_map(|(addr, site)| write_byte(pid, addr, site.original).err().map(|e| (addr, e)))
+            .collect()
+    }
+
+    ///
+    /// Forgets about every site within the given range without touching the
+    /// process, for when that memory has been unmapped.
+    ///
+    pub fn forget(&mut self, start: u64, end: u64) {
+        let addrs: Vec<u64> = self.sites.range(start..end).map(|(addr, _)| *addr).collect();
+        for addr in addrs {
+            self.sites.remove(&addr);
+        }
+    }
+
+    ///
+    /// Temporarily puts the original byte back at a site, without releasing
+    /// it, so that the real instruction can be executed.
+    ///
+    pub fn suspend(&self, pid: i32, addr: u64) -> Result<()> {
+        match self.sites.get(&addr) {
+            Some(site) => write_byte(pid, addr, site.original),
+            None => Err(DebugError::InvalidOperation(Reason::NoSuchBreakpoint))
+        }
+    }
+
+    ///
+    /// Re-inserts the breakpoint instruction at a suspended site.
+    ///
+    pub fn resume(&self, pid: i32, addr: u64) -> Result<()> {
+        match self.sites.get(&addr) {
+            Some(_) => write_byte(pid, addr, BREAKPOINT_INSTRUCTION),
+            None => Err(DebugError::InvalidOperation(Reason::NoSuchBreakpoint))
+        }
+    }
+
+    ///
+    /// Returns the site at the given address, if there is one.
+    ///
+    pub fn get(&self, addr: u64) -> Option<&Site> {
+        self.sites.get(&addr)
+    }
+
+    ///
+    /// Iterates over all the sites, in address order.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item=&Site> {
+        self.sites.values()
+    }
+
+    ///
+    /// Replaces any breakpoint instructions in `buf`, which was read from
+    /// the process at `addr`, with the original bytes.
+    ///
+    pub fn unpatch(&self, addr: u64, buf: &mut [u8]) {
+        let end = addr.saturating_add(buf.len() as u64);
+        for (site_addr, site) in self.sites.range(addr..end) {
+            buf[(site_addr - addr) as usize] = site.original;
+        }
+    }
+
+    ///
+    /// Writes `data` into the process at `addr`, such that any sites it
+    /// overlaps keep their breakpoint instruction, unless they're currently
+    /// suspended. The bytes that were destined for those sites become the new
+    /// original bytes instead.
+    ///
+    pub fn write(&mut self, pid: i32, addr: u64, data: &[u8], suspended: bool) -> Result<()> {
+        let end = addr.checked_add(data.len() as u64).ok_or(DebugError::InvalidOperation(Reason::NoMemory(addr)))?;
+        let mut buf = data.to_vec();
+        if !suspended {
+            for (site_addr, _) in self.sites.range(addr..end) {
+                buf[(site_addr - addr) as usize] = BREAKPOINT_INSTRUCTION;
+            }
+        }
+        write_bytes(pid, addr, &buf)?;
+
+        for (site_addr, site) in self.sites.range_mut(addr..end) {
+            site.original = data[(site_addr - addr) as usize];
+        }
+        Ok(())
+    }
+}
+
+///
+/// Reads a single byte of the process text.
+///
+fn read_byte(pid: i32, addr: u64) -> Result<u8> {
+    let (word_addr, shift) = word_of(addr);
+    let word = trace::read_text(pid, word_addr).map_err(DebugError::TraceFailure)? as u64;
+    Ok((word >> shift) as u8)
+}
+
+///
+/// Writes a single byte into the process text. Only one byte of the word
+/// is ours, so the rest is re-read in case it has changed.
+///
+fn write_byte(pid: i32, addr: u64, byte: u8) -> Result<()> {
+    let (word_addr, shift) = word_of(addr);
+    let word = trace::read_text(pid, word_addr).map_err(DebugError::TraceFailure)? as u64;
+    let patched = (word & !(0xff << shift)) | (byte as u64) << shift;
+    trace::write_text(pid, word_addr, patched).map_err(DebugError::TraceFailure)
+}
+
+///
+/// Writes any number of bytes into the process text, an aligned word at a
+/// time. Where the bytes only cover part of a word, the rest of it is
+/// re-read and left as it was.
+///
+fn write_bytes(pid: i32, addr: u64, bytes: &[u8]) -> Result<()> {
+    let mut done = 0;
+    while done < bytes.len() {
+        let (word_addr, shift) = word_of(addr + done as u64);
+        let offset = (shift / 8) as usize;
+        let len = (8 - offset).min(bytes.len() - done);
+        let mut word = if len == 8 {
+            [0; 8]
+        } else {
+            trace::read_text(pid, word_addr).map_err(DebugError::TraceFailure)?.to_ne_bytes()
+        };
+        word[offset..offset + len].copy_from_slice(&bytes[done..done + len]);
+        trace::write_text(pid, word_addr, u64::from_ne_bytes(word)).map_err(DebugError::TraceFailure)?;
+        done += len;
+    }
+    Ok(())
+}
+
+///
+/// The aligned word holding the byte at the given address, and how far the
+/// byte is shifted within it. Unlike the word starting at the byte, it can't
+/// run past the end of the mapping.
+///
+fn word_of(addr: u64) -> (u64, u64) {
+    (addr & !7, (addr & 7) * 8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{last_page, Child};
+
+    fn byte(child: &Child, addr: u64) -> u8 {
+        read_byte(child.0, addr).unwrap()
+    }
+
+    #[test]
+    fn shared_site() {
+        let addr = last_page() + 0x13;
+        let child = Child::fork();
+        let mut sites = Sites::default();
+
+        sites.insert(child.0, addr).unwrap();
+        sites.insert(child.0, addr).unwrap();
+        assert_eq!(byte(&child, addr), BREAKPOINT_INSTRUCTION);
+        assert_eq!(sites.get(addr).unwrap().original(), 0x13);
+
+        // the site is still wanted by the other breakpoint
+        sites.remove(child.0, addr).unwrap();
+        assert_eq!(byte(&child, addr), BREAKPOINT_INSTRUCTION);
+
+        sites.remove(child.0, addr).unwrap();
+        assert_eq!(byte(&child, addr), 0x13);
+        assert!(sites.get(addr).is_none());
+        assert!(sites.remove(child.0, addr).is_err());
+        // the bytes around the site were left alone throughout
+        assert_eq!(byte(&child, addr - 1), 0x12);
+        assert_eq!(byte(&child, addr + 1), 0x14);
+    }
+
+    #[test]
+    fn site_at_end_of_mapping() {
+        let addr = last_page() + 4095;
+        let child = Child::fork();
+        let mut sites = Sites::default();
+
+        sites.insert(child.0, addr).unwrap();
+        assert_eq!(byte(&child, addr), BREAKPOINT_INSTRUCTION);
+        assert_eq!(byte(&child, addr - 1), 0xfe);
+        sites.remove(child.0, addr).unwrap();
+        assert_eq!(byte(&child, addr), 0xff);
+    }
+
+    #[test]
+    fn write_over_site() {
+        let page = last_page();
+        let child = Child::fork();
+        let mut sites = Sites::default();
+
+        // unaligned, across two words, with the site in the second
+        sites.insert(child.0, page + 0x0a).unwrap();
+        sites.write(child.0, page + 0x05, &[0xa0; 8], false).unwrap();
+        assert_eq!(byte(&child, page + 0x04), 0x04);
+        assert_eq!(byte(&child, page + 0x05), 0xa0);
+        assert_eq!(byte(&child, page + 0x0a), BREAKPOINT_INSTRUCTION);
+        assert_eq!(byte(&child, page + 0x0c), 0xa0);
+        assert_eq!(byte(&child, page + 0x0d), 0x0d);
+        assert_eq!(sites.get(page + 0x0a).unwrap().original(), 0xa0);
+
+        // a suspended site gets the byte itself, and keeps it as the original
+        sites.suspend(child.0, page + 0x0a).unwrap();
+        sites.write(child.0, page + 0x0a, &[0xb0], true).unwrap();
+        assert_eq!(byte(&child, page + 0x0a), 0xb0);
+        sites.resume(child.0, page + 0x0a).unwrap();
+        sites.remove(child.0, page + 0x0a).unwrap();
+        assert_eq!(byte(&child, page + 0x0a), 0xb0);
+    }
+
+    #[test]
+    fn write_at_end_of_mapping() {
+        let addr = last_page() + 4092;
+        let child = Child::fork();
+        let mut sites = Sites::default();
+
+        sites.write(child.0, addr, &[0xc0; 4], false).unwrap();
+        assert_eq!(byte(&child, addr - 1), 0xfb);
+        assert_eq!(byte(&child, addr + 3), 0xc0);
+    }
+}
@@ -0,0 +1,530 @@
+use std::convert::TryFrom;
+use std::fs::File;
+use std::mem;
+use std::os::unix::fs::FileExt;
+use std::path::PathBuf;
+
+use object::{Bytes, Endianness};
+use object::elf::{ET_CORE, FileHeader64, NT_AUXV, NT_FILE, NT_FPREGSET, NT_PRPSINFO, NT_PRSTATUS, NT_SIGINFO, PF_R, PF_W, PF_X, PT_LOAD, PT_NOTE};
+use object::read::elf::{FileHeader, ProgramHeader};
+
+use crate::error::{DebugError, Reason, Result};
+use crate::inspect::Inspect;
+use crate::process::auxv;
+use crate::process::maps::MemoryMap;
+use crate::signals::{Signal, SignalInfo};
+use crate::trace::{FpRegisters, Registers, SigInfo};
+
+// Offsets into `struct elf_prstatus` from <linux/elfcore.h>, for 64 bit processes.
+const PR_PID: usize = 32;
+const PR_REG: usize = 112;
+
+// Offsets into `struct elf_prpsinfo`.
+const PR_PS_PID: usize = 24;
+const PR_FNAME: usize = 40;
+const PR_FNAME_LEN: usize = 16;
+const PR_PSARGS: usize = 56;
+const PR_PSARGS_LEN: usize = 80;
+
+///
+/// A thread of the process that dumped the core, as it was when it died.
+///
+struct CoreThread {
+    /// The identifier shown to the user, handed out in the order the
+    /// threads are listed in the core, which starts with the one that
+    /// took the process down.
+    id: u32,
+    tid: i32,
+    registers: Registers,
+    fp_registers: Option<FpRegisters>,
+    siginfo: Option<SignalInfo>,
+}
+
+///
+/// A loadable segment of the core, i.e. one mapping of the process. Pages
+/// that were never written to, such as most of the code, are left out of
+/// the core and are read from the file they were mapped from instead.
+///
+struct Segment {
+    start: u64,
+    end: u64,
+    flags: u32,
+    /// Where the contents of the segment start in the core, and how much
+    /// of it is there.
+    offset: u64,
+    size: u64,
+}
+
+///
+/// A file mapped into the process, as listed in the core's `NT_FILE` note.
+///
+struct FileMapping {
+    start: u64,
+    end: u64,
+    /// The offset into the file, in bytes.
+    offset: u64,
+    path: String,
+}
+
+///
+/// A core file, which is inspected in place of a live process once one has
+/// crashed. Only the notes are read up front. Memory is read from the core,
+/// or the files mapped into the process, as it's asked for.
+///
+pub struct Core {
+    pub path: PathBuf,
+    file: File,
+    pid: i32,
+    /// The command line the process was run with, as far as the kernel
+    /// keeps it.
+    pub command: String,
+    threads: Vec<CoreThread>,
+    current: usize,
+    auxv: Vec<(u64, u64)>,
+    segments: Vec<Segment>,
+    files: Vec<FileMapping>,
+}
+
+impl Core {
+    ///
+    /// Opens the core file at the given path, reading its threads and
+    /// memory layout.
+    ///
+    pub fn open<P: Into<PathBuf>>(path: P) -> Result<Self> {
+        let path = path.into();
+        let file = File::open(&path).map_err(DebugError::Sys)?;
+        let invalid = |e: object::read::Error| DebugError::InvalidOperation(Reason::InvalidCore(e.to_string()));
+        let out_of_range = |what: &str| DebugError::InvalidOperation(Reason::InvalidCore(format!("{} out of range", what)));
+
+        // the core is mostly memory, which is left where it is. The headers
+        // and the notes come first, so only the start of it is read.
+        let data = read_prefix(&file, mem::size_of::<FileHeader64<Endianness>>() as u64)?;
+        let header = FileHeader64::<Endianness>::parse(Bytes(&data)).map_err(invalid)?;
+        let endian = header.endian().map_err(invalid)?;
+        if header.e_type(endian) != ET_CORE {
+            return Err(DebugError::InvalidOperation(Reason::InvalidCore(String::from("not a core file"))));
+        }
+        // every offset and size comes from the file, so none can be trusted
+        // not to overflow
+        let headers_end = header.e_phoff(endian)
+            .checked_add(header.e_phnum(endian) as u64 * header.e_phentsize(endian) as u64)
+            .ok_or_else(|| out_of_range("program headers"))?;
+        let data = read_prefix(&file, headers_end)?;
+        let header = FileHeader64::<Endianness>::parse(Bytes(&data)).map_err(invalid)?;
+        let notes_end = header.program_headers(endian, Bytes(&data)).map_err(invalid)?.iter()
+            .filter(|ph| ph.p_type(endian) == PT_NOTE)
+            .map(|ph| ph.p_offset(endian).checked_add(ph.p_filesz(endian)))
+            .collect::<Option<Vec<u64>>>()
+            .ok_or_else(|| out_of_range("notes"))?
+            .into_iter()
+            .max()
+            .unwrap_or_default();
+        let data = read_prefix(&file, headers_end.max(notes_end))?;
+        let header = FileHeader64::<Endianness>::parse(Bytes(&data)).map_err(invalid)?;
+        let program_headers = header.program_headers(endian, Bytes(&data)).map_err(invalid)?;
+
+        let segments = program_headers.iter()
+            .filter(|ph| ph.p_type(endian) == PT_LOAD)
+            .map(|ph| {
+                ph.p_offset(endian).checked_add(ph.p_filesz(endian))?;
+                Some(Segment {
+                    start: ph.p_vaddr(endian),
+                    end: ph.p_vaddr(endian).checked_add(ph.p_memsz(endian))?,
+                    flags: ph.p_flags(endian),
+                    offset: ph.p_offset(endian),
+                    size: ph.p_filesz(endian),
+                })
+            })
+            .collect::<Option<Vec<Segment>>>()
+            .ok_or_else(|| out_of_range("memory segment"))?;
+
+        let mut core = Self {
+            path,
+            file,
+            pid: 0,
+            command: String::new(),
+            threads: Vec::new(),
+            current: 0,
+            auxv: Vec::new(),
+            segments,
+            files: Vec::new(),
+        };
+        for ph in program_headers {
+            let mut notes = match ph.notes(endian, Bytes(&data)).map_err(invalid)? {
+                Some(notes) => notes,
+                None => continue,
+            };
+            while let Some(note) = notes.next().map_err(invalid)? {
+                // the kernel's own notes, rather than those of the architecture
+                if note.name() == b"CORE" {
+                    core.read_note(note.n_type(endian), note.desc());
+                }
+            }
+        }
+
+        if core.threads.is_empty() {
+            return Err(DebugError::InvalidOperation(Reason::InvalidCore(String::from("no threads"))));
+        }
+        if core.pid == 0 {
+            core.pid = core.threads[0].tid;
+        }
+        Ok(core)
+    }
+
+    ///
+    /// Takes in a single note. The notes describing a thread, such as its
+    /// floating point registers, follow the `NT_PRSTATUS` that starts it.
+    ///
+    fn read_note(&mut self, kind: u32, desc: &[u8]) {
+        match kind {
+            NT_PRSTATUS => {
+                let registers = match desc.get(PR_REG..).and_then(read_struct::<Registers>) {
+                    Some(registers) => registers,
+                    None => return,
+                };
+                let tid = read_struct::<i32>(&desc[PR_PID..]).unwrap_or_default();
+                self.threads.push(CoreThread {
+                    id: self.threads.len() as u32 + 1,
+                    tid,
+                    registers,
+                    fp_registers: None,
+                    siginfo: None,
+                });
+            }
+            NT_FPREGSET => {
+                if let Some(thread) = self.threads.last_mut() {
+                    thread.fp_registers = read_struct::<FpRegisters>(desc);
+                }
+            }
+            NT_SIGINFO => {
+                let raw = read_struct::<SigInfo>(desc);
+                let signal = raw.and_then(|raw| Signal::try_from(raw.si_signo).ok());
+                if let (Some(thread), Some(raw), Some(signal)) = (self.threads.last_mut(), raw, signal) {
+                    thread.siginfo = Some(SignalInfo::from_raw(signal, &raw));
+                }
+            }
+            NT_PRPSINFO => {
+                let text = |offset: usize, len: usize| {
+                    let bytes = desc.get(offset..offset + len).unwrap_or_default();
+                    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+                    String::from_utf8_lossy(&bytes[..end]).trim_end().to_string()
+                };
+                self.pid = desc.get(PR_PS_PID..).and_then(read_struct::<i32>).unwrap_or_default();
+                let args = text(PR_PSARGS, PR_PSARGS_LEN);
+                self.command = if args.is_empty() { text(PR_FNAME, PR_FNAME_LEN) } else { args };
+            }
+            NT_AUXV => self.auxv = auxv::parse(desc),
+            NT_FILE => self.files = parse_files(desc),
+            _ => {}
+        }
+    }
+
+    ///
+    /// The signal that killed the process, along with its details.
+    ///
+    pub fn signal(&self) -> Option<SignalInfo> {
+        self.threads[0].siginfo
+    }
+
+    ///
+    /// The path the given address was mapped from, if it's in a file.
+    ///
+    pub fn mapped_file(&self, addr: u64) -> Option<&str> {
+        self.files.iter()
+            .find(|file| file.start <= addr && addr < file.end)
+            .map(|file| file.path.as_str())
+    }
+
+    ///
+    /// Reads as much as possible of the given range from a single place,
+    /// into the start of `buf`, returning how many bytes were read.
+    ///
+    fn read_chunk(&self, addr: u64, buf: &mut [u8]) -> Result<usize> {
+        let no_memory = || DebugError::InvalidOperation(Reason::NoMemory(addr));
+
+        let segment = self.segments.iter().find(|segment| segment.start <= addr && addr < segment.end);
+        if let Some(segment) = segment {
+            let offset = addr - segment.start;
+            if offset < segment.size {
+                let len = buf.len().min((segment.size - offset) as usize);
+                self.file.read_exact_at(&mut buf[..len], segment.offset + offset).map_err(DebugError::Sys)?;
+                return Ok(len);
+            }
+        }
+
+        let mapping = self.files.iter()
+            .find(|file| file.start <= addr && addr < file.end)
+            .ok_or_else(no_memory)?;
+        let end = segment.map_or(mapping.end, |segment| segment.end.min(mapping.end));
+        let len = buf.len().min((end - addr) as usize);
+        let file = File::open(&mapping.path).map_err(|_| no_memory())?;
+        file.read_exact_at(&mut buf[..len], mapping.offset + (addr - mapping.start)).map_err(|_| no_memory())?;
+        Ok(len)
+    }
+
+    fn thread(&self, tid: i32) -> Result<&CoreThread> {
+        self.threads.iter()
+            .find(|thread| thread.tid == tid)
+            .ok_or(DebugError::InvalidOperation(Reason::NoSuchThread))
+    }
+}
+
+impl Inspect for Core {
+    fn pid(&self) -> i32 {
+        self.pid
+    }
+
+    fn read(&self, addr: u64) -> Result<i64> {
+        let mut bytes = [0u8; 8];
+        let word = self.read_memory(addr, bytes.len())?;
+        bytes.copy_from_slice(&word);
+        Ok(i64::from_ne_bytes(bytes))
+    }
+
+    ///
+    /// Reads an arbitrary number of bytes from the given address, which may
+    /// be spread across the core and the files that were mapped.
+    ///
+    fn read_memory(&self, addr: u64, len: usize) -> Result<Vec<u8>> {
+        let mut bytes = vec![0u8; len];
+        let mut done = 0;
+        while done < len {
+            let current = addr.checked_add(done as u64).ok_or(DebugError::InvalidOperation(Reason::NoMemory(addr)))?;
+            done += self.read_chunk(current, &mut bytes[done..])?;
+        }
+        Ok(bytes)
+    }
+
+    fn maps(&self) -> Result<Vec<MemoryMap>> {
+        Ok(self.segments.iter()
+            .map(|segment| {
+                let mapping = self.files.iter().find(|file| file.start <= segment.start && segment.start < file.end);
+                MemoryMap {
+                    start: segment.start,
+                    end: segment.end,
+                    readable: segment.flags & PF_R != 0,
+                    writable: segment.flags & PF_W != 0,
+                    executable: segment.flags & PF_X != 0,
+                    offset: mapping.map_or(0, |file| file.offset + (segment.start - file.start)),
+                    path: mapping.map(|file| file.path.clone()),
+                }
+            })
+            .collect())
+    }
+
+    fn auxv(&self) -> Result<Vec<(u64, u64)>> {
+        Ok(self.auxv.clone())
+    }
+
+    fn thread_ids(&self) -> Vec<(u32, i32)> {
+        self.threads.iter().map(|thread| (thread.id, thread.tid)).collect()
+    }
+
+    fn current_tid(&self) -> i32 {
+        self.threads[self.current].tid
+    }
+
+    fn switch_thread(&mut self, id: u32) -> Result<i32> {
+        self.current = self.threads.iter()
+            .position(|thread| thread.id == id)
+            .ok_or(DebugError::InvalidOperation(Reason::NoSuchThread))?;
+        Ok(self.current_tid())
+    }
+
+    fn thread_status(&self, _tid: i32) -> Option<&'static str> {
+        None
+    }
+
+    fn thread_registers(&self, tid: i32) -> Result<Registers> {
+        Ok(self.thread(tid)?.registers)
+    }
+
+    fn thread_fp_registers(&self, tid: i32) -> Result<FpRegisters> {
+        self.thread(tid)?.fp_registers
+            .ok_or_else(|| DebugError::InvalidOperation(Reason::NotInCore(String::from("floating point registers"))))
+    }
+
+    fn thread_siginfo(&self, tid: i32) -> Option<SignalInfo> {
+        self.thread(tid).ok()?.siginfo
+    }
+}
+
+///
+/// Reads the first `len` bytes of a file, which is checked against the
+/// file's size before anything is allocated for them, since the length
+/// comes from the file itself.
+///
+fn read_prefix(file: &File, len: u64) -> Result<Vec<u8>> {
+    if len > file.metadata().map_err(DebugError::Sys)?.len() {
+        return Err(DebugError::InvalidOperation(Reason::InvalidCore(String::from("the file is cut short"))));
+    }
+    let mut data = vec![0u8; len as usize];
+    file.read_exact_at(&mut data, 0).map_err(DebugError::Sys)?;
+    Ok(data)
+}
+
+///
+/// Reads a plain C struct from the start of the given bytes, if there
+/// are enough of them.
+///
+fn read_struct<T: Copy>(bytes: &[u8]) -> Option<T> {
+    if bytes.len() < mem::size_of::<T>() {
+        return None;
+    }
+    // the kernel's structs are laid out as in the C headers, which the
+    // libc definitions follow, but notes are only aligned to 4 bytes.
+    Some(unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const T) })
+}
+
+///
+/// Parses an `NT_FILE` note: a count and page size, followed by the range
+/// and page offset of each mapping, and then all of their paths. A note
+/// whose count doesn't fit in it is ignored altogether.
+///
+fn parse_files(desc: &[u8]) -> Vec<FileMapping> {
+    let word = |idx: usize| desc.get(idx * 8..).and_then(read_struct::<u64>);
+    let (count, page_size) = match (word(0).and_then(|count| usize::try_from(count).ok()), word(1)) {
+        (Some(count), Some(page_size)) => (count, page_size),
+        _ => return Vec::new(),
+    };
+
+    let names = count.checked_mul(3)
+        .and_then(|words| words.checked_add(2))
+        .and_then(|words| words.checked_mul(8))
+        .and_then(|start| desc.get(start..));
+    let names = match names {
+        Some(names) => names,
+        None => return Vec::new(),
+    };
+    names.split(|b| *b == 0)
+        .zip(0..count)
+        .filter_map(|(name, idx)| {
+            let (start, end) = (word(2 + idx * 3)?, word(3 + idx * 3)?);
+            let offset = word(4 + idx * 3)?.checked_mul(page_size)?;
+            // the whole of the mapping has to be somewhere in the file
+            offset.checked_add(end.checked_sub(start)?)?;
+            Some(FileMapping { start, end, offset, path: String::from_utf8_lossy(name).to_string() })
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///
+    /// Lays out an `NT_FILE` note with the given count, page size and
+    /// words, followed by the paths.
+    ///
+    fn note(words: &[u64], paths: &[&str]) -> Vec<u8> {
+        let mut desc: Vec<u8> = words.iter().flat_map(|word| word.to_ne_bytes()).collect();
+        for path in paths {
+            desc.extend_from_slice(path.as_bytes());
+            desc.push(0);
+        }
+        desc
+    }
+
+    #[test]
+    fn files() {
+        let desc = note(&[2, 0x1000, 0x400000, 0x401000, 0, 0x401000, 0x403000, 2], &["/bin/a", "/lib/b.so"]);
+        let files = parse_files(&desc);
+        assert_eq!(files.len(), 2);
+        assert_eq!((files[0].start, files[0].end, files[0].offset), (0x400000, 0x401000, 0));
+        assert_eq!(files[0].path, "/bin/a");
+        assert_eq!((files[1].start, files[1].end, files[1].offset), (0x401000, 0x403000, 0x2000));
+        assert_eq!(files[1].path, "/lib/b.so");
+    }
+
+    #[test]
+    fn truncated() {
+        // the second mapping's range is cut off, along with every path
+        let desc = note(&[2, 0x1000, 0x400000, 0x401000, 0, 0x401000], &[]);
+        assert!(parse_files(&desc).is_empty());
+        assert!(parse_files(&desc[..12]).is_empty());
+    }
+
+    #[test]
+    fn oversized_count() {
+        for count in [u64::MAX, u64::MAX / 3, u64::MAX / 24, 1 << 40] {
+            let desc = note(&[count, 0x1000, 0x400000, 0x401000, 0], &["/bin/a"]);
+            assert!(parse_files(&desc).is_empty());
+        }
+    }
+
+    #[test]
+    fn mapping_ending_before_it_starts() {
+        let desc = note(&[1, 0x1000, 0x401000, 0x400000, 0], &["/bin/a"]);
+        assert!(parse_files(&desc).is_empty());
+    }
+
+    ///
+    /// Writes out an ELF core header followed by the given program headers,
+    /// as `(type, offset, vaddr, filesz, memsz)`, claiming `phnum` of them
+    /// at `phoff`, and opens it.
+    ///
+    fn open(name: &str, phoff: u64, phnum: u16, headers: &[(u32, u64, u64, u64, u64)]) -> Result<Core> {
+        let mut data = vec![0u8; 64];
+        data[..8].copy_from_slice(&[0x7f, b'E', b'L', b'F', 2, 1, 1, 0]);
+        data[16..18].copy_from_slice(&ET_CORE.to_le_bytes());
+        data[18..20].copy_from_slice(&62u16.to_le_bytes());
+        data[20..24].copy_from_slice(&1u32.to_le_bytes());
+        data[32..40].copy_from_slice(&phoff.to_le_bytes());
+        data[52..54].copy_from_slice(&64u16.to_le_bytes());
+        data[54..56].copy_from_slice(&56u16.to_le_bytes());
+        data[56..58].copy_from_slice(&phnum.to_le_bytes());
+        for (p_type, offset, vaddr, filesz, memsz) in headers {
+            let mut header = vec![0u8; 56];
+            header[..4].copy_from_slice(&p_type.to_le_bytes());
+            header[8..16].copy_from_slice(&offset.to_le_bytes());
+            header[16..24].copy_from_slice(&vaddr.to_le_bytes());
+            header[32..40].copy_from_slice(&filesz.to_le_bytes());
+            header[40..48].copy_from_slice(&memsz.to_le_bytes());
+            data.extend(header);
+        }
+        let path = std::env::temp_dir().join(format!("sdbg-core-{}-{}", std::process::id(), name));
+        std::fs::write(&path, data).unwrap();
+        let core = Core::open(&path);
+        let _ = std::fs::remove_file(&path);
+        core
+    }
+
+    fn is_invalid(core: Result<Core>) -> bool {
+        matches!(core, Err(DebugError::InvalidOperation(Reason::InvalidCore(_))))
+    }
+
+    #[test]
+    fn headers_out_of_range() {
+        assert!(is_invalid(open("phoff", u64::MAX, 1, &[])));
+        // more headers than the file has room for
+        assert!(is_invalid(open("phnum", 64, u16::MAX, &[])));
+        assert!(is_invalid(open("note", 64, 1, &[(PT_NOTE, u64::MAX, 0, 1, 0)])));
+        assert!(is_invalid(open("note-size", 64, 1, &[(PT_NOTE, 64, 0, 1 << 40, 0)])));
+        assert!(is_invalid(open("load", 64, 1, &[(PT_LOAD, u64::MAX, 0x1000, 1, 1)])));
+        assert!(is_invalid(open("vaddr", 64, 1, &[(PT_LOAD, 0, u64::MAX, 0, 2)])));
+    }
+
+    #[test]
+    fn read_at_top_of_memory() {
+        let core = Core {
+            path: PathBuf::from("/proc/self/exe"),
+            file: File::open("/proc/self/exe").unwrap(),
+            pid: 0,
+            command: String::new(),
+            threads: Vec::new(),
+            current: 0,
+            auxv: Vec::new(),
+            segments: vec![Segment { start: u64::MAX - 1, end: u64::MAX, flags: PF_R, offset: 0, size: 1 }],
+            files: Vec::new(),
+        };
+        assert_eq!(core.read_memory(u64::MAX - 1, 1).unwrap(), [0x7f]);
+        assert!(core.read_memory(u64::MAX - 1, 4).is_err());
+    }
+
+    #[test]
+    fn oversized_offset() {
+        let desc = note(&[1, 0x1000, 0x400000, 0x401000, u64::MAX], &["/bin/a"]);
+        assert!(parse_files(&desc).is_empty());
+    }
+}
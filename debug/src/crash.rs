@@ -0,0 +1,182 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+use crate::process::maps::MemoryMap;
+use crate::signals::{Signal, SignalInfo};
+use crate::trace::Registers;
+
+/// Addresses below this are never mapped (the kernel's default
+/// `vm.mmap_min_addr`), so faulting on one means a null pointer was used.
+const NULL_LIMIT: u64 = 0x10000;
+
+/// How far below the stack a fault can be while still being the stack
+/// growing into its guard, which is a megabyte for the main thread.
+const STACK_GUARD_GAP: u64 = 0x100000;
+
+/// `si_code` of a SIGSEGV raised by a general protection fault.
+const SI_KERNEL: i32 = 0x80;
+const SEGV_ACCERR: i32 = 2;
+const BUS_ADRALN: i32 = 1;
+const FPE_INTDIV: i32 = 1;
+
+///
+/// A first guess at why the process crashed.
+///
+pub enum Crash {
+    /// Memory was accessed through a null pointer, at the given offset.
+    NullDereference { addr: u64 },
+    /// The stack grew into its guard, usually from unbounded recursion.
+    StackOverflow { addr: u64 },
+    /// Memory that is mapped read only was written to.
+    WriteToReadOnly { addr: u64, region: String },
+    /// The program jumped into memory that isn't executable.
+    ExecuteNonExecutable { addr: u64, region: String },
+    /// The program jumped to an address where nothing is mapped.
+    JumpToUnmapped { addr: u64 },
+    /// Memory was accessed with an address that isn't aligned as the
+    /// instruction requires, e.g. an aligned SSE move.
+    MisalignedAccess { addr: Option<u64> },
+    /// Memory was accessed at an address where nothing is mapped.
+    UnmappedAccess { addr: u64 },
+    /// Memory was accessed in a region that can't be accessed at all.
+    ProtectedAccess { addr: u64, region: String },
+    /// The processor refused an access for another reason, such as a
+    /// non-canonical address.
+    GeneralProtection,
+    /// An integer was divided by zero, or the most negative number by -1,
+    /// which x86 reports the same way.
+    DivideByZero,
+    /// The program executed an instruction that deliberately traps.
+    Trap,
+    IllegalInstruction,
+    /// The program called `abort`, e.g. from a failed assertion.
+    Abort,
+}
+
+///
+/// Classifies a crash from the signal it caused, the registers of the thread
+/// that crashed, the memory maps of the process, and the bytes of the
+/// faulting instruction. Returns `None` if the signal isn't a crash.
+///
+pub fn diagnose(info: &SignalInfo, regs: &Registers, maps: &[MemoryMap], code: &[u8]) -> Option<Crash> {
+    let crash = match info.signal {
+        Signal::SIGSEGV if info.code == SI_KERNEL => {
+            if is_aligned_vector_access(code) {
+                Crash::MisalignedAccess { addr: None }
+            } else {
+                Crash::GeneralProtection
+            }
+        }
+        Signal::SIGSEGV => segfault(info.addr?, info.code, regs, maps),
+        Signal::SIGBUS if info.code == BUS_ADRALN => Crash::MisalignedAccess { addr: info.addr },
+        Signal::SIGBUS => Crash::UnmappedAccess { addr: info.addr? },
+        Signal::SIGFPE if info.code == FPE_INTDIV => Crash::DivideByZero,
+        Signal::SIGILL if code.starts_with(&[0x0f, 0x0b]) => Crash::Trap,
+        Signal::SIGILL => Crash::IllegalInstruction,
+        Signal::SIGABRT => Crash::Abort,
+        _ => return None,
+    };
+    Some(crash)
+}
+
+fn segfault(addr: u64, code: i32, regs: &Registers, maps: &[MemoryMap]) -> Crash {
+    let map = maps.iter().find(|map| map.contains(addr));
+    let region = |map: &MemoryMap| map.path.clone().unwrap_or_else(|| String::from("anonymous memory"));
+
+    // a fault on fetching the instruction itself
+    if addr == regs.rip {
+        return match map {
+            Some(map) if !map.executable => Crash::ExecuteNonExecutable { addr, region: region(map) },
+            None if addr < NULL_LIMIT => Crash::NullDereference { addr },
+            _ => Crash::JumpToUnmapped { addr },
+        };
+    }
+
+    let inaccessible = map.is_none_or(|map| !map.readable && !map.writable && !map.executable);
+    if inaccessible && is_stack_overflow(addr, regs.rsp, maps) {
+        return Crash::StackOverflow { addr };
+    }
+
+    match map {
+        None if addr < NULL_LIMIT => Crash::NullDereference { addr },
+        None => Crash::UnmappedAccess { addr },
+        Some(map) if inaccessible => Crash::ProtectedAccess { addr, region: region(map) },
+        Some(map) if code == SEGV_ACCERR && !map.writable => Crash::WriteToReadOnly { addr, region: region(map) },
+        Some(map) => Crash::ProtectedAccess { addr, region: region(map) },
+    }
+}
+
+///
+/// Whether an inaccessible address is where the stack would grow to next:
+/// either the stack pointer has already run off the end of the stack, or
+/// the address is just below the mapping it is in.
+///
+fn is_stack_overflow(addr: u64, rsp: u64, maps: &[MemoryMap]) -> bool {
+    match maps.iter().find(|map| map.contains(rsp)) {
+        Some(stack) if stack.readable && stack.writable => addr < stack.start && stack.start - addr <= STACK_GUARD_GAP,
+        _ => addr.abs_diff(rsp) < NULL_LIMIT,
+    }
+}
+
+///
+/// Whether the instruction is an SSE or AVX move which requires its memory
+/// operand to be aligned (e.g. `movaps`, `movdqa`), which raises a general
+/// protection fault rather than a bus error when it isn't.
+///
+fn is_aligned_vector_access(code: &[u8]) -> bool {
+    let mut operand_size = false;
+    let mut rest = code;
+    while let Some((&byte, tail)) = rest.split_first() {
+        match byte {
+            0x66 => operand_size = true,
+            0x40..=0x4f | 0xf2 | 0xf3 => {}
+            _ => break,
+        }
+        rest = tail;
+    }
+
+    match rest {
+        // movaps, movapd, movntps, movntpd
+        [0x0f, 0x28, ..] | [0x0f, 0x29, ..] | [0x0f, 0x2b, ..] => true,
+        // movdqa, movntdq
+        [0x0f, 0x6f, ..] | [0x0f, 0x7f, ..] | [0x0f, 0xe7, ..] => operand_size,
+        // the same, VEX encoded, where `pp` of 1 stands in for the 0x66 prefix
+        [0xc5, vex, opcode, ..] => is_aligned_vex(*vex & 0x3, *opcode),
+        [0xc4, map, vex, opcode, ..] if map & 0x1f == 1 => is_aligned_vex(*vex & 0x3, *opcode),
+        _ => false,
+    }
+}
+
+fn is_aligned_vex(pp: u8, opcode: u8) -> bool {
+    match opcode {
+        0x28 | 0x29 | 0x2b => pp <= 1,
+        0x6f | 0x7f | 0xe7 => pp == 1,
+        _ => false,
+    }
+}
+
+impl Display for Crash {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Crash::NullDereference { addr: 0 } => write!(f, "null pointer dereference"),
+            Crash::NullDereference { addr } => write!(f, "null pointer dereference (at offset 0x{:x} from null)", addr),
+            Crash::StackOverflow { addr } => write!(f, "stack overflow, touching the guard below the stack at 0x{:x}", addr),
+            Crash::WriteToReadOnly { addr, region } => write!(f, "write to read only memory at 0x{:x} in {}", addr, region),
+            Crash::ExecuteNonExecutable { addr, region } => {
+                write!(f, "executing non-executable memory at 0x{:x} in {}", addr, region)
+            }
+            Crash::JumpToUnmapped { addr } => write!(f, "jump to unmapped address 0x{:x}, e.g. through a bad function pointer", addr),
+            Crash::MisalignedAccess { addr: Some(addr) } => write!(f, "misaligned access at 0x{:x}", addr),
+            Crash::MisalignedAccess { addr: None } => {
+                write!(f, "misaligned access by an instruction which requires 16 byte alignment")
+            }
+            Crash::UnmappedAccess { addr } => write!(f, "access to unmapped address 0x{:x}, e.g. a wild or freed pointer", addr),
+            Crash::ProtectedAccess { addr, region } => write!(f, "access to protected memory at 0x{:x} in {}", addr, region),
+            Crash::GeneralProtection => write!(f, "general protection fault, e.g. a non-canonical address"),
+            Crash::DivideByZero => write!(f, "integer divide by zero, or overflow dividing by -1"),
+            Crash::Trap => write!(f, "trap instruction (ud2), e.g. __builtin_trap or unreachable code"),
+            Crash::IllegalInstruction => write!(f, "illegal instruction"),
+            Crash::Abort => write!(f, "abort called, e.g. by a failed assertion"),
+        }
+    }
+}
@@ -11,6 +11,7 @@ pub enum DebugError {
     InvalidOperation(Reason),
     TraceFailure(TraceError),
     Sys(std::io::Error),
+    Symbols(String),
     Unknown(Box::<dyn Error>),
 }
 
@@ -20,6 +21,20 @@ pub enum Reason {
     NoProcess,
     ProcessExists,
     NoSuchBreakpoint,
+    InvalidCondition(String),
+    LaunchFailed,
+    NoSuchThread,
+    RequiresNonStop,
+    NoSuchInferior,
+    NoSignal,
+    UnknownValue(String),
+    NotTracingSyscalls,
+    NoSymbol(String),
+    InvalidArguments(String),
+    InvalidCore(String),
+    NotInCore(String),
+    NoMemory(u64),
+    SeccompFilter(i32),
 }
 
 impl Display for Reason {
@@ -29,7 +44,22 @@ impl Display for Reason {
             NoFile => write!(f, "no file"),
             NoProcess => write!(f, "no process"),
             ProcessExists => write!(f, "process already exists"),
-            NoSuchBreakpoint => write!(f, "no such breakpoint")
+            NoSuchBreakpoint => write!(f, "no such breakpoint"),
+            InvalidCondition(c) => write!(f, "invalid condition: {}", c),
+            LaunchFailed => write!(f, "process failed to launch"),
+            NoSuchThread => write!(f, "no such thread"),
+            RequiresNonStop => write!(f, "only available in non-stop mode"),
+            NoSuchInferior => write!(f, "no such inferior"),
+            NoSignal => write!(f, "the thread isn't stopped with a signal"),
+            UnknownValue(v) => write!(f, "unknown value: {}", v),
+            NotTracingSyscalls => write!(f, "syscalls aren't being traced"),
+            NoSymbol(name) => write!(f, "no symbol {}", name),
+            InvalidArguments(e) => write!(f, "invalid arguments: {}", e),
+            InvalidCore(e) => write!(f, "invalid core file: {}", e),
+            NotInCore(what) => write!(f, "the core file has no {}", what),
+            NoMemory(addr) => write!(f, "cannot access memory at 0x{:x}", addr),
+            SeccompFilter(pid) => write!(f, "process {} has a seccomp filter, so the syscalls it stops on \
+                would fail once detached; kill it instead", pid),
         }
     }
 }
@@ -41,6 +71,7 @@ impl Display for DebugError {
             InvalidOperation(r) => write!(f, "Invalid operation: {}", r),
             Sys(inner) => write!(f, "Encountered IO error: {}", inner),
             TraceFailure(t) => write!(f, "tracing failed: {}", t),
+            Symbols(s) => write!(f, "failed to read symbols: {}", s),
             Unknown(n) => write!(f, "unknown error: {}", n)
         }
     }
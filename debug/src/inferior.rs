@@ -0,0 +1,166 @@
+use std::collections::BTreeMap;
+
+use crate::coredump::Core;
+use crate::error::{DebugError, Reason, Result};
+use crate::inspect::Inspect;
+use crate::launch::Launch;
+use crate::module::Modules;
+use crate::module::link_map::DynamicLinker;
+use crate::process::Process;
+
+///
+/// A program being debugged, along with the process running it if there is
+/// one. Every inferior has its own modules, while the breakpoints are shared
+/// between all of them, unless they're scoped to just one.
+///
+pub struct Inferior {
+    /// The identifier shown to the user, handed out in the order the
+    /// inferiors were added.
+    pub id: u32,
+    /// The path of the executable the inferior runs.
+    pub file: Option<String>,
+    /// How the executable is launched, which carries over between runs.
+    pub launch: Launch,
+    pub(crate) process: Option<Process>,
+    /// The executable and libraries loaded into the process.
+    pub(crate) modules: Modules,
+    /// Tells us when libraries are loaded into or unloaded from the process.
+    pub(crate) linker: Option<DynamicLinker>,
+    /// The parent of a followed vfork child, which can only be detached from
+    /// once the child has stopped sharing its memory.
+    pub(crate) vfork_parent: Option<Process>,
+    /// The core file being examined in place of a process.
+    pub(crate) core: Option<Core>,
+}
+
+impl Inferior {
+    ///
+    /// The process of the inferior, if it has one which is still alive.
+    ///
+    pub fn process(&self) -> Option<&Process> {
+        self.process.as_ref().filter(|process| process.running)
+    }
+
+    ///
+    /// What commands that only look at the program inspect: the process if
+    /// there is one, or otherwise the core file being examined.
+    ///
+    pub fn target(&self) -> Option<&dyn Inspect> {
+        match self.process() {
+            Some(process) => Some(process),
+            None => self.core.as_ref().map(|core| core as &dyn Inspect),
+        }
+    }
+
+    pub fn target_mut(&mut self) -> Option<&mut dyn Inspect> {
+        match self.process.as_mut().filter(|process| process.running) {
+            Some(process) => Some(process),
+            None => self.core.as_mut().map(|core| core as &mut dyn Inspect),
+        }
+    }
+
+    pub fn modules(&self) -> &Modules {
+        &self.modules
+    }
+}
+
+///
+/// The table of every inferior in the session. There is always at least one,
+/// and one of them is current, which is the one commands act on.
+///
+pub struct Inferiors {
+    last_id: u32,
+    current: u32,
+    entries: BTreeMap<u32, Inferior>,
+}
+
+impl Default for Inferiors {
+    fn default() -> Self {
+        let mut inferiors = Self {
+            last_id: 0,
+            current: 1,
+            entries: BTreeMap::new(),
+        };
+        inferiors.add(None);
+        inferiors
+    }
+}
+
+impl Inferiors {
+    ///
+    /// Adds a new inferior without a process, returning its newly
+    /// assigned identifier.
+    ///
+    pub fn add(&mut self, file: Option<String>) -> u32 {
+        self.last_id += 1;
+        self.entries.insert(self.last_id, Inferior {
+            id: self.last_id,
+            file,
+            launch: Launch::default(),
+            process: None,
+            modules: Modules::default(),
+            linker: None,
+            vfork_parent: None,
+            core: None,
+        });
+        self.last_id
+    }
+
+    pub fn get(&self, id: u32) -> Result<&Inferior> {
+        self.entries.get(&id).ok_or(DebugError::InvalidOperation(Reason::NoSuchInferior))
+    }
+
+    pub fn get_mut(&mut self, id: u32) -> Result<&mut Inferior> {
+        self.entries.get_mut(&id).ok_or(DebugError::InvalidOperation(Reason::NoSuchInferior))
+    }
+
+    ///
+    /// The identifier of the current inferior.
+    ///
+    pub fn current_id(&self) -> u32 {
+        self.current
+    }
+
+    pub fn current(&self) -> &Inferior {
+        &self.entries[&self.current]
+    }
+
+    pub fn current_mut(&mut self) -> &mut Inferior {
+        self.entries.get_mut(&self.current).unwrap()
+    }
+
+    ///
+    /// Makes the given inferior the current one.
+    ///
+    pub fn select(&mut self, id: u32) -> Result<&Inferior> {
+        self.get(id)?;
+        self.current = id;
+        Ok(self.current())
+    }
+
+    ///
+    /// Iterates over every inferior, in order of identifier.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item=&Inferior> {
+        self.entries.values()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item=&mut Inferior> {
+        self.entries.values_mut()
+    }
+
+    ///
+    /// The identifiers of every inferior, in increasing order.
+    ///
+    pub fn ids(&self) -> Vec<u32> {
+        self.entries.keys().copied().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
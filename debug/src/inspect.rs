@@ -0,0 +1,100 @@
+use crate::error::Result;
+use crate::process::maps::MemoryMap;
+use crate::signals::SignalInfo;
+use crate::trace::{FpRegisters, Registers};
+
+///
+/// Read-only access to the state of a program, whether that's a live
+/// process stopped under the tracer, or the core file a process left behind
+/// when it died. Everything that only looks at the program, such as reading
+/// its memory and registers or unwinding its stack, works on either.
+///
+pub trait Inspect {
+    ///
+    /// The id of the process, or of the process that dumped the core.
+    ///
+    fn pid(&self) -> i32;
+
+    ///
+    /// Reads a single word from the given address.
+    ///
+    fn read(&self, addr: u64) -> Result<i64>;
+
+    ///
+    /// Reads an arbitrary number of bytes from the given address.
+    ///
+    fn read_memory(&self, addr: u64, len: usize) -> Result<Vec<u8>>;
+
+    ///
+    /// Reads a nul terminated string from the given address, up to a
+    /// sensible maximum length.
+    ///
+    fn read_string(&self, addr: u64) -> Result<String> {
+        const MAX_LEN: usize = 4096;
+        let word_size = std::mem::size_of::<u64>() as u64;
+
+        let mut bytes = Vec::new();
+        let mut current = addr;
+        while bytes.len() < MAX_LEN {
+            let word = self.read(current)?.to_ne_bytes();
+            match word.iter().position(|b| *b == 0) {
+                Some(end) => {
+                    bytes.extend_from_slice(&word[..end]);
+                    break;
+                }
+                None => bytes.extend_from_slice(&word),
+            }
+            current += word_size;
+        }
+        Ok(String::from_utf8_lossy(&bytes).to_string())
+    }
+
+    ///
+    /// The memory maps of the program, in address order.
+    ///
+    fn maps(&self) -> Result<Vec<MemoryMap>>;
+
+    ///
+    /// The auxiliary vector the kernel handed to the program.
+    ///
+    fn auxv(&self) -> Result<Vec<(u64, u64)>>;
+
+    ///
+    /// The identifier shown to the user, and the kernel's identifier, of
+    /// every thread, in the order they are listed.
+    ///
+    fn thread_ids(&self) -> Vec<(u32, i32)>;
+
+    ///
+    /// The kernel identifier of the thread whose registers are read.
+    ///
+    fn current_tid(&self) -> i32;
+
+    ///
+    /// Switches the thread whose registers are read, by the identifier
+    /// shown to the user, returning its kernel identifier.
+    ///
+    fn switch_thread(&mut self, id: u32) -> Result<i32>;
+
+    ///
+    /// Why the given thread can't be inspected, e.g. because it's running,
+    /// or `None` if it can.
+    ///
+    fn thread_status(&self, tid: i32) -> Option<&'static str>;
+
+    ///
+    /// Reads the general purpose registers of the given thread.
+    ///
+    fn thread_registers(&self, tid: i32) -> Result<Registers>;
+
+    ///
+    /// Reads the floating point and SSE registers of the given thread.
+    ///
+    fn thread_fp_registers(&self, tid: i32) -> Result<FpRegisters>;
+
+    ///
+    /// The details of the signal the given thread is stopped with, or that
+    /// killed it, if any.
+    ///
+    fn thread_siginfo(&self, tid: i32) -> Option<SignalInfo>;
+}
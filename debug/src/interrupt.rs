@@ -0,0 +1,66 @@
+use std::os::unix::io::RawFd;
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+
+/// Whether the wait has been asked to end.
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+/// Where to write to wake up a wait for the processes, once there's one to
+/// wake up.
+static WAKE: AtomicI32 = AtomicI32::new(-1);
+
+/// What's written to wake up the wait when it's asked to end.
+pub(crate) const WAKE_INTERRUPT: u8 = b'i';
+
+///
+/// Asks for the process being waited on to stop, so that the wait ends
+/// and the process can be looked at, e.g. when the user presses Ctrl-C.
+/// Only does what's safe in a signal handler, on any thread: the wait is
+/// woken up, and the thread tracing the process interrupts it from there.
+///
+pub fn request() {
+    REQUESTED.store(true, Ordering::SeqCst);
+    let fd = WAKE.load(Ordering::SeqCst);
+    if fd >= 0 {
+        unsafe { libc::write(fd, &WAKE_INTERRUPT as *const u8 as *const libc::c_void, 1) };
+    }
+}
+
+///
+/// Sets where requests are written to, to wake up the wait.
+///
+pub(crate) fn wake_with(fd: RawFd) {
+    WAKE.store(fd, Ordering::SeqCst);
+}
+
+///
+/// Marks the process as being waited on, until the returned guard is
+/// dropped. Any request made before now is forgotten, as is one made
+/// during the wait once it's over, since whatever ended the wait answered
+/// it.
+///
+pub(crate) fn waiting() -> Waiting {
+    REQUESTED.store(false, Ordering::SeqCst);
+    Waiting
+}
+
+///
+/// Whether the wait has been asked to end, leaving the request to be taken
+/// by whatever stop answers it.
+///
+pub(crate) fn requested() -> bool {
+    REQUESTED.load(Ordering::SeqCst)
+}
+
+///
+/// Whether the wait was asked to end, clearing the request.
+///
+pub(crate) fn take_request() -> bool {
+    REQUESTED.swap(false, Ordering::SeqCst)
+}
+
+pub(crate) struct Waiting;
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        REQUESTED.store(false, Ordering::SeqCst);
+    }
+}
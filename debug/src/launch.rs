@@ -0,0 +1,334 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use common::words;
+use common::words::Token;
+
+///
+/// How an inferior's program is launched: the arguments it's given, the
+/// changes made to the environment it inherits from the debugger, where it
+/// runs, and where its standard streams go. It carries over from one run
+/// to the next.
+///
+#[derive(Clone)]
+pub struct Launch {
+    /// The arguments passed to the program, after its own path.
+    pub args: Vec<String>,
+    /// Variables set (or, if `None`, removed) on top of the debugger's own
+    /// environment.
+    env: BTreeMap<String, Option<String>>,
+    /// The directory the program runs in, rather than the debugger's.
+    pub cwd: Option<PathBuf>,
+    pub stdin: Option<Redirect>,
+    pub stdout: Option<Redirect>,
+    pub stderr: Option<Redirect>,
+    /// Whether the program runs with address space randomization turned
+    /// off, so that its addresses are the same from one run to the next.
+    pub disable_randomization: bool,
+    /// The resource limits the program runs with, in place of those it
+    /// would inherit from the debugger.
+    pub limits: BTreeMap<Limit, u64>,
+}
+
+impl Default for Launch {
+    fn default() -> Self {
+        Self {
+            args: Vec::new(),
+            env: BTreeMap::new(),
+            cwd: None,
+            stdin: None,
+            stdout: None,
+            stderr: None,
+            disable_randomization: true,
+            limits: BTreeMap::new(),
+        }
+    }
+}
+
+///
+/// The resource limits a program can be launched with.
+///
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Limit {
+    /// The largest core file the program dumps, in bytes.
+    Core,
+    /// The largest the main thread's stack grows, in bytes.
+    Stack,
+    /// The most virtual memory the program maps, in bytes.
+    AddressSpace,
+}
+
+impl Limit {
+    pub fn resource(self) -> libc::__rlimit_resource_t {
+        match self {
+            Limit::Core => libc::RLIMIT_CORE,
+            Limit::Stack => libc::RLIMIT_STACK,
+            Limit::AddressSpace => libc::RLIMIT_AS,
+        }
+    }
+}
+
+impl FromStr for Limit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "core" => Ok(Limit::Core),
+            "stack" => Ok(Limit::Stack),
+            "as" | "address-space" => Ok(Limit::AddressSpace),
+            _ => Err(format!("expected `core`, `stack` or `as`, found {}", s)),
+        }
+    }
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Core => write!(f, "core"),
+            Limit::Stack => write!(f, "stack"),
+            Limit::AddressSpace => write!(f, "as"),
+        }
+    }
+}
+
+///
+/// Parses the value of a resource limit, as a number of bytes with an
+/// optional `K`, `M` or `G` suffix, or `unlimited`.
+///
+pub fn parse_limit(s: &str) -> Result<u64, String> {
+    if s == "unlimited" {
+        return Ok(libc::RLIM_INFINITY);
+    }
+    let (digits, scale) = match s.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&s[..i], 1 << 10),
+        Some((i, 'm' | 'M')) => (&s[..i], 1 << 20),
+        Some((i, 'g' | 'G')) => (&s[..i], 1 << 30),
+        _ => (s, 1),
+    };
+    digits.parse::<u64>().ok()
+        .and_then(|value| value.checked_mul(scale))
+        .ok_or_else(|| format!("expected a size or `unlimited`, found {}", s))
+}
+
+///
+/// Shows the value of a resource limit the way it was given.
+///
+pub fn show_limit(value: u64) -> String {
+    if value == libc::RLIM_INFINITY {
+        String::from("unlimited")
+    } else {
+        value.to_string()
+    }
+}
+
+///
+/// A file one of the program's standard streams is redirected from or to.
+///
+#[derive(Clone)]
+pub struct Redirect {
+    pub path: PathBuf,
+    /// Whether output is added to the end of the file, rather than
+    /// replacing what's in it.
+    pub append: bool,
+}
+
+impl Redirect {
+    fn new(path: &str, append: bool) -> Self {
+        Self { path: PathBuf::from(path), append }
+    }
+
+    ///
+    /// Where the file is for a program running in the given directory, or
+    /// the debugger's if none is given. A relative path is relative to where
+    /// the program runs, the same as it would be in a shell.
+    ///
+    pub fn path_in(&self, cwd: Option<&Path>) -> PathBuf {
+        match cwd {
+            Some(cwd) => cwd.join(&self.path),
+            None => self.path.clone(),
+        }
+    }
+
+    ///
+    /// Opens the file for a program running in the given directory to read
+    /// from.
+    ///
+    pub fn open_input(&self, cwd: Option<&Path>) -> io::Result<File> {
+        File::open(self.path_in(cwd))
+    }
+
+    ///
+    /// Opens the file for a program running in the given directory to write
+    /// to, creating it if needed.
+    ///
+    pub fn open_output(&self, cwd: Option<&Path>) -> io::Result<File> {
+        OpenOptions::new().write(true).create(true).append(self.append).truncate(!self.append).open(self.path_in(cwd))
+    }
+}
+
+impl Display for Redirect {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.path.display(), if self.append { " (appending)" } else { "" })
+    }
+}
+
+impl Launch {
+    ///
+    /// Sets the arguments and redirections from a command line, which is
+    /// split up the way a shell would, e.g. `-v 'input file.txt' > out.txt`.
+    /// The redirections are `< FILE`, `> FILE`, `>> FILE`, `2> FILE` and
+    /// `2>> FILE`, with or without a space before the file. Quoted, they're
+    /// passed to the program like any other argument.
+    ///
+    pub fn set_command_line(&mut self, line: &str) -> Result<(), String> {
+        let mut args = Vec::new();
+        let (mut stdin, mut stdout, mut stderr) = (None, None, None);
+
+        let mut tokens = words::tokenize(line)?.into_iter();
+        while let Some(token) = tokens.next() {
+            let op = match token {
+                Token::Word(word) => {
+                    args.push(word);
+                    continue;
+                }
+                Token::Redirect(op) => op,
+            };
+            let target = match tokens.next() {
+                Some(Token::Word(target)) => target,
+                _ => return Err(format!("expected a file after {}", op)),
+            };
+            match op {
+                "<" => stdin = Some(Redirect::new(&target, false)),
+                ">" | ">>" => stdout = Some(Redirect::new(&target, op == ">>")),
+                _ => stderr = Some(Redirect::new(&target, op == "2>>")),
+            }
+        }
+
+        self.args = args;
+        self.stdin = stdin;
+        self.stdout = stdout;
+        self.stderr = stderr;
+        Ok(())
+    }
+
+    pub fn set_env(&mut self, name: &str, value: &str) {
+        self.env.insert(name.to_string(), Some(value.to_string()));
+    }
+
+    ///
+    /// Removes a variable from the program's environment, even if the
+    /// debugger has it set.
+    ///
+    pub fn unset_env(&mut self, name: &str) {
+        self.env.insert(name.to_string(), None);
+    }
+
+    ///
+    /// The variables set or removed on top of the debugger's environment.
+    ///
+    pub fn env_changes(&self) -> impl Iterator<Item=(&str, Option<&str>)> {
+        self.env.iter().map(|(name, value)| (name.as_str(), value.as_deref()))
+    }
+
+    ///
+    /// The environment the program is started with, as `NAME=value`
+    /// strings: the debugger's own, with the changes made on top.
+    ///
+    pub fn environment(&self) -> Vec<String> {
+        let mut vars: BTreeMap<String, String> = env::vars_os()
+            .map(|(name, value)| (name.to_string_lossy().into_owned(), value.to_string_lossy().into_owned()))
+            .collect();
+        for (name, value) in &self.env {
+            match value {
+                Some(value) => vars.insert(name.clone(), value.clone()),
+                None => vars.remove(name),
+            };
+        }
+        vars.into_iter().map(|(name, value)| format!("{}={}", name, value)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn launch(line: &str) -> Launch {
+        let mut launch = Launch::default();
+        launch.set_command_line(line).unwrap();
+        launch
+    }
+
+    fn path(redirect: &Option<Redirect>) -> Option<(&str, bool)> {
+        redirect.as_ref().map(|r| (r.path.to_str().unwrap(), r.append))
+    }
+
+    #[test]
+    fn unquoted_operators_redirect() {
+        let launch = launch("-v <in.txt > out.txt 2>err.txt");
+        assert_eq!(launch.args, vec!["-v"]);
+        assert_eq!(path(&launch.stdin), Some(("in.txt", false)));
+        assert_eq!(path(&launch.stdout), Some(("out.txt", false)));
+        assert_eq!(path(&launch.stderr), Some(("err.txt", false)));
+    }
+
+    #[test]
+    fn operators_split_words() {
+        let launch = launch("a>b c2>d");
+        assert_eq!(launch.args, vec!["a", "c2"]);
+        assert_eq!(path(&launch.stdout), Some(("d", false)));
+    }
+
+    #[test]
+    fn appending() {
+        let launch = launch("x >> out.txt 2>> err.txt");
+        assert_eq!(launch.args, vec!["x"]);
+        assert_eq!(path(&launch.stdout), Some(("out.txt", true)));
+        assert_eq!(path(&launch.stderr), Some(("err.txt", true)));
+    }
+
+    #[test]
+    fn quoted_operators_are_arguments() {
+        let launch = launch(r#"'a>b' "<" x \> "2">y '2>>' z"#);
+        assert_eq!(launch.args, vec!["a>b", "<", "x", ">", "2", "2>>", "z"]);
+        assert!(launch.stdin.is_none());
+        assert!(launch.stderr.is_none());
+        assert_eq!(path(&launch.stdout), Some(("y", false)));
+    }
+
+    #[test]
+    fn quoted_targets() {
+        let launch = launch("> 'my file.txt'");
+        assert!(launch.args.is_empty());
+        assert_eq!(path(&launch.stdout), Some(("my file.txt", false)));
+    }
+
+    #[test]
+    fn missing_target() {
+        for line in &["x >", "x 2>>", "< > out.txt"] {
+            assert!(Launch::default().set_command_line(line).is_err(), "{}", line);
+        }
+    }
+
+    #[test]
+    fn failure_leaves_launch_alone() {
+        let mut launch = launch("a > out.txt");
+        assert!(launch.set_command_line("b <").is_err());
+        assert_eq!(launch.args, vec!["a"]);
+        assert_eq!(path(&launch.stdout), Some(("out.txt", false)));
+    }
+
+    #[test]
+    fn relative_to_program_directory() {
+        let launch = launch("< in.txt > /abs/out.txt");
+        let cwd = Path::new("/work");
+        assert_eq!(launch.stdin.as_ref().unwrap().path_in(Some(cwd)), Path::new("/work/in.txt"));
+        assert_eq!(launch.stdin.as_ref().unwrap().path_in(None), Path::new("in.txt"));
+        assert_eq!(launch.stdout.as_ref().unwrap().path_in(Some(cwd)), Path::new("/abs/out.txt"));
+    }
+}
@@ -4,25 +4,64 @@ extern crate libc;
 extern crate log;
 extern crate nix;
 
-use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
 
-use log::info;
+use log::{info, warn};
 
 use common::config::Config;
 
-use crate::breakpoint::Breakpoint;
+use crate::breakpoint::{Breakpoint, Breakpoints, Catch, Kind};
+use crate::breakpoint::condition::Condition;
+use crate::breakpoint::location::Location;
+use crate::coredump::Core;
 use crate::error::{DebugError, Reason, Result};
-use crate::process::Process;
+use crate::inferior::Inferiors;
+use crate::inspect::Inspect;
+use crate::launch::{show_limit, Limit};
+use crate::module::Module;
+use crate::module::link_map::DynamicLinker;
+use crate::process::{auxv, list, maps, Process, Stop};
+use crate::settings::{FollowForkMode, SchedulerLocking, Settings};
+use crate::signals::{Signal, SignalAction, SignalInfo, Signals};
+use crate::syscalls::{SyscallFilter, SyscallInfo, SyscallTrace};
+use crate::trace::Registers;
 
 pub mod trace;
 pub mod process;
 pub mod error;
 pub mod breakpoint;
+pub mod module;
+pub mod registers;
+pub mod settings;
+pub mod inferior;
+pub mod signals;
+pub mod crash;
+pub mod syscalls;
+pub mod launch;
+pub mod interrupt;
+pub mod inspect;
+pub mod coredump;
+#[cfg(test)]
+mod testing;
+
+/// How often `/proc` is looked through while waiting for a process to
+/// start, which is often enough to catch it early on, without keeping a
+/// CPU busy.
+const WAIT_FOR_INTERVAL: Duration = Duration::from_millis(20);
 
 pub struct Debugger {
-    process: RefCell<Option<Process>>,
-    file: Option<String>,
-    breakpoints: Vec<Breakpoint>,
+    /// Every program being debugged. Only the current one's process is
+    /// resumed, while the others are kept stopped.
+    inferiors: Inferiors,
+    breakpoints: Breakpoints,
+    settings: Settings,
+    /// What to do when the process receives each signal.
+    signals: Signals,
+    /// The system calls being logged as the processes make them.
+    syscall_trace: Option<SyscallTrace>,
 }
 
 impl Debugger {
@@ -31,41 +70,214 @@ impl Debugger {
     /// set itself up read to debug the target, whether that's a
     /// running process, where we immediately try to attach,
     /// or a binary file on disk, in which case it will be launched
-    /// manually later.
+    /// manually later. If we're to wait for a process to start, this
+    /// doesn't return until it has.
     ///
     pub fn from_config<C: Into<Config>>(cfg: C) -> Self {
         let cfg = cfg.into();
 
-        let process = if cfg.should_attach {
-            Some(Process::attach(cfg.pid.unwrap()))
-        } else {
-            None
+        let mut inferiors = Inferiors::default();
+        inferiors.current_mut().file = cfg.file;
+
+        let mut debugger = Self {
+            inferiors,
+            breakpoints: Breakpoints::default(),
+            settings: Settings::default(),
+            signals: Signals::default(),
+            syscall_trace: None,
+        };
+        let attached = match (cfg.should_attach, cfg.await_start, cfg.pid, cfg.name) {
+            (true, _, Some(pid), _) => debugger.attach(pid),
+            (_, true, _, Some(name)) => debugger.wait_for(&name),
+            _ => Ok(()),
         };
+        if let Err(e) = attached {
+            warn!("failed to attach: {}", e);
+        }
+        if let Err(e) = cfg.core.map_or(Ok(()), |core| debugger.load_core(&core)) {
+            warn!("failed to load core file: {}", e);
+        }
+        debugger
+    }
+
+    ///
+    /// Attaches to a running process in the current inferior, stopping it.
+    /// Its program and the libraries it has already loaded are found through
+    /// `/proc`, and any pending breakpoints in them are inserted.
+    ///
+    pub fn attach(&mut self, pid: i32) -> Result<()> {
+        if self.inferiors.current().process.is_some() {
+            return Err(DebugError::InvalidOperation(Reason::ProcessExists));
+        }
+
+        let path = fs::read_link(format!("/proc/{}/exe", pid)).map_err(DebugError::Sys)?;
+        self.drop_core();
+        let mut process = Process::attach(pid)?;
+        process.set_non_stop(self.settings.non_stop);
+        info!("attached to process {}: {}", pid, path.display());
+
+        self.inferiors.current_mut().file = Some(path.to_string_lossy().into_owned());
+        let loaded = self.load_executable(&mut process, path)
+            .and_then(|_| self.libraries_changed(&mut process));
+        if let Err(e) = loaded {
+            // we can't debug it without its program, so let go of it rather
+            // than leave it stopped.
+            self.remove_breakpoints(&mut process);
+            let _ = process.detach();
+            self.forget_program(self.inferiors.current_id());
+            return Err(e);
+        }
+
+        self.inferiors.current_mut().process = Some(process);
+        self.update_syscall_tracing();
+        Ok(())
+    }
 
-        Self {
-            process: RefCell::new(process),
-            file: cfg.file,
-            breakpoints: Vec::new(),
+    ///
+    /// Waits for a process with the given name to start, and attaches to it
+    /// as soon as it's seen. Processes already running by that name are
+    /// passed over. Only the name the kernel knows each process by is
+    /// looked at, so e.g. `./app` is found as `app`.
+    ///
+    /// The wait is given up on if it's interrupted, e.g. by the user
+    /// pressing Ctrl-C, leaving us without a process.
+    ///
+    pub fn wait_for(&mut self, name: &str) -> Result<()> {
+        let mut seen = list::pids_named(name)?;
+        interrupt::take_request();
+        info!("waiting for a process named {} to start...", name);
+        loop {
+            for pid in list::pids_named(name)? {
+                if seen.contains(&pid) {
+                    continue;
+                }
+                match self.attach(pid) {
+                    Ok(()) => return Ok(()),
+                    Err(e) => warn!("failed to attach to process {}: {}", pid, e),
+                }
+                seen.push(pid);
+            }
+            if interrupt::take_request() {
+                info!("stopped waiting for {}.", name);
+                return Ok(());
+            }
+            thread::sleep(WAIT_FOR_INTERVAL);
         }
     }
 
     ///
-    /// Run the debuggee process, using the file path from the config.
-    /// the new process is immediately traced and is waiting to continue.
+    /// Opens a core file left behind by the current inferior's program,
+    /// which is examined in place of a process until one is started. The
+    /// program is the inferior's file if it has one, or otherwise whichever
+    /// file the core says the process was running. The libraries are those
+    /// the core says were mapped.
     ///
-    pub fn run(&self, args: Vec<String>, env: Vec<String>) -> Result<()> {
-        {
-            let maybe_process = self.process.borrow();
-            if maybe_process.is_some() {
-                return Err(DebugError::InvalidOperation(Reason::ProcessExists));
+    pub fn load_core(&mut self, path: &str) -> Result<()> {
+        if self.inferiors.current().process().is_some() {
+            return Err(DebugError::InvalidOperation(Reason::ProcessExists));
+        }
+
+        let core = Core::open(path)?;
+        let file = match self.inferiors.current().file.clone() {
+            Some(file) => file,
+            None => auxv::get(&core.auxv()?, auxv::AT_ENTRY)
+                .and_then(|entry| core.mapped_file(entry))
+                .map(String::from)
+                .ok_or(DebugError::InvalidOperation(Reason::NoFile))?,
+        };
+        info!("core was generated by `{}`.", core.command);
+        if let Some(info) = core.signal() {
+            info!("program terminated with signal {}.", info);
+        }
+
+        self.drop_core();
+        let id = self.inferiors.current_id();
+        self.forget_program(id);
+        let maps = core.maps()?;
+        let executable = PathBuf::from(&file);
+        match Module::find_base(&executable, &maps)? {
+            Some(base) => self.inferiors.current_mut().modules.add(Module::load(executable, base)?),
+            None => warn!("{} is not mapped into the core file", file),
+        }
+
+        // the libraries are whatever else was mapped from the start of a
+        // file, as long as it's still around to read symbols from.
+        let executable = fs::canonicalize(&file).map_err(DebugError::Sys)?;
+        let mut libraries: Vec<&str> = maps.iter()
+            .filter(|map| map.offset == 0)
+            .filter_map(|map| map.path.as_deref())
+            .filter(|path| Path::new(path) != executable && Path::new(path).is_file())
+            .collect();
+        libraries.dedup();
+        for library in libraries {
+            let loaded = Module::find_base(Path::new(library), &maps)
+                .and_then(|base| base.map(|base| Module::load(library, base)).transpose());
+            match loaded {
+                Ok(Some(module)) => self.inferiors.current_mut().modules.add(module),
+                Ok(None) => {}
+                Err(e) => warn!("failed to load symbols for {}: {}", library, e),
             }
         }
 
-        match &self.file {
+        if let Some(info) = core.signal() {
+            self.report_crash(&core, core.current_tid(), &info)?;
+        }
+        let inferior = self.inferiors.current_mut();
+        inferior.file = Some(file);
+        inferior.core = Some(core);
+        Ok(())
+    }
+
+    ///
+    /// Stops examining the current inferior's core file, if it has one,
+    /// forgetting the modules that were loaded for it.
+    ///
+    pub fn drop_core(&mut self) {
+        let inferior = self.inferiors.current_mut();
+        if let Some(core) = inferior.core.take() {
+            info!("no longer examining core file {}", core.path.display());
+            let id = inferior.id;
+            self.forget_program(id);
+        }
+    }
+
+    ///
+    /// Run the debuggee process in the current inferior, using its file path
+    /// and launch configuration. The new process is immediately traced and is
+    /// waiting to continue.
+    ///
+    /// Any pending breakpoints in the executable are inserted before
+    /// it gets the chance to run. Breakpoints in shared libraries are
+    /// inserted as the dynamic linker loads them.
+    ///
+    pub fn run(&mut self) -> Result<()> {
+        let inferior = self.inferiors.current();
+        if inferior.process.is_some() {
+            return Err(DebugError::InvalidOperation(Reason::ProcessExists));
+        }
+
+        match inferior.file.clone() {
             Some(file) => {
-                let process = Process::start(file.clone(), args, Some(env))?;
+                self.drop_core();
+                let inferior = self.inferiors.current();
+                // a process can't be rid of a seccomp filter, and once we've
+                // let go of it, the calls the filter traps fail with ENOSYS,
+                // so it's only used while forked children are kept too, and
+                // the process can't be detached from.
+                let trace = self.syscall_trace.as_ref().map(|trace| &trace.filter);
+                let mut syscalls: Vec<_> = self.breakpoints.syscall_filters(inferior.id).chain(trace).collect();
+                if self.settings.detach_on_fork && !syscalls.is_empty() {
+                    warn!("stopping on every syscall to catch or trace some of them; set detach-on-fork off to stop on just those.");
+                    syscalls.clear();
+                }
+                let mut process = Process::start(file.clone(), &inferior.launch, &syscalls)?;
+                process.set_non_stop(self.settings.non_stop);
                 info!("started process: {}", process.pid);
-                self.process.replace(Some(process));
+
+                self.load_executable(&mut process, PathBuf::from(file))?;
+
+                self.inferiors.current_mut().process = Some(process);
+                self.update_syscall_tracing();
                 Ok(())
             }
             None => Err(DebugError::InvalidOperation(Reason::NoFile))
@@ -73,121 +285,1522 @@ impl Debugger {
     }
 
     ///
-    /// Checks whether the traced process is currently alive. It may
-    /// be in a stopped state, but still exists.
+    /// Sets the arguments the current inferior's program is run with, and
+    /// the files its standard streams are redirected to, from a command
+    /// line, e.g. `-v input.txt > out.txt`.
+    ///
+    pub fn set_args(&mut self, line: &str) -> Result<()> {
+        let launch = &mut self.inferiors.current_mut().launch;
+        launch.set_command_line(line).map_err(|e| DebugError::InvalidOperation(Reason::InvalidArguments(e)))
+    }
+
+    ///
+    /// Sets a variable in the environment the current inferior's program
+    /// is run with, on top of the debugger's own.
+    ///
+    pub fn set_env(&mut self, name: &str, value: &str) {
+        self.inferiors.current_mut().launch.set_env(name, value);
+        info!("{} will be set to {}", name, value);
+    }
+
+    ///
+    /// Removes a variable from the environment the current inferior's
+    /// program is run with, even if the debugger has it set.
+    ///
+    pub fn unset_env(&mut self, name: &str) {
+        self.inferiors.current_mut().launch.unset_env(name);
+        info!("{} will be unset", name);
+    }
+
+    ///
+    /// Sets the directory the current inferior's program is run in, or goes
+    /// back to the debugger's own if none is given.
+    ///
+    pub fn set_cwd(&mut self, cwd: Option<PathBuf>) {
+        match &cwd {
+            Some(cwd) => info!("working directory is {}", cwd.display()),
+            None => info!("working directory is the debugger's"),
+        }
+        self.inferiors.current_mut().launch.cwd = cwd;
+    }
+
+    ///
+    /// Sets whether the current inferior's program is run with address space
+    /// randomization turned off, so its addresses stay the same between runs.
+    ///
+    pub fn set_disable_randomization(&mut self, disable: bool) {
+        self.inferiors.current_mut().launch.disable_randomization = disable;
+        info!("disable-randomization is {}.", if disable { "on" } else { "off" });
+    }
+
+    ///
+    /// Sets a resource limit the current inferior's program is run with, or
+    /// goes back to the one inherited from the debugger if none is given.
+    ///
+    pub fn set_limit(&mut self, limit: Limit, value: Option<u64>) {
+        let limits = &mut self.inferiors.current_mut().launch.limits;
+        match value {
+            Some(value) => {
+                limits.insert(limit, value);
+                info!("{} limit will be {}", limit, show_limit(value));
+            }
+            None => {
+                limits.remove(&limit);
+                info!("{} limit will be inherited", limit);
+            }
+        }
+    }
+
+    ///
+    /// Logs how the current inferior's program will be launched.
+    ///
+    pub fn show_launch(&self) {
+        let launch = &self.inferiors.current().launch;
+        info!("arguments: {}", launch.args.join(" "));
+        match &launch.cwd {
+            Some(cwd) => info!("working directory: {}", cwd.display()),
+            None => info!("working directory: the debugger's"),
+        }
+        for (name, redirect) in [("stdin", &launch.stdin), ("stdout", &launch.stdout), ("stderr", &launch.stderr)] {
+            if let Some(redirect) = redirect {
+                info!("{} redirected to {}", name, redirect);
+            }
+        }
+        info!("address space randomization: {}", if launch.disable_randomization { "disabled" } else { "enabled" });
+        for (limit, value) in &launch.limits {
+            info!("{} limit: {}", limit, show_limit(*value));
+        }
+        for (name, value) in launch.env_changes() {
+            match value {
+                Some(value) => info!("environment: {}={}", name, value),
+                None => info!("environment: {} unset", name),
+            }
+        }
+    }
+
+    ///
+    /// Kills the current inferior's process, forgetting everything tied to
+    /// it. Its breakpoints are left pending for the next run.
+    ///
+    pub fn kill(&mut self) -> Result<()> {
+        let mut process = self.take_process()?;
+        let result = process.kill();
+        if result.is_ok() {
+            info!("killed process {}", process.pid);
+        }
+        self.put_process(process);
+        result
+    }
+
+    ///
+    /// Kills the current inferior's process, if it has one, and runs the
+    /// program again with the same launch configuration. The breakpoints
+    /// are inserted into the new process as its modules are loaded, the
+    /// same as they were in the old one, and the syscalls caught or traced
+    /// are stopped on from the start.
+    ///
+    /// There are no watch expressions or displays to re-apply, since the
+    /// debugger doesn't have them yet.
+    ///
+    pub fn restart(&mut self) -> Result<()> {
+        if self.inferiors.current().process().is_some() {
+            self.kill()?;
+        }
+        self.run()
+    }
+
+    ///
+    /// Checks whether any inferior's traced process is currently alive. It
+    /// may be in a stopped state, but still exists.
     ///
     pub fn process_is_running(&mut self) -> bool {
-        match &*self.process.borrow() {
-            Some(process) => process.running,
-            _ => false
+        self.inferiors.iter().any(|inferior| inferior.process().is_some())
+    }
+
+    ///
+    /// Runs the debuggee process in the current inferior, and continues it
+    /// up to the start of `main`, where it's stopped by a temporary
+    /// breakpoint.
+    ///
+    pub fn start(&mut self) -> Result<()> {
+        let main = Location::Symbol(String::from("main"));
+        self.run()?;
+        if self.inferiors.current().modules.executable().and_then(|module| main.resolve(module)).is_none() {
+            return Err(DebugError::InvalidOperation(Reason::NoSymbol(main.to_string())));
+        }
+        self.run_to(main)
+    }
+
+    ///
+    /// Runs the debuggee process in the current inferior, leaving it stopped
+    /// at the very first instruction it runs, which is usually in the dynamic
+    /// linker. With `entry`, it's continued up to the entry point of the
+    /// program itself instead.
+    ///
+    pub fn start_instruction(&mut self, entry: bool) -> Result<()> {
+        self.run()?;
+        let process = self.inferiors.current().process().ok_or(DebugError::InvalidOperation(Reason::NoProcess))?;
+        if entry {
+            let entry = auxv::get(&auxv::read(process.pid)?, auxv::AT_ENTRY)
+                .ok_or(DebugError::InvalidOperation(Reason::NoSymbol(String::from("entry point"))))?;
+            return self.run_to(Location::Address(entry));
         }
+
+        let pc = process.current_thread().pc()?;
+        info!("stopped at the first instruction, at {}", self.inferiors.current().modules.describe(pc));
+        Ok(())
+    }
+
+    ///
+    /// Continues the current inferior's process until it reaches the given
+    /// location, with a temporary breakpoint there.
+    ///
+    fn run_to(&mut self, location: Location) -> Result<()> {
+        let mut bp = Breakpoint::new(location);
+        bp.inferior = Some(self.inferiors.current_id());
+        bp.temporary = true;
+        self.insert_breakpoint(bp)?;
+        self.proceed()
     }
 
+    ///
+    /// Detaches from every traced process, leaving them to run freely. Every
+    /// breakpoint is removed from a process first, so that it can't trap on
+    /// one once we've gone.
+    ///
+    /// Nothing is detached from while a process has a seccomp filter, since
+    /// the calls it traps would fail from then on.
+    ///
     pub fn detach(&mut self) -> Result<()> {
-        match &mut *self.process.borrow_mut() {
-            Some(process) => process.detach(),
-            None => Ok(())
+        let filtered = self.inferiors.iter()
+            .filter_map(|inferior| inferior.process())
+            .find(|process| process.has_seccomp_filter());
+        if let Some(process) = filtered {
+            return Err(DebugError::InvalidOperation(Reason::SeccompFilter(process.pid)));
+        }
+        let mut result = Ok(());
+        for id in self.inferiors.ids() {
+            result = result.and(self.detach_inferior(id));
+        }
+        result
+    }
+
+    ///
+    /// Kills every traced process, forgetting everything tied to them.
+    ///
+    pub fn kill_all(&mut self) -> Result<()> {
+        let mut result = Ok(());
+        for id in self.inferiors.ids() {
+            result = result.and(self.kill_inferior(id));
+        }
+        result
+    }
+
+    ///
+    /// Lets go of every traced process on the way out of the debugger. The
+    /// processes we launched are killed if kill-on-exit is set, and the
+    /// rest are detached from, to carry on without us. A process with a
+    /// seccomp filter is killed regardless, since the calls it traps would
+    /// fail once we're gone.
+    ///
+    pub fn quit(&mut self) -> Result<()> {
+        let mut result = Ok(());
+        for id in self.inferiors.ids() {
+            let process = self.inferiors.get(id)?.process();
+            let kill = process.is_some_and(|process| self.killed_on_exit(process));
+            if let Some(process) = process.filter(|_| kill && !self.settings.kill_on_exit) {
+                warn!("killing process {}, which has a seccomp filter, and can't run without us.", process.pid);
+            }
+            result = result.and(if kill {
+                self.kill_inferior(id)
+            } else {
+                self.detach_inferior(id)
+            });
+        }
+        result
+    }
+
+    ///
+    /// Whether quitting kills any process, rather than only detaching.
+    ///
+    pub fn kills_on_exit(&self) -> bool {
+        self.inferiors.iter().any(|inferior| inferior.process().is_some_and(|process| self.killed_on_exit(process)))
+    }
+
+    fn killed_on_exit(&self, process: &Process) -> bool {
+        !process.attached && (self.settings.kill_on_exit || process.has_seccomp_filter())
+    }
+
+    fn detach_inferior(&mut self, id: u32) -> Result<()> {
+        let inferior = self.inferiors.get_mut(id)?;
+        let (process, parent) = (inferior.process.take(), inferior.vfork_parent.take());
+        if let Some(parent) = parent {
+            self.release(parent);
+        }
+
+        let mut result = Ok(());
+        if let Some(mut process) = process.filter(|process| process.running) {
+            result = process.stop().and_then(|_| {
+                self.remove_breakpoints(&mut process);
+                process.detach()
+            });
+        }
+        self.forget_program(id);
+        result
+    }
+
+    ///
+    /// Kills an inferior's process, along with the vfork parent waiting on
+    /// it, if there is one.
+    ///
+    fn kill_inferior(&mut self, id: u32) -> Result<()> {
+        let inferior = self.inferiors.get_mut(id)?;
+        let (process, parent) = (inferior.process.take(), inferior.vfork_parent.take());
+
+        let mut result = Ok(());
+        for mut process in process.into_iter().chain(parent).filter(|process| process.running) {
+            let killed = process.kill();
+            if killed.is_ok() {
+                info!("killed process {}", process.pid);
+            }
+            result = result.and(killed);
         }
+        self.forget_program(id);
+        result
     }
 
     ///
     /// Continue the traced process. Continues until a signal is received,
     /// or the process exits.
     ///
-    pub fn proceed(&self) -> Result<()> {
-        match &mut *self.process.borrow_mut() {
-            Some(process) => process.proceed(),
-            None => Err(DebugError::InvalidOperation(Reason::NoProcess))
-        }
+    /// Breakpoints whose condition doesn't hold are passed over without
+    /// stopping, as are the dynamic linker's reports of libraries being
+    /// loaded, unless a catchpoint is interested in them.
+    ///
+    /// In non-stop mode, only the current thread is resumed, and this waits
+    /// for the next event from any of the running threads.
+    ///
+    pub fn proceed(&mut self) -> Result<()> {
+        let mut process = self.take_process()?;
+        let result = if self.settings.non_stop {
+            let tid = process.current_thread().tid;
+            self.resume_threads(&mut process, &[tid])
+        } else {
+            self.proceed_process(&mut process)
+        };
+        self.put_process(process);
+        result
     }
 
     ///
-    /// Add a new breakpoint at the given address in the traced process.
-    /// The address is expected to be absolute.
+    /// Resumes a single thread, or every stopped thread if none is given,
+    /// leaving the others as they are. Waits for the next event from any
+    /// of the running threads. Only available in non-stop mode.
     ///
-    /// TODO: abstract the breakpoint location so that it can be a source location
-    ///       or a function, or an actual address. Might need our own CLI parser...
+    pub fn proceed_threads(&mut self, id: Option<u32>) -> Result<()> {
+        if !self.settings.non_stop {
+            return Err(DebugError::InvalidOperation(Reason::RequiresNonStop));
+        }
+
+        let mut process = self.take_process()?;
+        let result = thread_ids(&process, id, true)
+            .and_then(|tids| self.resume_threads(&mut process, &tids));
+        self.put_process(process);
+        result
+    }
+
     ///
-    pub fn add_breakpoint(&mut self, addr: u64) -> Result<()> {
-        match &*self.process.borrow() {
-            Some(process) => {
-                let mut bp = Breakpoint::new(addr);
-                bp.enable(process)?;
-                self.breakpoints.push(bp);
-                Ok(())
+    /// Stops a single running thread, or every running thread if none is
+    /// given, leaving the others as they are. Only available in non-stop mode.
+    ///
+    pub fn interrupt_threads(&mut self, id: Option<u32>) -> Result<()> {
+        if !self.settings.non_stop {
+            return Err(DebugError::InvalidOperation(Reason::RequiresNonStop));
+        }
+
+        let mut process = self.take_process()?;
+        let result = thread_ids(&process, id, false).and_then(|tids| {
+            for tid in tids {
+                if let Some((tid, stop)) = process.interrupt_thread(tid)? {
+                    self.handle_event(&mut process, tid, &stop)?;
+                }
             }
-            None => Err(DebugError::InvalidOperation(Reason::NoProcess))
+            Ok(())
+        });
+        self.put_process(process);
+        result
+    }
+
+    ///
+    /// Reports any events that have happened to the running threads since
+    /// we last looked, without waiting for more. Only does anything in
+    /// non-stop mode, since otherwise nothing runs in the background.
+    ///
+    pub fn poll_events(&mut self) -> Result<()> {
+        if !self.settings.non_stop || self.inferiors.current().process().is_none() {
+            return Ok(());
         }
+
+        let mut process = self.take_process()?;
+        let result = self.wait_events(&mut process, false);
+        self.put_process(process);
+        result
     }
 
     ///
-    /// Removes a breakpoint, including disabling it in the traced process.
+    /// Single steps the current thread by one instruction. Whether other
+    /// threads run while it does depends on the scheduler locking setting,
+    /// and they never do in non-stop mode.
     ///
-    pub fn remove_breakpoint(&mut self, idx: usize) -> Result<()> {
-        match &*self.process.borrow() {
-            Some(process) => {
-                if let Some(bp) = self.breakpoints.get_mut(idx) {
-                    if bp.enabled {
-                        bp.disable(process)?;
-                    }
-                } else {
-                    return Err(DebugError::InvalidOperation(Reason::NoSuchBreakpoint));
+    pub fn step_instruction(&mut self) -> Result<()> {
+        let mut process = self.take_process()?;
+        let only_current = self.settings.scheduler_locking != SchedulerLocking::Off;
+        let stepping = process.current_thread().id;
+        let result = (|| loop {
+            let stop = process.step(only_current)?;
+            let tid = process.current_thread().tid;
+            if self.handle_event(&mut process, tid, &stop)? || !process.running {
+                return Ok(());
+            }
+
+            // another thread stopped for something not worth reporting
+            // before the step finished, so carry on with it.
+            process.select_thread(stepping)?;
+        })();
+        self.put_process(process);
+        result
+    }
+
+    fn take_process(&mut self) -> Result<Process> {
+        self.inferiors.current_mut().process.take().ok_or(DebugError::InvalidOperation(Reason::NoProcess))
+    }
+
+    ///
+    /// Puts back the process taken while it was being continued, or forgets
+    /// everything tied to it if it's gone away in the meantime.
+    ///
+    fn put_process(&mut self, process: Process) {
+        if process.running {
+            self.inferiors.current_mut().process = Some(process);
+        } else {
+            self.process_gone();
+        }
+    }
+
+    fn proceed_process(&mut self, process: &mut Process) -> Result<()> {
+        loop {
+            let stop = process.proceed(self.settings.scheduler_locking == SchedulerLocking::On)?;
+            let tid = process.current_thread().tid;
+            if self.handle_event(process, tid, &stop)? {
+                return Ok(());
+            }
+        }
+    }
+
+    fn resume_threads(&mut self, process: &mut Process, tids: &[i32]) -> Result<()> {
+        for tid in tids {
+            if let Some((tid, stop)) = process.resume_thread(*tid)? {
+                if self.report_event(process, tid, stop)? {
+                    return Ok(());
                 }
+            }
+        }
+        self.wait_events(process, true)
+    }
 
-                self.breakpoints.remove(idx);
-                info!("breakpoint removed.");
-                Ok(())
+    ///
+    /// Waits for events from the running threads in non-stop mode, reporting
+    /// them. If `block` is set, this returns after the first event that is
+    /// reported, otherwise once there is nothing left to report.
+    ///
+    fn wait_events(&mut self, process: &mut Process, block: bool) -> Result<()> {
+        while let Some((tid, stop)) = process.wait(block)? {
+            if self.report_event(process, tid, stop)? && block {
+                break;
             }
-            None => Err(DebugError::InvalidOperation(Reason::NoProcess))
         }
+        Ok(())
     }
 
     ///
-    /// Logs all the known breakpoints in the debugger.
+    /// Handles an event in non-stop mode, resuming the thread it happened
+    /// to if it isn't worth stopping for. Returns whether it was reported.
     ///
-    pub fn list_breakpoints(&mut self) {
-        for (i, bp) in self.breakpoints.iter().enumerate() {
-            info!("{}: addr: {:x} {}", i + 1, bp.addr, if bp.enabled { "(enabled)" } else { "" });
+    fn report_event(&mut self, process: &mut Process, mut tid: i32, mut stop: Stop) -> Result<bool> {
+        loop {
+            if self.handle_event(process, tid, &stop)? {
+                return Ok(true);
+            }
+
+            // we may have switched to following a forked child
+            if process.threads().get(tid).is_none() {
+                tid = process.current_thread().tid;
+            }
+            match process.resume_thread(tid)? {
+                Some((next, next_stop)) => {
+                    tid = next;
+                    stop = next_stop;
+                }
+                None => return Ok(false),
+            }
         }
     }
 
     ///
-    /// Enables the given breakpoint in the traced process.
+    /// Handles a thread stopping, returning whether the user should be told
+    /// about it, or whether it should just keep going.
     ///
-    pub fn enable_breakpoint(&mut self, idx: usize) -> Result<()> {
-        match &*self.process.borrow() {
-            Some(process) => {
-                if let Some(bp) = self.breakpoints.get_mut(idx) {
-                    if !bp.enabled {
-                        bp.enable(process)?;
-                        info!("breakpoint at 0x{:x} enabled.", bp.addr);
+    fn handle_event(&mut self, process: &mut Process, tid: i32, stop: &Stop) -> Result<bool> {
+        let thread_id = process.threads().get(tid).map(|thread| thread.id);
+        let thread = match thread_id {
+            Some(id) if process.threads().len() > 1 || self.settings.non_stop => format!(" in thread {}", id),
+            _ => String::new(),
+        };
+
+        let inferior = self.inferiors.current_id();
+        let located = self.located(inferior);
+        match *stop {
+            Stop::Breakpoint(addr) => {
+                let mut stopped = false;
+                if self.inferiors.current().linker.as_ref().map(|linker| linker.hook) == Some(addr) {
+                    stopped = self.libraries_changed(process)?;
+                }
+
+                let regs = match process.threads().get(tid) {
+                    Some(thread) => thread.registers()?,
+                    None => return Ok(stopped),
+                };
+                let modules = &self.inferiors.current().modules;
+                let mut spent = Vec::new();
+                for bp in self.breakpoints.at_mut(inferior, addr) {
+                    if bp.thread.is_some() && bp.thread != thread_id {
+                        continue;
                     }
-                    Ok(())
-                } else {
-                    Err(DebugError::InvalidOperation(Reason::NoSuchBreakpoint))
+                    if bp.condition.as_ref().is_none_or(|c| c.holds(&regs)) {
+                        bp.hits += 1;
+                        let kind = if bp.temporary { "temporary breakpoint" } else { "breakpoint" };
+                        info!("{} {} hit{}{} at {}", kind, bp.id, located, thread, modules.describe(addr));
+                        if bp.temporary {
+                            spent.push(bp.id);
+                        }
+                        stopped = true;
+                    }
+                }
+                for id in spent {
+                    self.remove_temporary(process, id)?;
+                }
+                Ok(stopped)
+            }
+            Stop::Forked { child, vfork } => {
+                self.process_forked(process, child, vfork)?;
+                Ok(false)
+            }
+            Stop::Exec => {
+                self.forget_program(inferior);
+                if let Some(parent) = self.inferiors.current_mut().vfork_parent.take() {
+                    self.release(parent);
+                }
+
+                let path = fs::read_link(format!("/proc/{}/exe", process.pid)).map_err(DebugError::Sys)?;
+                info!("process {} is executing new program: {}", process.pid, path.display());
+                self.inferiors.current_mut().file = Some(path.to_string_lossy().into_owned());
+                self.load_executable(process, path)?;
+                Ok(false)
+            }
+            Stop::Stepped => {
+                let pc = process.threads().get(tid).map_or(Ok(0), |thread| thread.pc())?;
+                info!("stepped{}{} to {}", located, thread, self.inferiors.current().modules.describe(pc));
+                Ok(true)
+            }
+            Stop::Signal(info) => {
+                let policy = self.signals.get(info.signal);
+                if !policy.pass {
+                    process.discard_signal(tid);
+                }
+                if policy.print {
+                    let pc = process.threads().get(tid).map_or(Ok(0), |thread| thread.pc())?;
+                    info!("received signal {}{}{} at {}", info, located, thread, self.inferiors.current().modules.describe(pc));
                 }
+                if policy.stop {
+                    self.report_crash(process, tid, &info)?;
+                }
+                Ok(policy.stop)
+            }
+            Stop::Syscall(info) => {
+                if let Some(trace) = self.syscall_trace.as_mut() {
+                    trace.log(process, tid, &info);
+                }
+                Ok(self.catch_syscall(process, &info, thread_id, &format!("{}{}", located, thread)))
+            }
+            Stop::Interrupted => {
+                let pc = process.threads().get(tid).map_or(Ok(0), |thread| thread.pc())?;
+                info!("interrupted{}{} at {}", located, thread, self.inferiors.current().modules.describe(pc));
+                Ok(true)
+            }
+            _ => Ok(true)
+        }
+    }
+
+    ///
+    /// If a signal looks like the process crashing, logs a first guess at
+    /// why, along with the faulting instruction and the backtrace.
+    ///
+    fn report_crash(&self, target: &dyn Inspect, tid: i32, info: &SignalInfo) -> Result<()> {
+        let regs = target.thread_registers(tid)?;
+        let code: Vec<u8> = [regs.rip, regs.rip.wrapping_add(8)].iter()
+            .map_while(|addr| target.read(*addr).ok())
+            .flat_map(i64::to_ne_bytes)
+            .collect();
+        let crash = match crash::diagnose(info, &regs, &target.maps()?, &code) {
+            Some(crash) => crash,
+            None => return Ok(()),
+        };
+
+        let modules = &self.inferiors.current().modules;
+        info!("crash: {}", crash);
+        let bytes = match code.get(..8) {
+            Some(bytes) => bytes.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" ") + " ...",
+            None => String::from("<unreadable>"),
+        };
+        info!("faulting instruction at {}: {}", modules.describe(regs.rip), bytes);
+        info!("backtrace:");
+        self.log_backtrace(target, &regs);
+        Ok(())
+    }
+
+    ///
+    /// Logs the frames of the stack of a thread with the given registers.
+    ///
+    fn log_backtrace(&self, target: &dyn Inspect, regs: &Registers) {
+        let modules = &self.inferiors.current().modules;
+        let frames = modules.backtrace(target, regs);
+        let mut i = 0;
+        while i < frames.len() {
+            // runs of the same return address, as left by a recursion, are
+            // shown once rather than filling the screen
+            let repeats = frames[i..].iter().take_while(|pc| **pc == frames[i]).count();
+            info!("#{:<3} {}", i, modules.describe(frames[i]));
+            if repeats > 2 {
+                info!("     ... repeated {} more times", repeats - 1);
+                i += repeats;
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    ///
+    /// Decides which of a parent and its newly forked child to keep debugging,
+    /// and what to do with the other one, detaching from it or holding it
+    /// stopped as a new inferior.
+    ///
+    fn process_forked(&mut self, process: &mut Process, child: i32, vfork: bool) -> Result<()> {
+        let mut other = process.forked(child);
+        info!("process {} {} child process {}", process.pid, if vfork { "vforked" } else { "forked" }, child);
+
+        if self.settings.follow_fork_mode == FollowForkMode::Child {
+            std::mem::swap(process, &mut other);
+            info!("following child process {}", process.pid);
+        }
+
+        if !self.settings.detach_on_fork {
+            let pid = other.pid;
+            let id = self.add_forked_inferior(other);
+            info!("holding process {} stopped as inferior {}", pid, id);
+            if vfork && pid == child {
+                warn!("process {} can't run again until its vfork child {} does.", process.pid, child);
             }
-            None => Err(DebugError::InvalidOperation(Reason::NoProcess))
+        } else if vfork && other.pid == child {
+            // the child shares our memory, so taking the breakpoints out of
+            // it would take them out of the parent too. They are hidden from
+            // the child instead, until it's done with the memory.
+            process.suspend_sites()?;
+            other.detach()?;
+            info!("detached from process {}", other.pid);
+        } else if vfork {
+            // likewise, the parent's breakpoints are the child's until it
+            // execs or exits, at which point the parent can be let go.
+            self.inferiors.current_mut().vfork_parent = Some(other);
+        } else {
+            self.release(other);
+        }
+        Ok(())
+    }
+
+    ///
+    /// Adds a new inferior for a process forked from the current one. It
+    /// runs the same program, so it starts off with the same modules, and
+    /// with the breakpoints resolved to the same addresses, since the fork
+    /// copied the sites along with the rest of the memory.
+    ///
+    fn add_forked_inferior(&mut self, process: Process) -> u32 {
+        let current = self.inferiors.current();
+        let (file, modules, linker) = (current.file.clone(), current.modules.clone(), current.linker.clone());
+        let parent = current.id;
+
+        let id = self.inferiors.add(file);
+        for bp in self.breakpoints.iter_mut().filter(|bp| bp.applies_to(id)) {
+            bp.copy_resolution(parent, id);
+        }
+
+        let inferior = self.inferiors.get_mut(id).unwrap();
+        inferior.process = Some(process);
+        inferior.modules = modules;
+        inferior.linker = linker;
+        id
+    }
+
+    ///
+    /// Takes the breakpoints out of a process which is no longer being
+    /// debugged, and detaches from it.
+    ///
+    fn release(&mut self, mut process: Process) {
+        self.remove_breakpoints(&mut process);
+        match process.detach() {
+            Ok(()) => info!("detached from process {}", process.pid),
+            Err(e) => warn!("failed to detach from process {}: {}", process.pid, e),
+        }
+    }
+
+    ///
+    /// Add a new breakpoint at the given location. If the location can't be
+    /// found in the process yet (or there is no process), the breakpoint is
+    /// left pending, and will be inserted as soon as the module containing
+    /// its location is loaded. Returns the identifier of the new breakpoint.
+    ///
+    /// If a thread is given, only that thread stops at the breakpoint. If an
+    /// inferior is given, the breakpoint is only inserted into that one,
+    /// rather than into all of them.
+    ///
+    pub fn add_breakpoint(&mut self, location: Location, condition: Option<Condition>, thread: Option<u32>,
+                          inferior: Option<u32>) -> Result<u32> {
+        let scope = match inferior {
+            Some(id) => self.inferiors.get(id)?,
+            None => self.inferiors.current(),
+        };
+        if let (Some(id), Some(process)) = (thread, scope.process()) {
+            process.threads().by_id(id).ok_or(DebugError::InvalidOperation(Reason::NoSuchThread))?;
         }
+
+        let mut bp = Breakpoint::new(location);
+        bp.condition = condition;
+        bp.thread = thread;
+        bp.inferior = inferior;
+        self.insert_breakpoint(bp)
     }
 
     ///
-    /// Disables the given breakpoint in the traced process.
+    /// Enables a new breakpoint, and resolves it wherever it can be yet.
+    /// Should it fail to be inserted into one of the processes, it's taken
+    /// back out of those it was already inserted into.
     ///
-    pub fn disable_breakpoint(&mut self, idx: usize) -> Result<()> {
-        match &*self.process.borrow() {
-            Some(process) => {
-                if let Some(bp) = self.breakpoints.get_mut(idx) {
-                    if bp.enabled {
-                        bp.disable(process)?;
-                        info!("breakpoint at 0x{:x} disabled.", bp.addr);
+    fn insert_breakpoint(&mut self, mut bp: Breakpoint) -> Result<u32> {
+        bp.enable(&mut self.inferiors)?;
+
+        let mut resolved = Ok(());
+        'inferiors: for inferior in self.inferiors.iter_mut() {
+            if let Some(process) = inferior.process.as_mut() {
+                for module in inferior.modules.iter() {
+                    match bp.resolve(inferior.id, module, process) {
+                        Ok(true) => break,
+                        Ok(false) => {}
+                        Err(e) => {
+                            resolved = Err(e);
+                            break 'inferiors;
+                        }
                     }
-                    Ok(())
-                } else {
-                    Err(DebugError::InvalidOperation(Reason::NoSuchBreakpoint))
                 }
             }
-            None => Err(DebugError::InvalidOperation(Reason::NoProcess))
         }
-    }
-}
\ No newline at end of file
+        if let Err(e) = resolved {
+            if let Err(e) = bp.disable(&mut self.inferiors) {
+                warn!("failed to take breakpoint back out: {}", e);
+            }
+            return Err(e);
+        }
+
+        let id = self.breakpoints.add(bp);
+        let bp = self.breakpoints.get(id)?;
+        let kind = if bp.temporary { "temporary breakpoint" } else { "breakpoint" };
+        if bp.is_pending() {
+            info!("{} {} ({}) pending.", kind, id, bp.what());
+        }
+        for (inferior, addr) in bp.addrs() {
+            let modules = &self.inferiors.get(inferior)?.modules;
+            info!("{} {}{} at {}", kind, id, self.located(inferior), modules.describe(addr));
+        }
+        Ok(id)
+    }
+
+    ///
+    /// Add a new catchpoint, which stops the process when the given event
+    /// happens. Returns the identifier of the new catchpoint.
+    ///
+    pub fn add_catchpoint(&mut self, catch: Catch) -> Result<u32> {
+        let mut bp = Breakpoint::catchpoint(catch);
+        bp.enable(&mut self.inferiors)?;
+
+        let id = self.breakpoints.add(bp);
+        info!("catchpoint {} ({})", id, self.breakpoints.get(id)?.what());
+        self.update_syscall_tracing();
+        Ok(id)
+    }
+
+    ///
+    /// Removes a breakpoint, including disabling it in the traced processes.
+    ///
+    pub fn remove_breakpoint(&mut self, id: u32) -> Result<()> {
+        self.breakpoints.get_mut(id)?.disable(&mut self.inferiors)?;
+        self.breakpoints.remove(id)?;
+        info!("breakpoint {} removed.", id);
+        self.update_syscall_tracing();
+        Ok(())
+    }
+
+    ///
+    /// Deletes a temporary breakpoint which has just stopped the current
+    /// inferior's process, which is passed in since it's taken out of the
+    /// inferior while it runs.
+    ///
+    fn remove_temporary(&mut self, process: &mut Process, id: u32) -> Result<()> {
+        let mut bp = self.breakpoints.remove(id)?;
+        if let (true, Some(addr)) = (bp.enabled, bp.addr(self.inferiors.current_id())) {
+            process.remove_site(addr)?;
+        }
+        bp.disable(&mut self.inferiors)
+    }
+
+    ///
+    /// Logs the given system calls, or every one if none are given, as the
+    /// processes make them, without stopping them. They're written to the
+    /// file at `output` if there is one, rather than the console.
+    ///
+    pub fn trace_syscalls(&mut self, filter: SyscallFilter, output: Option<PathBuf>) -> Result<()> {
+        let trace = SyscallTrace::new(filter, output).map_err(DebugError::Sys)?;
+        match trace.path() {
+            Some(path) => info!("tracing {} to {}", trace.filter, path.display()),
+            None => info!("tracing {}", trace.filter),
+        }
+        self.syscall_trace = Some(trace);
+        self.update_syscall_tracing();
+        Ok(())
+    }
+
+    ///
+    /// Stops logging system calls.
+    ///
+    pub fn stop_tracing_syscalls(&mut self) -> Result<()> {
+        if self.syscall_trace.take().is_none() {
+            return Err(DebugError::InvalidOperation(Reason::NotTracingSyscalls));
+        }
+        info!("stopped tracing syscalls");
+        self.update_syscall_tracing();
+        Ok(())
+    }
+
+    ///
+    /// Makes the processes stop on system calls only while they're being
+    /// traced, or there's an enabled catchpoint interested in them, since it
+    /// slows them down.
+    ///
+    fn update_syscall_tracing(&mut self) {
+        let trace = self.syscall_trace.as_ref().map(|trace| &trace.filter);
+        for inferior in self.inferiors.iter_mut() {
+            let filters: Vec<_> = self.breakpoints.syscall_filters(inferior.id).chain(trace).collect();
+            if let Some(process) = inferior.process.as_mut() {
+                process.stop_on_syscalls(&filters);
+            }
+        }
+    }
+
+    ///
+    /// Pulls every breakpoint out of the process, warning about any that
+    /// could not be removed, since they'll be left behind in the process.
+    ///
+    fn remove_breakpoints(&mut self, process: &mut Process) {
+        let failures = process.remove_all_sites();
+        if !failures.is_empty() {
+            warn!("failed to remove {} breakpoint(s) from process {}:", failures.len(), process.pid);
+            for (addr, e) in failures {
+                warn!("    0x{:x}: {}", addr, e);
+            }
+        }
+    }
+
+    ///
+    /// Called when a module has been mapped into the process, so that any
+    /// pending breakpoints within it can be inserted.
+    ///
+    fn module_loaded(&mut self, process: &mut Process, module: Module) {
+        let inferior = self.inferiors.current_id();
+        let mut resolved = Vec::new();
+        for bp in self.breakpoints.iter_mut() {
+            match bp.resolve(inferior, &module, process) {
+                Ok(true) => resolved.push((bp.id, bp.addr(inferior).unwrap())),
+                Ok(false) => {}
+                Err(e) => warn!("failed to insert breakpoint {}: {}", bp.id, e),
+            }
+        }
+        self.inferiors.current_mut().modules.add(module);
+
+        let located = self.located(inferior);
+        for (id, addr) in resolved {
+            info!("breakpoint {}{} resolved to {}", id, located, self.inferiors.current().modules.describe(addr));
+        }
+    }
+
+    ///
+    /// Called when a module has been unmapped from the process. Its
+    /// breakpoint sites are gone with it, so the breakpoints within it
+    /// become pending again.
+    ///
+    fn module_unloaded(&mut self, process: &mut Process, module: &Module) {
+        let (start, end) = module.range();
+        let inferior = self.inferiors.current_id();
+        process.forget_sites(start, end);
+        for bp in self.breakpoints.iter_mut() {
+            if bp.unload(inferior, start, end) {
+                info!("breakpoint {} pending, {} was unloaded.", bp.id, module.name());
+            }
+        }
+    }
+
+    ///
+    /// Loads the executable the process is running, and starts following
+    /// the libraries loaded alongside it.
+    ///
+    fn load_executable(&mut self, process: &mut Process, path: PathBuf) -> Result<()> {
+        match Module::find_base(&path, &maps::read(process.pid)?)? {
+            Some(base) => self.module_loaded(process, Module::load(path, base)?),
+            None => warn!("{} is not mapped into process {}", path.display(), process.pid),
+        }
+        self.track_libraries(process)
+    }
+
+    ///
+    /// Loads the dynamic linker of the executable, if it has one, and starts
+    /// following the libraries it loads into the process.
+    ///
+    fn track_libraries(&mut self, process: &mut Process) -> Result<()> {
+        let path = match self.inferiors.current().modules.executable().and_then(Module::interpreter) {
+            Some(path) => PathBuf::from(path),
+            None => return Ok(()),
+        };
+
+        let base = match auxv::get(&auxv::read(process.pid)?, auxv::AT_BASE) {
+            Some(base) if base != 0 => base,
+            _ => {
+                warn!("unable to find where {} was loaded, shared libraries won't be tracked.", path.display());
+                return Ok(());
+            }
+        };
+
+        let interpreter = Module::load(path, base)?;
+        let inferior = self.inferiors.current_mut();
+        inferior.linker = DynamicLinker::new(process, inferior.modules.executable().unwrap(), &interpreter)?;
+        if inferior.linker.is_none() {
+            warn!("{} has no debugger hook, shared libraries won't be tracked.", interpreter.name());
+        }
+        self.module_loaded(process, interpreter);
+        Ok(())
+    }
+
+    ///
+    /// Called when the dynamic linker reports that it has changed the list
+    /// of loaded libraries. Brings the module table up to date with it, and
+    /// returns whether a catchpoint wants the process to stop.
+    ///
+    fn libraries_changed(&mut self, process: &mut Process) -> Result<bool> {
+        let entries = match self.inferiors.current_mut().linker.as_mut() {
+            Some(linker) => linker.libraries(process)?,
+            None => None,
+        };
+        let entries = match entries {
+            Some(entries) => entries,
+            None => return Ok(false),
+        };
+
+        // the first entry is the executable, and some entries (like the
+        // vdso) don't correspond to any file we could read symbols from.
+        let entries: Vec<_> = entries.into_iter()
+            .skip(1)
+            .filter(|entry| !entry.name.is_empty() && Path::new(&entry.name).is_file())
+            .collect();
+
+        // the dynamic linker is never unloaded, although it's missing from
+        // the list until it has got around to adding itself.
+        let hook = self.inferiors.current().linker.as_ref().map(|linker| linker.hook);
+        let mut stopped = false;
+        let unloaded: Vec<u64> = self.inferiors.current().modules.libraries()
+            .filter(|module| !hook.is_some_and(|hook| module.contains(hook)))
+            .map(|module| module.base)
+            .filter(|base| !entries.iter().any(|entry| entry.base == *base))
+            .collect();
+        for base in unloaded {
+            if let Some(module) = self.inferiors.current_mut().modules.remove(base) {
+                info!("unloaded {}", module.path.display());
+                self.module_unloaded(process, &module);
+                stopped |= self.catch_library(&module, false);
+            }
+        }
+
+        for entry in entries {
+            if self.inferiors.current().modules.by_base(entry.base).is_some() {
+                continue;
+            }
+
+            match Module::load(&entry.name, entry.base) {
+                Ok(module) => {
+                    info!("loaded {} at 0x{:x}", entry.name, entry.base);
+                    stopped |= self.catch_library(&module, true);
+                    self.module_loaded(process, module);
+                }
+                Err(e) => warn!("failed to load {}: {}", entry.name, e),
+            }
+        }
+        Ok(stopped)
+    }
+
+    ///
+    /// Reports any catchpoints interested in a library being loaded or
+    /// unloaded, returning whether there were any.
+    ///
+    fn catch_library(&mut self, module: &Module, loaded: bool) -> bool {
+        let path = module.path.to_string_lossy();
+        let mut stopped = false;
+        for bp in self.breakpoints.catchpoints_mut(self.inferiors.current_id()) {
+            if let Kind::Catchpoint(catch) = &bp.kind {
+                if catch.matches_library(&path, loaded) {
+                    bp.hits += 1;
+                    info!("catchpoint {} hit: {} {}", bp.id, if loaded { "loaded" } else { "unloaded" }, path);
+                    stopped = true;
+                }
+            }
+        }
+        stopped
+    }
+
+    ///
+    /// Reports any catchpoints interested in a thread entering or returning
+    /// from a system call, returning whether there were any.
+    ///
+    fn catch_syscall(&mut self, process: &Process, info: &SyscallInfo, thread: Option<u32>, located: &str) -> bool {
+        let mut description = None;
+        let mut stopped = false;
+        for bp in self.breakpoints.catchpoints_mut(self.inferiors.current_id()) {
+            if let Kind::Catchpoint(catch) = &bp.kind {
+                if catch.matches_syscall(info.number) && (bp.thread.is_none() || bp.thread == thread) {
+                    bp.hits += 1;
+                    let description = description.get_or_insert_with(|| info.describe(process));
+                    let event = if info.is_entry() { "call to" } else { "returned from" };
+                    info!("catchpoint {} hit{}: {} {}", bp.id, located, event, description);
+                    stopped = true;
+                }
+            }
+        }
+        stopped
+    }
+
+    ///
+    /// Forgets everything tied to the current inferior's process once it has
+    /// gone away, leaving the breakpoints pending for the next one.
+    ///
+    fn process_gone(&mut self) {
+        if let Some(parent) = self.inferiors.current_mut().vfork_parent.take() {
+            self.release(parent);
+        }
+        self.forget_program(self.inferiors.current_id());
+    }
+
+    ///
+    /// Forgets everything tied to the program an inferior's process was
+    /// running, leaving the breakpoints pending in it.
+    ///
+    fn forget_program(&mut self, id: u32) {
+        if let Ok(inferior) = self.inferiors.get_mut(id) {
+            inferior.modules.clear();
+            inferior.linker = None;
+        }
+        for bp in self.breakpoints.iter_mut() {
+            bp.unresolve(id);
+        }
+    }
+
+    ///
+    /// Says which inferior something happened in, if there's more than one
+    /// of them to tell apart.
+    ///
+    fn located(&self, id: u32) -> String {
+        if self.inferiors.len() > 1 {
+            format!(" in inferior {}", id)
+        } else {
+            String::new()
+        }
+    }
+
+    ///
+    /// Sets the condition under which a breakpoint stops the process,
+    /// or clears it so that the breakpoint always stops.
+    ///
+    pub fn set_breakpoint_condition(&mut self, id: u32, condition: Option<Condition>) -> Result<()> {
+        let bp = self.breakpoints.get_mut(id)?;
+        match &condition {
+            Some(c) => info!("breakpoint {} condition set to {}.", id, c),
+            None => info!("breakpoint {} is now unconditional.", id),
+        }
+        bp.condition = condition;
+        Ok(())
+    }
+
+    ///
+    /// The table of all the breakpoints known to the debugger.
+    ///
+    pub fn breakpoints(&self) -> &Breakpoints {
+        &self.breakpoints
+    }
+
+    ///
+    /// Logs all the known breakpoints in the debugger.
+    ///
+    pub fn list_breakpoints(&self) {
+        if self.breakpoints.iter().next().is_none() {
+            info!("no breakpoints.");
+            return;
+        }
+
+        info!("{:<4} {:<11} {:<4} {:<18} {:<6} What", "Num", "Type", "Enb", "Address", "Hits");
+        for bp in self.breakpoints.iter() {
+            let addrs = bp.addrs();
+            let addr = match (&bp.kind, addrs.as_slice()) {
+                (Kind::Catchpoint(_), _) => String::new(),
+                (_, []) => String::from("<pending>"),
+                (_, [(_, addr)]) => format!("0x{:016x}", addr),
+                (_, _) => String::from("<multiple>"),
+            };
+            let source = match addrs.as_slice() {
+                [(inferior, addr)] => self.source(*inferior, *addr),
+                _ => String::new(),
+            };
+            info!("{:<4} {:<11} {:<4} {:<18} {:<6} {}{}",
+                  bp.id, bp.kind.to_string(), if bp.enabled { "y" } else { "n" }, addr, bp.hits, bp.what(), source);
+            if addrs.len() > 1 {
+                for (inferior, addr) in addrs {
+                    info!("        in inferior {} at 0x{:016x}{}", inferior, addr, self.source(inferior, addr));
+                }
+            }
+            if let Some(inferior) = bp.inferior {
+                info!("        only in inferior {}", inferior);
+            }
+            if bp.temporary {
+                info!("        deleted once hit");
+            }
+            if let Some(condition) = &bp.condition {
+                info!("        stop only if {}", condition);
+            }
+            if let Some(thread) = bp.thread {
+                info!("        stop only in thread {}", thread);
+            }
+        }
+    }
+
+    ///
+    /// The source line of an address in an inferior, as a suffix for listings.
+    ///
+    fn source(&self, inferior: u32, addr: u64) -> String {
+        self.inferiors.get(inferior).ok()
+            .and_then(|inferior| inferior.modules.containing(addr)?.source(addr))
+            .map_or_else(String::new, |(file, line)| format!(" at {}:{}", file, line))
+    }
+
+    ///
+    /// Logs the shared libraries loaded into the current inferior's process.
+    ///
+    pub fn list_shared_libraries(&self) {
+        let modules = &self.inferiors.current().modules;
+        if modules.libraries().next().is_none() {
+            info!("no shared libraries loaded.");
+            return;
+        }
+
+        info!("{:<18} {:<18} Name", "From", "To");
+        for module in modules.libraries() {
+            let (start, end) = module.range();
+            info!("0x{:016x} 0x{:016x} {}", start, end, module.path.display());
+        }
+    }
+
+    ///
+    /// The settings changing how the debugger behaves.
+    ///
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    ///
+    /// Switches between all-stop and non-stop mode, which can only be
+    /// done while there is no process.
+    ///
+    pub fn set_non_stop(&mut self, non_stop: bool) -> Result<()> {
+        if self.process_is_running() {
+            return Err(DebugError::InvalidOperation(Reason::ProcessExists));
+        }
+        self.settings.non_stop = non_stop;
+        info!("non-stop mode is {}.", if non_stop { "on" } else { "off" });
+        Ok(())
+    }
+
+    ///
+    /// Sets which other threads may run while the current thread is resumed.
+    ///
+    pub fn set_scheduler_locking(&mut self, locking: SchedulerLocking) {
+        self.settings.scheduler_locking = locking;
+        info!("scheduler locking is {}.", locking);
+    }
+
+    ///
+    /// Sets which process to keep debugging when the process forks.
+    ///
+    pub fn set_follow_fork_mode(&mut self, mode: FollowForkMode) {
+        self.settings.follow_fork_mode = mode;
+        info!("follow-fork-mode is {}.", mode);
+    }
+
+    ///
+    /// Sets whether to detach from the process that isn't followed after a
+    /// fork, or to hold it stopped.
+    ///
+    pub fn set_detach_on_fork(&mut self, detach: bool) {
+        self.settings.detach_on_fork = detach;
+        info!("detach-on-fork is {}.", if detach { "on" } else { "off" });
+    }
+
+    ///
+    /// Sets whether the processes we launched are killed when the debugger
+    /// exits, or detached from like those we attached to.
+    ///
+    pub fn set_kill_on_exit(&mut self, kill: bool) {
+        self.settings.kill_on_exit = kill;
+        info!("kill-on-exit is {}.", if kill { "on" } else { "off" });
+    }
+
+    ///
+    /// Logs the threads of the current inferior's process, or its core
+    /// file, marking the current one.
+    ///
+    pub fn list_threads(&self) -> Result<()> {
+        let inferior = self.inferiors.current();
+        let target = inferior.target().ok_or(DebugError::InvalidOperation(Reason::NoProcess))?;
+
+        info!("  {:<4} {:<8} Where", "Id", "Tid");
+        let current = target.current_tid();
+        for (id, tid) in target.thread_ids() {
+            let location = match target.thread_status(tid) {
+                Some(status) => String::from(status),
+                None => target.thread_registers(tid)
+                    .map_or_else(|e| e.to_string(), |regs| inferior.modules.describe(regs.rip)),
+            };
+            let marker = if tid == current { "*" } else { " " };
+            info!("{} {:<4} {:<8} {}", marker, id, tid, location);
+        }
+        Ok(())
+    }
+
+    ///
+    /// The identifier of the current thread of the current inferior's
+    /// process, or its core file.
+    ///
+    pub fn current_thread(&self) -> Result<u32> {
+        let target = self.inferiors.current().target().ok_or(DebugError::InvalidOperation(Reason::NoProcess))?;
+        let current = target.current_tid();
+        target.thread_ids().into_iter()
+            .find(|(_, tid)| *tid == current)
+            .map(|(id, _)| id)
+            .ok_or(DebugError::InvalidOperation(Reason::NoSuchThread))
+    }
+
+    ///
+    /// Switches the thread whose registers are read from the current
+    /// inferior's process, or its core file.
+    ///
+    pub fn select_thread(&mut self, id: u32) -> Result<()> {
+        let inferior = self.inferiors.current_mut();
+        let tid = inferior.target_mut()
+            .ok_or(DebugError::InvalidOperation(Reason::NoProcess))?
+            .switch_thread(id)?;
+        let inferior = self.inferiors.current();
+        let target = inferior.target().ok_or(DebugError::InvalidOperation(Reason::NoProcess))?;
+        let location = match target.thread_status(tid) {
+            Some(status) => String::from(status),
+            None => inferior.modules.describe(target.thread_registers(tid)?.rip),
+        };
+        info!("switched to thread {} ({}) at {}", id, tid, location);
+        Ok(())
+    }
+
+    ///
+    /// Enables the given breakpoint in the traced processes.
+    ///
+    pub fn enable_breakpoint(&mut self, id: u32) -> Result<()> {
+        let bp = self.breakpoints.get_mut(id)?;
+        if !bp.enabled {
+            bp.enable(&mut self.inferiors)?;
+            info!("breakpoint {} enabled.", id);
+        }
+        self.update_syscall_tracing();
+        Ok(())
+    }
+
+    ///
+    /// Disables the given breakpoint in the traced processes.
+    ///
+    pub fn disable_breakpoint(&mut self, id: u32) -> Result<()> {
+        let bp = self.breakpoints.get_mut(id)?;
+        if bp.enabled {
+            bp.disable(&mut self.inferiors)?;
+            info!("breakpoint {} disabled.", id);
+        }
+        self.update_syscall_tracing();
+        Ok(())
+    }
+
+    ///
+    /// Adds a new inferior, without a process, which will run the given
+    /// file. Returns the identifier of the new inferior.
+    ///
+    pub fn add_inferior(&mut self, file: Option<String>) -> u32 {
+        let id = self.inferiors.add(file);
+        info!("added inferior {}", id);
+        id
+    }
+
+    ///
+    /// Makes the given inferior the current one, which commands act on.
+    /// Only the current inferior runs, so in non-stop mode any threads still
+    /// running in the one we're switching away from are stopped first.
+    ///
+    pub fn select_inferior(&mut self, id: u32) -> Result<()> {
+        self.inferiors.get(id)?;
+        if self.settings.non_stop {
+            if let Some(process) = self.inferiors.current_mut().process.as_mut().filter(|p| p.running) {
+                process.stop()?;
+            }
+        }
+
+        let inferior = self.inferiors.select(id)?;
+        match inferior.process() {
+            Some(process) => info!("switched to inferior {} (process {})", id, process.pid),
+            None => info!("switched to inferior {} (no process)", id),
+        }
+        Ok(())
+    }
+
+    ///
+    /// Changes what happens when the process receives a signal, and logs
+    /// the resulting policy.
+    ///
+    pub fn handle_signal(&mut self, sig: Signal, actions: &[SignalAction]) {
+        let policy = self.signals.get_mut(sig);
+        for action in actions {
+            policy.apply(*action);
+        }
+        self.list_signals(Some(sig));
+    }
+
+    ///
+    /// Logs what happens when the process receives a signal, or every
+    /// signal if none is given.
+    ///
+    pub fn list_signals(&self, sig: Option<Signal>) {
+        info!("{:<10} {:<6} {:<8} Pass", "Signal", "Stop", "Print");
+        for (other, policy) in self.signals.iter().filter(|(other, _)| sig.is_none_or(|sig| sig == *other)) {
+            info!("{:<10} {}", other.as_str(), policy);
+        }
+    }
+
+    ///
+    /// Logs the details of the signal the current thread is stopped with.
+    ///
+    pub fn show_signal(&self) -> Result<()> {
+        for line in self.current_siginfo()?.details() {
+            info!("{}", line);
+        }
+        Ok(())
+    }
+
+    ///
+    /// Logs the value of a `$` variable of the current thread: one of its
+    /// registers (`$rip`, `$xmm0`), or the signal it is stopped with
+    /// (`$_siginfo`) or one of the signal's fields (`$_siginfo.si_addr`).
+    /// Anything else is taken as the name of a variable, whose value is
+    /// read from memory.
+    ///
+    pub fn print_value(&self, name: &str) -> Result<()> {
+        let unknown = || DebugError::InvalidOperation(Reason::UnknownValue(name.to_string()));
+        let inferior = self.inferiors.current();
+        let target = inferior.target().ok_or(DebugError::InvalidOperation(Reason::NoProcess))?;
+        let var = match name.strip_prefix('$') {
+            Some(var) => var,
+            None => return self.print_variable(target, name),
+        };
+
+        match var.split_once('.') {
+            None if var == "_siginfo" => info!("{} = {}", name, self.current_siginfo()?.fields()),
+            Some(("_siginfo", field)) => match self.current_siginfo()?.field(field) {
+                Some(value) => info!("{} = 0x{:x} ({})", name, value, value as i64),
+                None => return Err(unknown()),
+            },
+            Some(_) => return Err(unknown()),
+            None if registers::exists(var) => {
+                let value = registers::get(&target.thread_registers(target.current_tid())?, var).ok_or_else(unknown)?;
+                info!("{} = 0x{:x} ({})", name, value, value as i64);
+            }
+            None => {
+                let regs = target.thread_fp_registers(target.current_tid())?;
+                let value = registers::get_fp(&regs, var).ok_or_else(unknown)?;
+                info!("{} = 0x{:x}", name, value);
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Logs the value of a variable, which is shown as a number if it's the
+    /// size of one, or as a string of bytes otherwise.
+    ///
+    fn print_variable(&self, target: &dyn Inspect, name: &str) -> Result<()> {
+        let (addr, sym) = self.inferiors.current().modules.find_symbol(name)
+            .ok_or_else(|| DebugError::InvalidOperation(Reason::NoSymbol(name.to_string())))?;
+        let bytes = target.read_memory(addr, sym.size.max(1) as usize)?;
+        match bytes.len() {
+            1 | 2 | 4 | 8 => {
+                let mut word = [0u8; 8];
+                word[..bytes.len()].copy_from_slice(&bytes);
+                let value = u64::from_le_bytes(word);
+                // sign extend, so that negative numbers show as such
+                let shift = 64 - bytes.len() as u32 * 8;
+                info!("{} = 0x{:x} ({})", name, value, (value << shift) as i64 >> shift);
+            }
+            _ => {
+                let end = bytes.iter().rposition(|b| *b != 0).map_or(0, |idx| idx + 1);
+                info!("{} = \"{}\"", name, bytes[..end].escape_ascii());
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// Logs the stack of the current thread, innermost frame first.
+    ///
+    pub fn backtrace(&self) -> Result<()> {
+        let target = self.inferiors.current().target().ok_or(DebugError::InvalidOperation(Reason::NoProcess))?;
+        let regs = target.thread_registers(target.current_tid())?;
+        self.log_backtrace(target, &regs);
+        Ok(())
+    }
+
+    ///
+    /// Logs the memory at an address, which is a number (`0x7ffe1000`), a
+    /// register (`$rsp`) or a variable (`&counter`), as `count` words of
+    /// `size` bytes, or as `count` strings one after another.
+    ///
+    pub fn examine(&self, expr: &str, count: usize, size: usize, strings: bool) -> Result<()> {
+        let modules = &self.inferiors.current().modules;
+        let target = self.inferiors.current().target().ok_or(DebugError::InvalidOperation(Reason::NoProcess))?;
+        let mut addr = self.address_of(target, expr)?;
+
+        if strings {
+            for _ in 0..count {
+                let string = target.read_string(addr)?;
+                info!("{}: \"{}\"", modules.describe(addr), string.escape_debug());
+                addr += string.len() as u64 + 1;
+            }
+            return Ok(());
+        }
+
+        let per_line = (16 / size).max(1);
+        let bytes = target.read_memory(addr, count * size)?;
+        for line in bytes.chunks(per_line * size) {
+            let words: Vec<String> = line.chunks(size)
+                .map(|word| {
+                    let mut value = [0u8; 8];
+                    value[..word.len()].copy_from_slice(word);
+                    format!("0x{:0width$x}", u64::from_le_bytes(value), width = size * 2)
+                })
+                .collect();
+            info!("{}: {}", modules.describe(addr), words.join(" "));
+            addr += line.len() as u64;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Works out the address an expression refers to: a number, a register
+    /// of the current thread, or a variable or function, which may be
+    /// written with a leading `&`.
+    ///
+    fn address_of(&self, target: &dyn Inspect, expr: &str) -> Result<u64> {
+        let unknown = || DebugError::InvalidOperation(Reason::UnknownValue(expr.to_string()));
+        let name = expr.strip_prefix('&').unwrap_or(expr);
+        if let Some(reg) = name.strip_prefix('$') {
+            let regs = target.thread_registers(target.current_tid())?;
+            return registers::get(&regs, reg).ok_or_else(unknown);
+        }
+        if let Some(hex) = name.strip_prefix("0x") {
+            return u64::from_str_radix(hex, 16).map_err(|_| unknown());
+        }
+        if let Ok(addr) = name.parse::<u64>() {
+            return Ok(addr);
+        }
+        self.inferiors.current().modules.find_symbol(name)
+            .map(|(addr, _)| addr)
+            .ok_or_else(|| DebugError::InvalidOperation(Reason::NoSymbol(name.to_string())))
+    }
+
+    fn current_siginfo(&self) -> Result<SignalInfo> {
+        let target = self.inferiors.current().target().ok_or(DebugError::InvalidOperation(Reason::NoProcess))?;
+        target.thread_siginfo(target.current_tid()).ok_or(DebugError::InvalidOperation(Reason::NoSignal))
+    }
+
+    ///
+    /// Logs every inferior, marking the current one.
+    ///
+    pub fn list_inferiors(&self) {
+        info!("  {:<4} {:<14} Executable", "Num", "Process");
+        let current = self.inferiors.current_id();
+        for inferior in self.inferiors.iter() {
+            let process = inferior.process()
+                .map_or_else(|| String::from("<null>"), |process| format!("process {}", process.pid));
+            let marker = if inferior.id == current { "*" } else { " " };
+            info!("{} {:<4} {:<14} {}", marker, inferior.id, process, inferior.file.as_deref().unwrap_or(""));
+        }
+    }
+}
+
+///
+/// The threads selected by a user given identifier, or every thread that is
+/// stopped (or running, if `stopped` isn't set) when there isn't one.
+///
+fn thread_ids(process: &Process, id: Option<u32>, stopped: bool) -> Result<Vec<i32>> {
+    match id {
+        Some(id) => process.threads().by_id(id)
+            .map(|thread| vec![thread.tid])
+            .ok_or(DebugError::InvalidOperation(Reason::NoSuchThread)),
+        None => Ok(process.threads().iter()
+            .filter(|thread| thread.stopped == stopped)
+            .map(|thread| thread.tid)
+            .collect()),
+    }
+}
+
+impl Drop for Debugger {
+    ///
+    /// Make sure the traced processes are left in a sane state if the
+    /// debugger exits while still tracing them, killing those that
+    /// kill-on-exit says to, just as quitting does.
+    ///
+    fn drop(&mut self) {
+        if let Err(e) = self.quit() {
+            warn!("failed to let go of the processes: {}", e);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use object::elf::{DT_DEBUG, DT_NULL};
+
+use crate::error::Result;
+use crate::inspect::Inspect;
+use crate::module::Module;
+use crate::process::Process;
+
+// Offsets into `struct r_debug` from <link.h>, for 64 bit processes.
+const R_MAP: u64 = 8;
+const R_BRK: u64 = 16;
+const R_STATE: u64 = 24;
+
+// Offsets into `struct link_map`.
+const L_ADDR: u64 = 0;
+const L_NAME: u64 = 8;
+const L_NEXT: u64 = 24;
+
+/// `r_state` while the library list is safe to read.
+const RT_CONSISTENT: i32 = 0;
+
+/// Guards against walking a corrupt, circular list forever.
+const MAX_LIBRARIES: usize = 4096;
+
+///
+/// A single entry of the dynamic linker's `link_map` list.
+///
+pub struct LinkMapEntry {
+    /// The difference between addresses in the file and in the process.
+    pub base: u64,
+    /// The path the library was loaded from.
+    pub name: String,
+}
+
+///
+/// Follows the dynamic linker's view of the loaded libraries, through the
+/// `r_debug` structure it shares with debuggers. The dynamic linker calls
+/// a hook function every time it changes the list, which we break on.
+///
+#[derive(Clone)]
+pub struct DynamicLinker {
+    /// The address of the hook function, where our breakpoint site is.
+    pub hook: u64,
+    /// The absolute address of the executable's dynamic section.
+    dynamic: Option<u64>,
+    /// The address of `_r_debug` in the dynamic linker, if it exports it.
+    fallback: Option<u64>,
+    /// The address of `r_debug` in the process, once it is known.
+    r_debug: Option<u64>,
+}
+
+impl DynamicLinker {
+    ///
+    /// Sets up tracking for an executable loaded by the given dynamic linker,
+    /// inserting a breakpoint site on its hook function. Returns `None` if
+    /// the dynamic linker doesn't provide a hook.
+    ///
+    pub fn new(process: &mut Process, executable: &Module, interpreter: &Module) -> Result<Option<Self>> {
+        let hook = match interpreter.symbol("_dl_debug_state") {
+            Some(hook) => hook,
+            None => return Ok(None),
+        };
+
+        process.insert_site(hook)?;
+        Ok(Some(Self {
+            hook,
+            dynamic: executable.dynamic(),
+            fallback: interpreter.symbol("_r_debug"),
+            r_debug: None,
+        }))
+    }
+
+    ///
+    /// Reads the current list of libraries, if the dynamic linker has finished
+    /// changing it. The first entry is always the executable itself.
+    ///
+    pub fn libraries(&mut self, process: &mut Process) -> Result<Option<Vec<LinkMapEntry>>> {
+        let r_debug = match self.find_r_debug(process)? {
+            Some(r_debug) => r_debug,
+            None => return Ok(None),
+        };
+
+        // the dynamic linker tells us where it'd really like to be
+        // broken on, which may differ from the symbol we started with.
+        let brk = process.read(r_debug + R_BRK)? as u64;
+        if brk != 0 && brk != self.hook {
+            process.insert_site(brk)?;
+            process.remove_site(self.hook)?;
+            self.hook = brk;
+        }
+
+        if process.read(r_debug + R_STATE)? as i32 != RT_CONSISTENT {
+            return Ok(None);
+        }
+
+        let mut libraries = Vec::new();
+        let mut entry = process.read(r_debug + R_MAP)? as u64;
+        while entry != 0 && libraries.len() < MAX_LIBRARIES {
+            let name = process.read(entry + L_NAME)? as u64;
+            libraries.push(LinkMapEntry {
+                base: process.read(entry + L_ADDR)? as u64,
+                name: if name == 0 { String::new() } else { process.read_string(name)? },
+            });
+            entry = process.read(entry + L_NEXT)? as u64;
+        }
+        Ok(Some(libraries))
+    }
+
+    ///
+    /// Finds `r_debug`, through the `DT_DEBUG` entry of the executable's
+    /// dynamic section, which the dynamic linker fills in when it starts.
+    ///
+    fn find_r_debug(&mut self, process: &Process) -> Result<Option<u64>> {
+        if self.r_debug.is_some() {
+            return Ok(self.r_debug);
+        }
+
+        if let Some(mut entry) = self.dynamic {
+            loop {
+                let tag = process.read(entry)? as u64;
+                if tag == DT_NULL as u64 {
+                    break;
+                }
+                if tag == DT_DEBUG as u64 {
+                    let value = process.read(entry + 8)? as u64;
+                    if value != 0 {
+                        self.r_debug = Some(value);
+                    }
+                    break;
+                }
+                entry += 16;
+            }
+        }
+
+        if self.r_debug.is_none() {
+            self.r_debug = self.fallback;
+        }
+        Ok(self.r_debug)
+    }
+}
@@ -0,0 +1,423 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use object::{Bytes, Endianness, Object, ObjectSection, ObjectSegment, ObjectSymbol, SymbolKind};
+use object::elf::{ET_DYN, FileHeader64, PT_DYNAMIC, PT_INTERP};
+use object::read::elf::{ElfFile64, FileHeader, ProgramHeader};
+
+use crate::error::{DebugError, Result};
+use crate::process::maps::MemoryMap;
+
+pub mod link_map;
+pub mod unwind;
+
+///
+/// A function or variable defined by a module.
+///
+#[derive(Clone)]
+pub struct Symbol {
+    pub name: String,
+    /// The address of the symbol in the file, before the module is relocated.
+    pub addr: u64,
+    pub size: u64,
+}
+
+///
+/// A row of the line table, mapping an address in the file to a line of source.
+///
+#[derive(Clone)]
+struct Line {
+    addr: u64,
+    file: usize,
+    line: u64,
+    is_stmt: bool,
+}
+
+///
+/// An executable or shared library mapped into the traced process, along
+/// with the symbols and line information it provides.
+///
+#[derive(Clone)]
+pub struct Module {
+    /// The path of the file on disk.
+    pub path: PathBuf,
+    /// The difference between addresses in the file, and addresses in the process.
+    pub base: u64,
+    /// The range of addresses the module occupies in the file.
+    range: (u64, u64),
+    /// The address of the dynamic section in the file, if it has one.
+    dynamic: Option<u64>,
+    /// The dynamic linker the module asks to be loaded by, if any.
+    interpreter: Option<String>,
+    symbols: Vec<Symbol>,
+    files: Vec<String>,
+    lines: Vec<Line>,
+    /// The address and contents of the `.eh_frame` section, used to unwind
+    /// the stack through the module's code.
+    eh_frame: Option<(u64, Vec<u8>)>,
+    /// The address of the `.text` section, which unwind information may be
+    /// relative to.
+    text: Option<u64>,
+}
+
+impl Module {
+    ///
+    /// Reads the symbols and line information from the object file at the given
+    /// path, which has been loaded into the process with the given base.
+    ///
+    pub fn load<P: Into<PathBuf>>(path: P, base: u64) -> Result<Self> {
+        let path = path.into();
+        let data = fs::read(&path).map_err(DebugError::Sys)?;
+        let file = ElfFile64::<Endianness>::parse(&data).map_err(|e| DebugError::Symbols(e.to_string()))?;
+
+        let header = FileHeader64::<Endianness>::parse(Bytes(&data)).map_err(|e| DebugError::Symbols(e.to_string()))?;
+        let endian = header.endian().map_err(|e| DebugError::Symbols(e.to_string()))?;
+        let program_headers = header.program_headers(endian, Bytes(&data)).map_err(|e| DebugError::Symbols(e.to_string()))?;
+
+        let dynamic = program_headers.iter()
+            .find(|ph| ph.p_type(endian) == PT_DYNAMIC)
+            .map(|ph| ph.p_vaddr(endian));
+        let interpreter = program_headers.iter()
+            .find(|ph| ph.p_type(endian) == PT_INTERP)
+            .and_then(|ph| ph.data(endian, Bytes(&data)).ok())
+            .map(|interp| String::from_utf8_lossy(interp.0).trim_end_matches('\0').to_string());
+
+        let range = file.segments()
+            .filter(|segment| segment.size() > 0)
+            .fold((u64::MAX, 0), |(start, end), segment| {
+                (start.min(segment.address()), end.max(segment.address() + segment.size()))
+            });
+
+        let mut symbols: Vec<Symbol> = file.symbols()
+            .chain(file.dynamic_symbols())
+            .filter(|sym| sym.is_definition() && matches!(sym.kind(), SymbolKind::Text | SymbolKind::Data))
+            .filter_map(|sym| Some(Symbol {
+                name: sym.name().ok()?.to_string(),
+                addr: sym.address(),
+                size: sym.size(),
+            }))
+            .collect();
+        symbols.sort_by_key(|sym| sym.addr);
+        symbols.dedup_by(|a, b| a.addr == b.addr && a.name == b.name);
+
+        let (files, mut lines) = read_lines(&file).unwrap_or_default();
+        lines.sort_by_key(|line| line.addr);
+
+        let eh_frame = file.section_by_name(".eh_frame")
+            .and_then(|section| Some((section.address(), section.data().ok()?.to_vec())));
+        let text = file.section_by_name(".text").map(|section| section.address());
+
+        Ok(Self {
+            path,
+            base,
+            range,
+            dynamic,
+            interpreter,
+            symbols,
+            files,
+            lines,
+            eh_frame,
+            text,
+        })
+    }
+
+    ///
+    /// Works out the base of the module at the given path from the memory
+    /// maps of the process it has been loaded into. Returns `None` if
+    /// the module isn't mapped.
+    ///
+    pub fn find_base(path: &Path, maps: &[MemoryMap]) -> Result<Option<u64>> {
+        let data = fs::read(path).map_err(DebugError::Sys)?;
+        let file = ElfFile64::<Endianness>::parse(&data).map_err(|e| DebugError::Symbols(e.to_string()))?;
+
+        let path = fs::canonicalize(path).map_err(DebugError::Sys)?;
+        let mapping = maps.iter()
+            .find(|map| map.offset == 0 && map.path.as_deref().map(Path::new) == Some(path.as_path()));
+        let mapping = match mapping {
+            Some(mapping) => mapping,
+            None => return Ok(None),
+        };
+
+        // executables which aren't position independent are loaded
+        // exactly where they ask to be.
+        let header = FileHeader64::<Endianness>::parse(Bytes(&data)).map_err(|e| DebugError::Symbols(e.to_string()))?;
+        let endian = header.endian().map_err(|e| DebugError::Symbols(e.to_string()))?;
+        if header.e_type(endian) != ET_DYN {
+            return Ok(Some(0));
+        }
+
+        let first = file.segments()
+            .filter(|segment| segment.size() > 0)
+            .map(|segment| segment.address())
+            .min()
+            .unwrap_or_default();
+        Ok(Some(mapping.start - (first & !0xfff)))
+    }
+
+    ///
+    /// The name of the module, i.e. the file name without its directory.
+    ///
+    pub fn name(&self) -> String {
+        self.path.file_name().map_or_else(|| self.path.display().to_string(), |n| n.to_string_lossy().to_string())
+    }
+
+    ///
+    /// The start and end of the module in the process.
+    ///
+    pub fn range(&self) -> (u64, u64) {
+        (self.range.0.wrapping_add(self.base), self.range.1.wrapping_add(self.base))
+    }
+
+    ///
+    /// The absolute address of the module's dynamic section, if it has one.
+    ///
+    pub fn dynamic(&self) -> Option<u64> {
+        self.dynamic.map(|addr| addr.wrapping_add(self.base))
+    }
+
+    ///
+    /// The path of the dynamic linker this module needs, if it is dynamically linked.
+    ///
+    pub fn interpreter(&self) -> Option<&str> {
+        self.interpreter.as_deref()
+    }
+
+    ///
+    /// Whether the given absolute address falls within the module.
+    ///
+    pub fn contains(&self, addr: u64) -> bool {
+        let (start, end) = self.range();
+        start <= addr && addr < end
+    }
+
+    ///
+    /// All the symbols the module defines, in address order.
+    ///
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    ///
+    /// Looks up the absolute address of a symbol by name.
+    ///
+    pub fn symbol(&self, name: &str) -> Option<u64> {
+        self.symbols.iter()
+            .find(|sym| sym.name == name)
+            .map(|sym| sym.addr.wrapping_add(self.base))
+    }
+
+    ///
+    /// Finds the symbol containing the given absolute address, along with
+    /// the offset of the address from the start of the symbol.
+    ///
+    pub fn symbolize(&self, addr: u64) -> Option<(&Symbol, u64)> {
+        let addr = addr.wrapping_sub(self.base);
+        let idx = match self.symbols.binary_search_by_key(&addr, |sym| sym.addr) {
+            Ok(idx) => idx,
+            Err(0) => return None,
+            Err(idx) => idx - 1,
+        };
+
+        let sym = &self.symbols[idx];
+        let offset = addr - sym.addr;
+        if offset < sym.size.max(1) {
+            Some((sym, offset))
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Finds the absolute address of the first instruction for a line of source,
+    /// where the file is matched by the end of its path. If no code was generated
+    /// for that line, the next line with code is used.
+    ///
+    pub fn line_address(&self, file: &str, line: u64) -> Option<u64> {
+        let file = Path::new(file);
+        let matching: Vec<&Line> = self.lines.iter()
+            .filter(|row| row.is_stmt && row.line >= line)
+            .filter(|row| Path::new(&self.files[row.file]).ends_with(file))
+            .collect();
+
+        let best = matching.iter().map(|row| row.line).min()?;
+        matching.iter()
+            .filter(|row| row.line == best)
+            .map(|row| row.addr.wrapping_add(self.base))
+            .min()
+    }
+
+    ///
+    /// Finds the line of source that the given absolute address belongs to.
+    ///
+    pub fn source(&self, addr: u64) -> Option<(&str, u64)> {
+        let addr = addr.wrapping_sub(self.base);
+        let idx = match self.lines.binary_search_by_key(&addr, |row| row.addr) {
+            Ok(idx) => idx,
+            Err(0) => return None,
+            Err(idx) => idx - 1,
+        };
+
+        let row = &self.lines[idx];
+        Some((self.files[row.file].as_str(), row.line))
+    }
+}
+
+///
+/// The table of modules loaded into the process. The executable is always
+/// the first module, followed by the libraries in the order they were loaded.
+///
+#[derive(Default, Clone)]
+pub struct Modules {
+    modules: Vec<Module>,
+}
+
+impl Modules {
+    pub fn add(&mut self, module: Module) {
+        self.modules.push(module);
+    }
+
+    ///
+    /// Removes the module loaded at the given base, returning it.
+    ///
+    pub fn remove(&mut self, base: u64) -> Option<Module> {
+        let idx = self.modules.iter().position(|m| m.base == base)?;
+        Some(self.modules.remove(idx))
+    }
+
+    pub fn clear(&mut self) {
+        self.modules.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item=&Module> {
+        self.modules.iter()
+    }
+
+    ///
+    /// The executable the process is running.
+    ///
+    pub fn executable(&self) -> Option<&Module> {
+        self.modules.first()
+    }
+
+    ///
+    /// The shared libraries loaded into the process, i.e. everything
+    /// but the executable.
+    ///
+    pub fn libraries(&self) -> impl Iterator<Item=&Module> {
+        self.modules.iter().skip(1)
+    }
+
+    ///
+    /// Finds the module loaded at the given base.
+    ///
+    pub fn by_base(&self, base: u64) -> Option<&Module> {
+        self.modules.iter().find(|m| m.base == base)
+    }
+
+    ///
+    /// Finds the module containing the given absolute address.
+    ///
+    pub fn containing(&self, addr: u64) -> Option<&Module> {
+        self.modules.iter().find(|m| m.contains(addr))
+    }
+
+    ///
+    /// Looks up a symbol by name in every module, returning its absolute
+    /// address along with it.
+    ///
+    pub fn find_symbol(&self, name: &str) -> Option<(u64, &Symbol)> {
+        self.modules.iter().find_map(|module| {
+            let sym = module.symbols.iter().find(|sym| sym.name == name)?;
+            Some((sym.addr.wrapping_add(module.base), sym))
+        })
+    }
+
+    ///
+    /// Describes an address in terms of the symbol and source line it belongs
+    /// to, as far as the loaded modules know about them.
+    ///
+    pub fn describe(&self, addr: u64) -> String {
+        let mut description = format!("0x{:x}", addr);
+        let module = match self.containing(addr) {
+            Some(module) => module,
+            None => return description,
+        };
+
+        if let Some((sym, offset)) = module.symbolize(addr) {
+            if offset == 0 {
+                description.push_str(&format!(" <{}>", sym.name));
+            } else {
+                description.push_str(&format!(" <{}+{}>", sym.name, offset));
+            }
+        }
+        match module.source(addr) {
+            Some((file, line)) => description.push_str(&format!(" at {}:{}", file, line)),
+            None if self.modules.first().map(|m| m.base) != Some(module.base) => {
+                description.push_str(&format!(" in {}", module.name()));
+            }
+            None => {}
+        }
+        description
+    }
+}
+
+///
+/// Reads every row of the DWARF line tables in the file, returning the
+/// file names along with the rows that refer to them.
+///
+fn read_lines(file: &ElfFile64<Endianness>) -> std::result::Result<(Vec<String>, Vec<Line>), gimli::Error> {
+    let load_section = |id: gimli::SectionId| -> std::result::Result<&[u8], gimli::Error> {
+        Ok(file.section_by_name(id.name()).and_then(|s| s.data().ok()).unwrap_or(&[]))
+    };
+    let no_sup = |_| Ok(&[][..]);
+    let dwarf = gimli::Dwarf::load(&load_section, no_sup)?;
+    let dwarf = dwarf.borrow(|section| gimli::EndianSlice::new(section, gimli::RunTimeEndian::Little));
+
+    let mut files: Vec<String> = Vec::new();
+    let mut indices: HashMap<String, usize> = HashMap::new();
+    let mut lines = Vec::new();
+
+    let mut units = dwarf.units();
+    while let Some(header) = units.next()? {
+        let unit = dwarf.unit(header)?;
+        let program = match unit.line_program.clone() {
+            Some(program) => program,
+            None => continue,
+        };
+
+        let comp_dir = unit.comp_dir.map(|dir| dir.to_string_lossy().to_string()).unwrap_or_default();
+        let mut rows = program.rows();
+        while let Some((header, row)) = rows.next_row()? {
+            if row.end_sequence() {
+                continue;
+            }
+
+            let entry = match row.file(header) {
+                Some(entry) => entry,
+                None => continue,
+            };
+
+            let mut path = PathBuf::from(&comp_dir);
+            if let Some(dir) = entry.directory(header) {
+                path.push(dwarf.attr_string(&unit, dir)?.to_string_lossy().as_ref());
+            }
+            path.push(dwarf.attr_string(&unit, entry.path_name())?.to_string_lossy().as_ref());
+            let path = path.to_string_lossy().to_string();
+
+            let file = *indices.entry(path).or_insert_with_key(|path| {
+                files.push(path.clone());
+                files.len() - 1
+            });
+
+            lines.push(Line {
+                addr: row.address(),
+                file,
+                line: row.line().unwrap_or_default(),
+                is_stmt: row.is_stmt(),
+            });
+        }
+    }
+
+    Ok((files, lines))
+}
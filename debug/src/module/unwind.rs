@@ -0,0 +1,112 @@
+use gimli::{BaseAddresses, CfaRule, EhFrame, LittleEndian, RegisterRule, UninitializedUnwindContext, UnwindSection, X86_64};
+
+use crate::inspect::Inspect;
+use crate::module::{Module, Modules};
+use crate::trace::Registers;
+
+/// Guards against unwinding a corrupt stack forever.
+const MAX_FRAMES: usize = 256;
+
+///
+/// How to find the caller's frame from a frame in the module, according to
+/// its call frame information. Offsets are from the canonical frame address
+/// (CFA), which is the value of the stack pointer in the caller.
+///
+struct FrameRule {
+    /// The DWARF number of the register the CFA is relative to.
+    cfa_register: u16,
+    cfa_offset: i64,
+    /// Where the return address is saved.
+    ra: Option<i64>,
+    /// Where the caller's frame pointer is saved, if it was.
+    rbp: Option<i64>,
+}
+
+impl Module {
+    ///
+    /// Looks up how to unwind from the given absolute address, from the
+    /// module's `.eh_frame`.
+    ///
+    fn frame_rule(&self, addr: u64) -> Option<FrameRule> {
+        let (section_addr, data) = self.eh_frame.as_ref()?;
+        let eh_frame = EhFrame::new(data, LittleEndian);
+        let mut bases = BaseAddresses::default().set_eh_frame(*section_addr);
+        if let Some(text) = self.text {
+            bases = bases.set_text(text);
+        }
+
+        let mut ctx = UninitializedUnwindContext::new();
+        let row = eh_frame.unwind_info_for_address(&bases, &mut ctx, addr.wrapping_sub(self.base), EhFrame::cie_from_offset)
+            .ok()?;
+        let (cfa_register, cfa_offset) = match row.cfa() {
+            CfaRule::RegisterAndOffset { register, offset } => (register.0, *offset),
+            CfaRule::Expression(_) => return None,
+        };
+        let saved = |register| match row.register(register) {
+            RegisterRule::Offset(offset) => Some(offset),
+            _ => None,
+        };
+
+        Some(FrameRule {
+            cfa_register,
+            cfa_offset,
+            ra: saved(X86_64::RA),
+            rbp: saved(X86_64::RBP),
+        })
+    }
+}
+
+impl Modules {
+    ///
+    /// Unwinds the stack of a stopped thread, or a thread in a core file, with the given registers,
+    /// returning the program counter of each frame, innermost first.
+    ///
+    /// Frames are unwound with the call frame information of the module
+    /// they're in, falling back on following the frame pointer for code
+    /// without any.
+    ///
+    pub fn backtrace(&self, target: &dyn Inspect, regs: &Registers) -> Vec<u64> {
+        let mut frames = vec![regs.rip];
+        let (mut pc, mut rsp, mut rbp) = (regs.rip, regs.rsp, regs.rbp);
+        let read = |addr: u64| target.read(addr).ok().map(|word| word as u64);
+
+        while frames.len() < MAX_FRAMES {
+            // return addresses point just past the call, which might be the
+            // start of another function, so the call itself is looked up.
+            let innermost = frames.len() == 1;
+            let lookup = if innermost { pc } else { pc.wrapping_sub(1) };
+
+            let (cfa, ra, saved_rbp) = match self.containing(lookup).map(|module| module.frame_rule(lookup)) {
+                Some(Some(rule)) => {
+                    let register = match rule.cfa_register {
+                        reg if reg == X86_64::RSP.0 => rsp,
+                        reg if reg == X86_64::RBP.0 => rbp,
+                        _ => break,
+                    };
+                    let cfa = register.wrapping_add(rule.cfa_offset as u64);
+                    match rule.ra {
+                        Some(ra) => (cfa, cfa.wrapping_add(ra as u64), rule.rbp.map(|offset| cfa.wrapping_add(offset as u64))),
+                        None => break,
+                    }
+                }
+                // jumping somewhere that isn't code at all is usually a call
+                // through a bad pointer, which left the return address on top.
+                None if innermost => (rsp.wrapping_add(8), rsp, None),
+                _ if rbp == 0 => break,
+                _ => (rbp.wrapping_add(16), rbp.wrapping_add(8), Some(rbp)),
+            };
+
+            let ra = match read(ra) {
+                Some(ra) if ra != 0 && cfa > rsp => ra,
+                _ => break,
+            };
+            if let Some(addr) = saved_rbp {
+                rbp = read(addr).unwrap_or_default();
+            }
+            rsp = cfa;
+            pc = ra;
+            frames.push(pc);
+        }
+        frames
+    }
+}
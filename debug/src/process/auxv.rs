@@ -0,0 +1,37 @@
+use std::fs;
+
+use crate::error::{DebugError, Result};
+
+pub use libc::{AT_BASE, AT_ENTRY, AT_NULL, AT_PHDR};
+
+///
+/// Parses an auxiliary vector, as found in `/proc/<pid>/auxv` or a core
+/// file, into its `(type, value)` pairs.
+///
+pub fn parse(data: &[u8]) -> Vec<(u64, u64)> {
+    data.chunks_exact(16)
+        .map(|entry| {
+            let mut key = [0u8; 8];
+            let mut value = [0u8; 8];
+            key.copy_from_slice(&entry[..8]);
+            value.copy_from_slice(&entry[8..]);
+            (u64::from_ne_bytes(key), u64::from_ne_bytes(value))
+        })
+        .take_while(|(key, _)| *key != AT_NULL)
+        .collect()
+}
+
+///
+/// Reads the auxiliary vector the kernel handed to the given process.
+///
+pub fn read(pid: i32) -> Result<Vec<(u64, u64)>> {
+    let data = fs::read(format!("/proc/{}/auxv", pid)).map_err(DebugError::Sys)?;
+    Ok(parse(&data))
+}
+
+///
+/// Looks up a single entry of an auxiliary vector.
+///
+pub fn get(auxv: &[(u64, u64)], key: u64) -> Option<u64> {
+    auxv.iter().find(|(k, _)| *k == key).map(|(_, value)| *value)
+}
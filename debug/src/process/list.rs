@@ -0,0 +1,114 @@
+use std::fs;
+use std::path::Path;
+use std::process;
+
+use crate::error::{DebugError, Result};
+
+/// The kernel cuts process names down to this many characters.
+const NAME_LENGTH: usize = 15;
+
+///
+/// A process running on the system, as listed in `/proc`.
+///
+pub struct ProcessEntry {
+    pub pid: i32,
+    /// The name the kernel knows the process by, which is the file name of
+    /// its program, unless the process has changed it.
+    pub name: String,
+    /// The command line the process was started with, as found in
+    /// `/proc/<pid>/cmdline`.
+    pub args: Vec<String>,
+}
+
+impl ProcessEntry {
+    fn read(pid: i32) -> Option<Self> {
+        let name = fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+        let cmdline = fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+        Some(Self {
+            pid,
+            name: name.trim_end_matches('\n').to_string(),
+            args: cmdline.split(|b| *b == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                .collect(),
+        })
+    }
+
+    ///
+    /// Whether the process goes by the given name, either as the kernel
+    /// knows it, or as the program it was started with, e.g. `app` for
+    /// `./app`.
+    ///
+    pub fn is_named(&self, name: &str) -> bool {
+        if name_matches(&self.name, name) {
+            return true;
+        }
+        match self.args.first() {
+            Some(program) => program == name || Path::new(program).file_name().is_some_and(|file| file == name),
+            None => false,
+        }
+    }
+
+    ///
+    /// The command line the process was started with, or its name in
+    /// brackets if it has none, like a kernel thread.
+    ///
+    pub fn command_line(&self) -> String {
+        match self.args.is_empty() {
+            true => format!("[{}]", self.name),
+            false => self.args.join(" "),
+        }
+    }
+}
+
+///
+/// Lists every process on the system we can see, other than ourselves, in
+/// order of PID.
+///
+pub fn all() -> Result<Vec<ProcessEntry>> {
+    // processes can go away while we're reading them
+    Ok(pids()?.into_iter().filter_map(ProcessEntry::read).collect())
+}
+
+///
+/// Lists the PIDs of the processes the kernel knows by the given name, or
+/// the file name of the given path, in order. Only each process's name is
+/// read, which keeps this cheap enough to do over and over.
+///
+pub fn pids_named(name: &str) -> Result<Vec<i32>> {
+    let name = Path::new(name).file_name().map_or_else(|| name.into(), |file| file.to_string_lossy());
+    Ok(pids()?.into_iter()
+        .filter(|pid| {
+            fs::read_to_string(format!("/proc/{}/comm", pid))
+                .is_ok_and(|comm| name_matches(comm.trim_end_matches('\n'), &name))
+        })
+        .collect())
+}
+
+///
+/// The PIDs of every process other than ourselves, in order.
+///
+fn pids() -> Result<Vec<i32>> {
+    let ours = process::id() as i32;
+    let mut pids: Vec<i32> = fs::read_dir("/proc").map_err(DebugError::Sys)?
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+        .filter(|pid| *pid != ours)
+        .collect();
+    pids.sort_unstable();
+    Ok(pids)
+}
+
+///
+/// Whether the name the kernel knows a process by is the given one, which
+/// it may have cut short.
+///
+fn name_matches(comm: &str, name: &str) -> bool {
+    comm == name || (comm.len() == NAME_LENGTH && name.starts_with(comm))
+}
+
+///
+/// Lists the processes going by the given name, in order of PID.
+///
+pub fn named(name: &str) -> Result<Vec<ProcessEntry>> {
+    Ok(all()?.into_iter().filter(|entry| entry.is_named(name)).collect())
+}
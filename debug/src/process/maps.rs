@@ -0,0 +1,53 @@
+use std::fs;
+
+use crate::error::{DebugError, Result};
+
+///
+/// A single mapping in the address space of a process, as listed in
+/// `/proc/<pid>/maps`.
+///
+pub struct MemoryMap {
+    pub start: u64,
+    pub end: u64,
+    pub readable: bool,
+    pub writable: bool,
+    pub executable: bool,
+    /// The offset into the mapped file, if there is one.
+    pub offset: u64,
+    /// The path of the mapped file, or a pseudo-path such as `[stack]`.
+    pub path: Option<String>,
+}
+
+impl MemoryMap {
+    pub fn contains(&self, addr: u64) -> bool {
+        self.start <= addr && addr < self.end
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(6, ' ');
+        let (start, end) = fields.next()?.split_once('-')?;
+        let perms = fields.next()?.as_bytes();
+        let offset = fields.next()?;
+        let _device = fields.next()?;
+        let _inode = fields.next()?;
+        let path = fields.next().map(str::trim).filter(|p| !p.is_empty());
+
+        Some(Self {
+            start: u64::from_str_radix(start, 16).ok()?,
+            end: u64::from_str_radix(end, 16).ok()?,
+            readable: perms.first() == Some(&b'r'),
+            writable: perms.get(1) == Some(&b'w'),
+            executable: perms.get(2) == Some(&b'x'),
+            offset: u64::from_str_radix(offset, 16).ok()?,
+            path: path.map(String::from),
+        })
+    }
+}
+
+///
+/// Reads the memory maps of the given process, in address order.
+///
+pub fn read(pid: i32) -> Result<Vec<MemoryMap>> {
+    let maps = fs::read_to_string(format!("/proc/{}/maps", pid)).map_err(DebugError::Sys)?;
+    Ok(maps.lines().filter_map(MemoryMap::parse).collect())
+}
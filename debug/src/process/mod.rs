@@ -1,106 +1,1325 @@
 use std::ffi::CString;
+use std::fs;
+use std::fs::File;
+use std::io;
 use std::os::raw::c_char;
-use std::path::PathBuf;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::path::{Path, PathBuf};
 use std::ptr;
 
-use crate::error::{DebugError, Result};
+use crate::breakpoint::site::Sites;
+use crate::error::{DebugError, Reason, Result};
+use crate::inspect::Inspect;
+use crate::interrupt;
+use crate::launch::{show_limit, Launch, Redirect};
+use crate::signals;
+use crate::signals::SignalInfo;
+use crate::syscalls::{SyscallFilter, SyscallInfo};
+use crate::syscalls::seccomp;
+use crate::syscalls::seccomp::SeccompFilter;
 use crate::trace;
-use nix::sys::wait::{waitpid, WaitStatus};
-use nix::unistd::Pid;
+use crate::trace::{FpRegisters, Options, Registers};
 
-use log::info;
+use self::maps::MemoryMap;
+use self::thread::{Thread, Threads};
+use nix::errno::Errno;
+use nix::fcntl::OFlag;
+use nix::sys::personality;
+use nix::sys::personality::Persona;
+use nix::sys::signal::{kill, Signal};
+use nix::sys::wait::{waitpid, WaitPidFlag, WaitStatus};
+use nix::unistd::{close, pipe2, read, write, Pid};
 
-cfg_if! {
-    if #[cfg(any(target_os = "linux"))] {
-        use libc::__errno_location as errno_location;
-    } else if #[cfg(any(target_os = "macos"))] {
-        use libc::__error as errno_location;
-    }
+use log::{info, warn};
+
+pub mod auxv;
+pub mod list;
+pub mod maps;
+pub mod thread;
+mod waiter;
+
+///
+/// Why the process stopped after it was last continued.
+///
+pub enum Stop {
+    /// The process hit the breakpoint site at the given address.
+    Breakpoint(u64),
+    /// The process received a signal, with the given details.
+    Signal(SignalInfo),
+    /// The process exited with the given status.
+    Exited(i32),
+    /// The process was killed by the given signal.
+    Killed(Signal),
+    /// The thread was stopped at our request.
+    Interrupted,
+    /// The thread finished single stepping an instruction.
+    Stepped,
+    /// The thread entered or returned from a system call, while they're
+    /// being traced.
+    Syscall(SyscallInfo),
+    /// The process forked a child, which is stopped under the tracer.
+    /// With `vfork`, the two share memory until the child execs or exits.
+    Forked { child: i32, vfork: bool },
+    /// The process replaced its program with a new one.
+    Exec,
+    /// Nothing interesting happened to the process.
+    Other,
 }
 
 #[derive(Default)]
 pub struct Process {
     pub pid: i32,
     pub running: bool,
+    /// The threads of the process, including the main thread.
+    threads: Threads,
+    /// The thread whose registers are read and written, which is the thread
+    /// that last stopped unless another has been selected since.
+    current: i32,
+    /// Whether threads stop on their own, rather than all together.
+    non_stop: bool,
+    /// Forked children whose first stop was seen before the fork event.
+    early_children: Vec<i32>,
+    /// Whether the breakpoint sites have been taken out of memory, while
+    /// it is shared with a vfork child.
+    sites_suspended: bool,
+    /// The breakpoint sites currently inserted into the process.
+    sites: Sites,
+    /// Whether threads stop on entry to and exit from every system call.
+    trace_syscalls: bool,
+    /// The system calls trapped by the seccomp filter the process was
+    /// launched with, which threads stop on without tracing every call.
+    seccomp: Vec<u64>,
+    /// Whether we attached to the process, rather than launching it, in
+    /// which case it isn't ours to kill.
+    pub attached: bool,
+    /// The job control signal the process is stopped by, for as long as it
+    /// stays stopped.
+    stopped_by: Option<Signal>,
 }
 
 impl Process {
     pub fn new(pid: i32) -> Self {
+        let mut threads = Threads::default();
+        threads.add(pid);
         Self {
             pid,
             running: true,
+            threads,
+            current: pid,
+            non_stop: false,
+            early_children: Vec::new(),
+            sites_suspended: false,
+            sites: Sites::default(),
+            trace_syscalls: false,
+            seccomp: Vec::new(),
+            attached: false,
+            stopped_by: None,
+        }
+    }
+
+    ///
+    /// Attaches to a running process and every one of its threads, leaving
+    /// them all stopped. The threads are listed again until no new ones
+    /// turn up, since they may be created while we attach to the others.
+    ///
+    /// Threads are seized and interrupted rather than sent a SIGSTOP, so a
+    /// process that was stopped from the shell stays stopped, and one that
+    /// wasn't doesn't have its parent told it was.
+    ///
+    pub fn attach(pid: i32) -> Result<Self> {
+        let mut process = Process::new(pid);
+        process.attached = true;
+
+        let mut attached: Vec<i32> = Vec::new();
+        loop {
+            let tids: Vec<i32> = task_ids(pid)?.into_iter().filter(|tid| !attached.contains(tid)).collect();
+            if tids.is_empty() {
+                break;
+            }
+
+            for tid in tids {
+                attached.push(tid);
+                match trace::seize(tid, trace_options()) {
+                    Ok(()) => {}
+                    Err(e) if tid == pid => return Err(DebugError::TraceFailure(e)),
+                    // the thread exited before we got to it
+                    Err(_) => continue,
+                }
+                if tid != pid {
+                    process.threads.add(tid);
+                }
+                if !process.attach_stopped(tid)? {
+                    process.threads.remove(tid);
+                }
+            }
         }
+        Ok(process)
     }
 
-    pub fn attach(_pid: i32) -> Self {
-        unimplemented!();
+    ///
+    /// Interrupts a thread we've just seized, and waits for it to stop.
+    /// Should it stop with a signal first, that is delivered once it's
+    /// resumed, and the interrupt is passed over when it comes. Returns
+    /// whether the thread is still there.
+    ///
+    fn attach_stopped(&mut self, tid: i32) -> Result<bool> {
+        // should it have exited in between, that's reported below
+        let _ = trace::interrupt(tid);
+        match waitpid(Pid::from_raw(tid), Some(WaitPidFlag::__WALL))? {
+            WaitStatus::PtraceEvent(_, sig, libc::PTRACE_EVENT_STOP) => {
+                self.group_stop_changed(tid, sig);
+                Ok(true)
+            }
+            WaitStatus::Stopped(_, sig) => {
+                self.signal_stopped(tid, sig);
+                if let Some(thread) = self.threads.get_mut(tid) {
+                    thread.stop_expected = true;
+                }
+                Ok(true)
+            }
+            WaitStatus::Exited(..) | WaitStatus::Signaled(..) if tid == self.pid => {
+                Err(DebugError::InvalidOperation(Reason::NoProcess))
+            }
+            WaitStatus::Exited(..) | WaitStatus::Signaled(..) => Ok(false),
+            _ => {
+                if let Some(thread) = self.threads.get_mut(tid) {
+                    thread.stop_expected = true;
+                }
+                Ok(true)
+            }
+        }
     }
 
+    ///
+    /// Detaches from every thread of the process. Interrupts that haven't
+    /// been reported yet are dropped by the kernel as we go, and a process
+    /// stopped by job control is left stopped, just as we found it. Signals
+    /// the threads stopped with, and which we've yet to deliver, are
+    /// delivered as each one is let go.
+    ///
     pub fn detach(&mut self) -> Result<()> {
-        trace::detach(self.pid).map_err(DebugError::TraceFailure)?;
+        if !self.seccomp.is_empty() {
+            warn!("process {} has a seccomp filter, so the syscalls it stops on will fail once detached.", self.pid);
+        }
+        self.stop_all()?;
+
+        for tid in self.threads.tids() {
+            let signal = self.threads.get_mut(tid).and_then(|thread| thread.signal.take());
+            trace::detach(tid, signal).map_err(DebugError::TraceFailure)?;
+        }
         self.running = false;
         Ok(())
     }
 
-    pub fn start<P: Into<PathBuf>>(path: P, args: Vec<String>, env: Option<Vec<String>>) -> Result<Self> {
-        if cfg!(any(target_os="linux", target_os="macos")) {
-            Process::do_start(path.into(), args, env, || {
-                use trace::TraceError::*;
-                if let Err(e) = trace::trace_me() {
-                    match e {
-                        Sys(n) => n.raw_os_error().unwrap_or(-1) as i64,
+    ///
+    /// Kills the process with SIGKILL, and reaps every one of its threads,
+    /// so that nothing is left of it to report later.
+    ///
+    pub fn kill(&mut self) -> Result<()> {
+        kill(Pid::from_raw(self.pid), Signal::SIGKILL)?;
+        for tid in self.threads.tids().into_iter().filter(|tid| *tid != self.pid) {
+            // threads that are already gone have nothing left to report
+            let _ = waitpid(Pid::from_raw(tid), Some(WaitPidFlag::__WALL));
+        }
+
+        // the main thread may still report stops it had queued up before it
+        // goes, and is only reaped once the rest of the threads are.
+        loop {
+            match waitpid(Pid::from_raw(self.pid), Some(WaitPidFlag::__WALL))? {
+                WaitStatus::Exited(..) | WaitStatus::Signaled(..) => break,
+                _ => continue,
+            }
+        }
+        self.threads = Threads::default();
+        self.running = false;
+        Ok(())
+    }
+
+    ///
+    /// Launches the file at the given path as a new traced process. The
+    /// process is stopped just after exec, before any of its code has run.
+    ///
+    /// It's given the arguments, environment, working directory and
+    /// standard streams the launch configuration asks for. The files its
+    /// streams are redirected to are opened here, relative to where it
+    /// runs, so that failing to open one fails the launch, rather than the
+    /// child.
+    ///
+    /// If it's known which system calls will need to be stopped on, a
+    /// seccomp filter is installed before exec so that only those calls
+    /// stop, rather than tracing every single one.
+    ///
+    /// The child waits on a pipe until we've seized it, rather than asking
+    /// to be traced itself, so that it's traced the same way as processes
+    /// we attach to.
+    ///
+    pub fn start<P: Into<PathBuf>>(path: P, launch: &Launch, syscalls: &[&SyscallFilter]) -> Result<Self> {
+        let cwd = match &launch.cwd {
+            Some(cwd) if !cwd.is_dir() => {
+                let e = io::Error::new(io::ErrorKind::NotFound, format!("{}: not a directory", cwd.display()));
+                return Err(DebugError::Sys(e));
+            }
+            Some(cwd) => Some(CString::new(cwd.as_os_str().as_bytes()).map_err(|e| DebugError::Sys(e.into()))?),
+            None => None,
+        };
+        let dir = launch.cwd.as_deref();
+        let opened = |redirect: &Redirect, file: io::Result<File>| file.map_err(|e| {
+            DebugError::Sys(io::Error::new(e.kind(), format!("cannot open {}: {}", redirect.path_in(dir).display(), e)))
+        });
+        let mut streams = Vec::new();
+        if let Some(redirect) = &launch.stdin {
+            streams.push((opened(redirect, redirect.open_input(dir))?, libc::STDIN_FILENO));
+        }
+        if let Some(redirect) = &launch.stdout {
+            streams.push((opened(redirect, redirect.open_output(dir))?, libc::STDOUT_FILENO));
+        }
+        if let Some(redirect) = &launch.stderr {
+            streams.push((opened(redirect, redirect.open_output(dir))?, libc::STDERR_FILENO));
+        }
+        let mut limits = Vec::new();
+        for (limit, value) in &launch.limits {
+            let mut current = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+            if unsafe { libc::getrlimit(limit.resource(), &mut current) } < 0 {
+                return Err(DebugError::Sys(io::Error::last_os_error()));
+            }
+            // only the soft limit can be changed freely, up to the hard one
+            if *value > current.rlim_max {
+                let e = format!("{} limit {} is above the hard limit {}", limit, value, show_limit(current.rlim_max));
+                return Err(DebugError::Sys(io::Error::new(io::ErrorKind::InvalidInput, e)));
+            }
+            limits.push((limit.resource(), libc::rlimit { rlim_cur: *value, rlim_max: current.rlim_max }));
+        }
+
+        let numbers: Option<Vec<u64>> = syscalls.iter()
+            .map(|filter| filter.numbers())
+            .try_fold(Vec::new(), |mut all, numbers| {
+                all.extend_from_slice(numbers?);
+                Some(all)
+            });
+        let filter = numbers.and_then(|numbers| SeccompFilter::new(&numbers));
+        let (ready, release) = pipe2(OFlag::O_CLOEXEC)?;
+        // closed by a successful exec, or used to tell us why it failed
+        let (failures, failed) = pipe2(OFlag::O_CLOEXEC)?;
+
+        // the path is relative to where we are, not where the program runs
+        let path = std::env::current_dir().map_err(DebugError::Sys)?.join(path.into());
+        let spawned = if cfg!(any(target_os="linux", target_os="macos")) {
+            Process::do_start(path.clone(), launch.args.clone(), Some(launch.environment()), failed, || {
+                // we're only released once traced, and if we get nothing
+                // but the end of the pipe the tracer has given up on us
+                let _ = close(release);
+                let _ = close(failures);
+                if read(ready, &mut [0u8]) != Ok(1) {
+                    return Err(ChildStep::Handshake);
+                }
+                let _ = close(ready);
+                if let Some(cwd) = &cwd {
+                    if unsafe { libc::chdir(cwd.as_ptr()) } < 0 {
+                        return Err(ChildStep::Chdir);
                     }
-                } else {
-                    0i64
                 }
+                for (file, fd) in &streams {
+                    if unsafe { libc::dup2(file.as_raw_fd(), *fd) } < 0 {
+                        return Err(ChildStep::Redirect(*fd));
+                    }
+                }
+                for (idx, (resource, limit)) in limits.iter().enumerate() {
+                    if unsafe { libc::setrlimit(*resource, limit) } < 0 {
+                        return Err(ChildStep::Limit(idx));
+                    }
+                }
+                // this may not be allowed, e.g. in a container, in which
+                // case the process just runs with randomization
+                if launch.disable_randomization {
+                    if let Ok(persona) = personality::get() {
+                        let _ = personality::set(persona | Persona::ADDR_NO_RANDOMIZE);
+                    }
+                }
+                // without the filter, the calls are still caught by tracing
+                // every system call, just more slowly.
+                if let Some(filter) = &filter {
+                    let _ = filter.install();
+                }
+                Ok(())
             })
         } else {
             unimplemented!("Unknown platform!");
+        };
+
+        let _ = close(ready);
+        let _ = close(failed);
+        let mut process = match spawned {
+            Ok(process) => process,
+            Err(e) => {
+                let _ = close(release);
+                let _ = close(failures);
+                return Err(e);
+            }
+        };
+        // should we die without letting go of the process, it's killed
+        // rather than left stopped, or trapping on our breakpoints.
+        let options = trace_options() | Options::PTRACE_O_EXITKILL;
+        let seized = trace::seize(process.pid, options);
+        if seized.is_ok() {
+            // if this fails the child exits instead, which fails the launch
+            let _ = write(release, &[0u8]);
+        }
+        let _ = close(release);
+        if let Err(e) = seized {
+            let _ = close(failures);
+            waitpid(Pid::from_raw(process.pid), None)?;
+            return Err(DebugError::TraceFailure(e));
+        }
+
+        let status = waitpid(Pid::from_raw(process.pid), None);
+        // the child has said why it failed, if it has exited on its own
+        let failure = match status {
+            Ok(WaitStatus::Exited(..)) => read_failure(failures),
+            _ => None,
+        };
+        let _ = close(failures);
+        match status {
+            Ok(WaitStatus::PtraceEvent(_, _, libc::PTRACE_EVENT_EXEC)) => {
+                if launch.disable_randomization && !randomization_disabled(process.pid) {
+                    warn!("failed to disable address space randomization for process {}", process.pid);
+                }
+                if let Some(filter) = filter.filter(|_| seccomp::installed(process.pid)) {
+                    info!("using a seccomp filter to stop on syscalls, so setuid programs won't gain privileges");
+                    process.seccomp = filter.numbers().to_vec();
+                }
+                Ok(process)
+            }
+            Ok(WaitStatus::Exited(..)) | Ok(WaitStatus::Signaled(..)) => match failure {
+                Some((step, errno)) => Err(step.error(&path, launch, errno)),
+                None => Err(DebugError::InvalidOperation(Reason::LaunchFailed)),
+            },
+            status => {
+                // the child is still there, stopped somewhere it shouldn't
+                // be, and would be left behind if it weren't killed
+                let _ = process.kill();
+                status?;
+                Err(DebugError::InvalidOperation(Reason::LaunchFailed))
+            }
+        }
+    }
+
+    ///
+    /// Creates the process for a child forked by this one, which has just
+    /// been seen to stop for the first time. It starts out with the same
+    /// breakpoint sites, since its memory is a copy of ours.
+    ///
+    pub fn forked(&self, child: i32) -> Self {
+        let mut process = Process::new(child);
+        process.sites = self.sites.clone();
+        process.non_stop = self.non_stop;
+        process.trace_syscalls = self.trace_syscalls;
+        process.seccomp = self.seccomp.clone();
+        process.attached = self.attached;
+        process
+    }
+
+    ///
+    /// Takes every breakpoint site out of memory without forgetting them,
+    /// while it is shared with a vfork child that isn't being debugged.
+    /// They are put back once the child is done with the memory.
+    ///
+    pub fn suspend_sites(&mut self) -> Result<()> {
+        let tid = self.memory_tid();
+        for site in self.sites.iter() {
+            self.sites.suspend(tid, site.addr)?;
+        }
+        self.sites_suspended = true;
+        Ok(())
+    }
+
+    fn resume_sites(&mut self) -> Result<()> {
+        let tid = self.memory_tid();
+        for site in self.sites.iter() {
+            self.sites.resume(tid, site.addr)?;
         }
+        self.sites_suspended = false;
+        Ok(())
+    }
+
+    ///
+    /// Switches between stopping every thread whenever one of them stops
+    /// (all-stop), and leaving the others running (non-stop).
+    ///
+    pub fn set_non_stop(&mut self, non_stop: bool) {
+        self.non_stop = non_stop;
+    }
+
+    ///
+    /// Whether the process was launched with a seccomp filter, which it
+    /// can't be rid of. The calls it traps fail with ENOSYS once there's
+    /// no tracer to stop on them.
+    ///
+    pub fn has_seccomp_filter(&self) -> bool {
+        !self.seccomp.is_empty()
+    }
+
+    ///
+    /// Sets the system calls threads need to stop on, which takes effect as
+    /// they're next resumed. Unless the seccomp filter the process was
+    /// launched with already stops on all of them, threads stop on entry to
+    /// and exit from every system call.
+    ///
+    pub fn stop_on_syscalls(&mut self, syscalls: &[&SyscallFilter]) {
+        self.trace_syscalls = !syscalls.iter().all(|filter| match filter.numbers() {
+            Some(numbers) => numbers.iter().all(|number| self.seccomp.contains(number)),
+            None => false,
+        });
     }
 
     ///
     /// Continue executing until the next event, where that can be a breakpoint
     /// is hit (SIGTRAP), the process has exited, or other possible wait statuses.
     ///
-    pub fn proceed(&mut self) -> Result<()> {
-        trace::proceed(self.pid).map_err(DebugError::TraceFailure)?;
-        match waitpid(Pid::from_raw(self.pid), None) {
-            Ok(WaitStatus::Stopped(pid, sig)) => info!("process {} stopped with signal {}", pid, sig),
-            Ok(WaitStatus::Exited(pid, status)) => {
-                info!("process {} exited (status: {})", pid, status);
+    /// Every thread is resumed together, and as soon as one of them stops the
+    /// others are stopped too, so the whole process is always either running
+    /// or stopped. The thread that stopped becomes the current thread.
+    ///
+    /// If a thread is sat on a breakpoint site, the original instruction is
+    /// stepped over first so the breakpoint isn't immediately hit again.
+    ///
+    /// If `only_current` is set, the other threads are left stopped, and
+    /// only the current thread runs.
+    ///
+    pub fn proceed(&mut self, only_current: bool) -> Result<Stop> {
+        if let Some(stop) = self.take_pending(only_current) {
+            return Ok(stop);
+        }
+
+        let tids = if only_current { vec![self.current] } else { self.threads.tids() };
+        if let Some(stop) = self.resume_stopped(&tids)? {
+            return Ok(stop);
+        }
+        self.wait_stop(None)
+    }
+
+    ///
+    /// Single steps the current thread by one instruction. If `only_current`
+    /// isn't set, the other threads run freely while it does, until the
+    /// step finishes or something else happens.
+    ///
+    pub fn step(&mut self, only_current: bool) -> Result<Stop> {
+        if let Some(stop) = self.take_pending(true) {
+            return Ok(stop);
+        }
+
+        let tid = self.current;
+        let pc = self.current_thread().pc()?;
+        if self.sites.get(pc).is_some() {
+            // stepping over the site is a single step in itself, which is
+            // done with every other thread stopped.
+            return match self.step_over_site(tid)? {
+                None => Ok(Stop::Stepped),
+                Some(status) => match self.handle_status(status)? {
+                    Some((_, stop)) => Ok(stop),
+                    None => self.wait_stop(Some(tid)),
+                }
+            };
+        }
+
+        if !only_current && !self.non_stop {
+            let others: Vec<i32> = self.threads.tids().into_iter().filter(|other| *other != tid).collect();
+            if let Some(stop) = self.resume_stopped(&others)? {
+                return Ok(stop);
+            }
+        }
+
+        let signal = self.threads.get_mut(tid).and_then(|thread| {
+            thread.syscall = None;
+            thread.signal.take()
+        });
+        trace::step(tid, signal).map_err(DebugError::TraceFailure)?;
+        if let Some(thread) = self.threads.get_mut(tid) {
+            thread.stopped = false;
+            thread.stepping = true;
+        }
+        self.wait_stop(if self.non_stop { Some(tid) } else { None })
+    }
+
+    ///
+    /// Threads may have stopped for something else while they were last
+    /// being stopped, which gets reported before anything runs again.
+    ///
+    fn take_pending(&mut self, only_current: bool) -> Option<Stop> {
+        let current = self.current;
+        let (tid, stop) = self.threads.iter_mut()
+            .filter(|thread| !only_current || thread.tid == current)
+            .find_map(|thread| thread.pending.take().map(|stop| (thread.tid, stop)))?;
+        self.current = tid;
+        Some(stop)
+    }
+
+    ///
+    /// Resumes the given threads that are stopped, stepping each over the
+    /// breakpoint site it is sat on first. Returns why the process stopped,
+    /// if it does while one of them is being stepped.
+    ///
+    fn resume_stopped(&mut self, tids: &[i32]) -> Result<Option<Stop>> {
+        for tid in tids {
+            if let Some(status) = self.step_over_site(*tid)? {
+                if let Some((_, stop)) = self.handle_status(status)? {
+                    return Ok(Some(stop));
+                }
+            }
+        }
+
+        for tid in tids {
+            if self.threads.get(*tid).is_some_and(|thread| thread.stopped) {
+                self.resume(*tid)?;
+            }
+        }
+        Ok(None)
+    }
+
+    ///
+    /// Waits until the process stops, for any thread or just the given one.
+    ///
+    fn wait_stop(&mut self, tid: Option<i32>) -> Result<Stop> {
+        let _waiting = interrupt::waiting();
+        loop {
+            let status = match self.wait_status(tid)? {
+                Some(status) => status,
+                None => return Ok(Stop::Interrupted),
+            };
+            if let Some((_, stop)) = self.handle_status(status)? {
+                return Ok(stop);
+            }
+        }
+    }
+
+    ///
+    /// Waits for the next status from any thread, or just the given one.
+    /// Rather than blocking in `waitpid`, we sleep until there may be a
+    /// status, so that being asked to interrupt the process wakes us up
+    /// too. A running thread is then interrupted, and its stop answers the
+    /// request, unless the process already has the terminal's SIGINT to
+    /// stop for.
+    ///
+    /// A process stopped by job control reports nothing until it's
+    /// continued, so every thread is stopped instead, and `None` is
+    /// returned. A single thread is only waited for while it steps, which
+    /// is over too soon to need interrupting.
+    ///
+    fn wait_status(&mut self, tid: Option<i32>) -> Result<Option<WaitStatus>> {
+        let pid = tid.map(Pid::from_raw);
+        if pid.is_some() && self.stopped_by.is_none() {
+            return Ok(Some(waitpid(pid, Some(WaitPidFlag::__WALL))?));
+        }
+
+        let mut interrupted = false;
+        loop {
+            match waitpid(pid, Some(WaitPidFlag::__WALL | WaitPidFlag::WNOHANG))? {
+                WaitStatus::StillAlive => {}
+                status => return Ok(Some(status)),
+            }
+            if !interrupted && interrupt::requested() {
+                if self.stopped_by.is_some() {
+                    interrupt::take_request();
+                    self.stop_all()?;
+                    return Ok(None);
+                }
+                interrupted = true;
+                if !self.sigint_pending() {
+                    let tid = self.running_thread();
+                    if let Err(e) = trace::interrupt(tid) {
+                        warn!("failed to interrupt thread {}: {}", tid, e);
+                    }
+                }
+            }
+            waiter::sleep()?;
+        }
+    }
+
+    ///
+    /// Whether the process has a SIGINT waiting for one of its threads to
+    /// take it, as it does when it shares our terminal and the user pressed
+    /// Ctrl-C. A signal every thread blocks is never taken, so isn't
+    /// counted.
+    ///
+    fn sigint_pending(&self) -> bool {
+        let bit = 1u64 << (libc::SIGINT - 1);
+        let mask = |path: String, field: &str| -> Option<u64> {
+            let status = fs::read_to_string(path).ok()?;
+            let value = status.lines().find_map(|line| line.strip_prefix(field))?;
+            u64::from_str_radix(value.trim(), 16).ok()
+        };
+        let pending = mask(format!("/proc/{}/status", self.pid), "ShdPnd:").is_some_and(|mask| mask & bit != 0);
+        pending && self.threads.iter().any(|thread| {
+            mask(format!("/proc/{}/task/{}/status", self.pid, thread.tid), "SigBlk:").is_some_and(|mask| mask & bit == 0)
+        })
+    }
+
+    ///
+    /// Resumes a single stopped thread, leaving the others as they are, for
+    /// non-stop mode. If the thread stops again straight away, e.g. while
+    /// stepping over a breakpoint site, that is returned.
+    ///
+    /// Other threads that are running while the thread steps over a site
+    /// may pass over it without stopping.
+    ///
+    pub fn resume_thread(&mut self, tid: i32) -> Result<Option<(i32, Stop)>> {
+        match self.threads.get(tid) {
+            Some(thread) if thread.stopped => {}
+            Some(_) => return Ok(None),
+            None => return Err(DebugError::InvalidOperation(Reason::NoSuchThread)),
+        }
+
+        if let Some(status) = self.step_over_site(tid)? {
+            if let Some(event) = self.handle_status(status)? {
+                return Ok(Some(event));
+            }
+        }
+
+        if self.threads.get(tid).is_some_and(|thread| thread.stopped) {
+            self.resume(tid)?;
+        }
+        Ok(None)
+    }
+
+    ///
+    /// Stops a single running thread, leaving the others as they are, for
+    /// non-stop mode. The thread may report stopping for another reason
+    /// first, which is returned in place of the interruption. Returns
+    /// `None` if the thread was already stopped.
+    ///
+    pub fn interrupt_thread(&mut self, tid: i32) -> Result<Option<(i32, Stop)>> {
+        match self.threads.get(tid) {
+            Some(thread) if !thread.stopped => thread.interrupt()?,
+            Some(_) => return Ok(None),
+            None => return Err(DebugError::InvalidOperation(Reason::NoSuchThread)),
+        }
+
+        loop {
+            let status = waitpid(Pid::from_raw(tid), Some(WaitPidFlag::__WALL))?;
+            if let WaitStatus::PtraceEvent(_, sig, libc::PTRACE_EVENT_STOP) = status {
+                self.thread_stopped(tid);
+                self.group_stop_changed(tid, sig);
+                return Ok(Some((tid, Stop::Interrupted)));
+            }
+
+            if let Some(event) = self.handle_status(status)? {
+                if let Some(thread) = self.threads.get_mut(tid) {
+                    thread.stop_expected = true;
+                }
+                return Ok(Some(event));
+            }
+            if self.threads.get(tid).is_none() {
+                return Ok(None);
+            }
+        }
+    }
+
+    ///
+    /// Waits for the next event from any of the running threads, for non-stop
+    /// mode. Returns `None` if nothing is running, or if `block` isn't set
+    /// and nothing has happened yet.
+    ///
+    pub fn wait(&mut self, block: bool) -> Result<Option<(i32, Stop)>> {
+        let _waiting = block.then(interrupt::waiting);
+        loop {
+            if !self.running || self.threads.iter().all(|thread| thread.stopped) {
+                return Ok(None);
+            }
+
+            let status = if block {
+                match self.wait_status(None)? {
+                    Some(status) => status,
+                    None => return Ok(Some((self.current, Stop::Interrupted))),
+                }
+            } else {
+                waitpid(None, Some(WaitPidFlag::__WALL | WaitPidFlag::WNOHANG))?
+            };
+            if let WaitStatus::StillAlive = status {
+                return Ok(None);
+            }
+            if let Some(event) = self.handle_status(status)? {
+                return Ok(Some(event));
+            }
+        }
+    }
+
+    ///
+    /// A thread which is still running, to be interrupted if we're asked to
+    /// stop the process, or the main thread if none are.
+    ///
+    fn running_thread(&self) -> i32 {
+        self.threads.iter().find(|thread| !thread.stopped).map_or(self.pid, |thread| thread.tid)
+    }
+
+    ///
+    /// Stops every thread which is still running.
+    ///
+    pub fn stop(&mut self) -> Result<()> {
+        self.stop_all()
+    }
+
+    ///
+    /// Deals with a status reported by one of the threads, either returning
+    /// the thread and why it stopped, or `None` if it should keep running.
+    /// In all-stop mode, every other thread is stopped along with it.
+    ///
+    fn handle_status(&mut self, status: WaitStatus) -> Result<Option<(i32, Stop)>> {
+        let tid = match status.pid() {
+            Some(pid) => pid.as_raw(),
+            None => return Ok(Some((self.current, Stop::Other))),
+        };
+
+        let stop = match status {
+            WaitStatus::PtraceEvent(_, _, libc::PTRACE_EVENT_CLONE) => {
+                let new = trace::get_event_message(tid).map_err(DebugError::TraceFailure)? as i32;
+                self.thread_created(new)?;
+                // it's already running if its first stop came before the event
+                if self.threads.get(new).is_some_and(|thread| thread.stopped) {
+                    self.resume(new)?;
+                }
+                self.resume(tid)?;
+                return Ok(None);
+            }
+            WaitStatus::PtraceEvent(_, _, libc::PTRACE_EVENT_STOP) if self.threads.get(tid).is_none() => {
+                // new threads and children can report their first stop
+                // before the event that tells us they were created.
+                if is_thread(self.pid, tid) {
+                    let id = self.threads.add(tid);
+                    info!("new thread {} ({})", id, tid);
+                    self.resume(tid)?;
+                } else {
+                    self.early_children.push(tid);
+                }
+                return Ok(None);
+            }
+            WaitStatus::PtraceEvent(_, sig, libc::PTRACE_EVENT_STOP) => match self.event_stopped(tid, sig)? {
+                Some(stop) => stop,
+                None => return Ok(None),
+            }
+            WaitStatus::PtraceEvent(_, _, event) => match self.ptrace_event(tid, event)? {
+                Some(stop) => stop,
+                None => {
+                    self.resume(tid)?;
+                    return Ok(None);
+                }
+            }
+            WaitStatus::Stopped(_, Signal::SIGTRAP) if self.threads.get(tid).is_some_and(|t| t.stepping) => {
+                self.thread_stopped(tid);
+                Stop::Stepped
+            }
+            // a process sharing our terminal gets the Ctrl-C we were asked to
+            // interrupt it for, which it would otherwise be killed by.
+            WaitStatus::Stopped(_, Signal::SIGINT) if interrupt::take_request() => {
+                self.thread_stopped(tid);
+                Stop::Interrupted
+            }
+            WaitStatus::Stopped(_, sig) => {
+                self.thread_stopped(tid);
+                match self.rewind_breakpoint(tid, sig)? {
+                    Some(addr) => Stop::Breakpoint(addr),
+                    None => Stop::Signal(self.signal_stopped(tid, sig)),
+                }
+            }
+            WaitStatus::Exited(_, status) if tid == self.pid => {
+                info!("process {} exited (status: {})", tid, status);
+                self.running = false;
+                Stop::Exited(status)
+            }
+            WaitStatus::Signaled(_, sig, _b) if tid == self.pid => {
+                info!("process {} received signal {}", tid, sig);
                 self.running = false;
+                Stop::Killed(sig)
             }
-            Ok(WaitStatus::Signaled(pid, sig, _b)) => info!("process {} received signal {}", pid, sig),
-            Ok(WaitStatus::PtraceEvent(_, _, _)) => {
-                todo!("ptrace event")
+            WaitStatus::Exited(..) | WaitStatus::Signaled(..) => {
+                self.thread_exited(tid);
+                return Ok(None);
             }
-            Ok(WaitStatus::PtraceSyscall(_)) => {
-                todo!("ptrace syscall")
+            WaitStatus::PtraceSyscall(_) => {
+                self.thread_stopped(tid);
+                Stop::Syscall(self.syscall_stopped(tid)?)
             }
-            Ok(WaitStatus::Continued(_)) => {}
-            Ok(WaitStatus::StillAlive) => {}
-            Err(e) => return Err(e.into()),
+            WaitStatus::Continued(_) => Stop::Other,
+            WaitStatus::StillAlive => Stop::Other,
         };
+
+        if self.running && !self.non_stop {
+            self.current = tid;
+            self.stop_all()?;
+        }
+        Ok(Some((tid, stop)))
+    }
+
+    ///
+    /// Stops every thread which is still running, waiting for each of them
+    /// to report the stop. Threads that stop for another reason on the way
+    /// keep it to be reported later, except for breakpoints, which they
+    /// will simply hit again once resumed.
+    ///
+    /// Threads in a group-stop are interrupted too, so that they can be
+    /// inspected, and go back to it once resumed.
+    ///
+    fn stop_all(&mut self) -> Result<()> {
+        for tid in self.threads.tids() {
+            match self.threads.get(tid) {
+                Some(thread) if !thread.stopped => thread.interrupt()?,
+                _ => continue,
+            }
+
+            let status = waitpid(Pid::from_raw(tid), Some(WaitPidFlag::__WALL))?;
+            match status {
+                WaitStatus::PtraceEvent(_, sig, libc::PTRACE_EVENT_STOP) => {
+                    self.thread_stopped(tid);
+                    self.group_stop_changed(tid, sig);
+                }
+                WaitStatus::Stopped(_, Signal::SIGTRAP) if self.threads.get(tid).is_some_and(|t| t.stepping) => {
+                    self.thread_stopped(tid);
+                    let thread = self.threads.get_mut(tid).unwrap();
+                    thread.stop_expected = true;
+                    thread.pending = Some(Stop::Stepped);
+                }
+                WaitStatus::Stopped(_, Signal::SIGINT) if interrupt::take_request() => {
+                    self.thread_stopped(tid);
+                    let thread = self.threads.get_mut(tid).unwrap();
+                    thread.stop_expected = true;
+                    thread.pending = Some(Stop::Interrupted);
+                }
+                WaitStatus::Stopped(_, sig) => {
+                    self.thread_stopped(tid);
+                    if self.rewind_breakpoint(tid, sig)?.is_none() {
+                        let info = self.signal_stopped(tid, sig);
+                        self.threads.get_mut(tid).unwrap().pending = Some(Stop::Signal(info));
+                    }
+                    self.threads.get_mut(tid).unwrap().stop_expected = true;
+                }
+                WaitStatus::PtraceEvent(_, _, libc::PTRACE_EVENT_CLONE) => {
+                    let new = trace::get_event_message(tid).map_err(DebugError::TraceFailure)? as i32;
+                    self.thread_created(new)?;
+                    self.thread_stopped(tid);
+                    self.threads.get_mut(tid).unwrap().stop_expected = true;
+                }
+                WaitStatus::Exited(..) | WaitStatus::Signaled(..) => self.thread_exited(tid),
+                WaitStatus::PtraceSyscall(_) => {
+                    self.thread_stopped(tid);
+                    let info = self.syscall_stopped(tid)?;
+                    let thread = self.threads.get_mut(tid).unwrap();
+                    thread.stop_expected = true;
+                    thread.pending = Some(Stop::Syscall(info));
+                }
+                WaitStatus::PtraceEvent(_, _, event) => {
+                    let stop = self.ptrace_event(tid, event)?;
+                    if let Some(thread) = self.threads.get_mut(tid) {
+                        thread.stop_expected = true;
+                        thread.pending = stop;
+                    }
+                }
+                _ => {
+                    self.thread_stopped(tid);
+                    self.threads.get_mut(tid).unwrap().stop_expected = true;
+                }
+            }
+        }
         Ok(())
     }
 
     ///
-    /// Reads a single word from the given address in the process memory.
+    /// Deals with a thread stopping for a ptrace event other than a new
+    /// thread being created, returning the stop to report if it's worth
+    /// reporting. The thread is left stopped either way.
+    ///
+    fn ptrace_event(&mut self, tid: i32, event: i32) -> Result<Option<Stop>> {
+        self.thread_stopped(tid);
+        match event {
+            libc::PTRACE_EVENT_FORK | libc::PTRACE_EVENT_VFORK => {
+                let child = trace::get_event_message(tid).map_err(DebugError::TraceFailure)? as i32;
+                match self.early_children.iter().position(|early| *early == child) {
+                    Some(idx) => {
+                        self.early_children.remove(idx);
+                    }
+                    None => {
+                        waitpid(Pid::from_raw(child), Some(WaitPidFlag::__WALL))?;
+                    }
+                }
+                Ok(Some(Stop::Forked { child, vfork: event == libc::PTRACE_EVENT_VFORK }))
+            }
+            libc::PTRACE_EVENT_VFORK_DONE => {
+                if self.sites_suspended {
+                    self.resume_sites()?;
+                }
+                Ok(None)
+            }
+            libc::PTRACE_EVENT_SECCOMP => Ok(self.seccomp_stopped(tid)?.map(Stop::Syscall)),
+            libc::PTRACE_EVENT_EXEC => {
+                // the old program is gone, along with every other thread
+                // and every breakpoint instruction that was written into it.
+                self.threads = Threads::default();
+                self.threads.add(self.pid);
+                self.current = self.pid;
+                self.sites = Sites::default();
+                self.sites_suspended = false;
+                Ok(Some(Stop::Exec))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    ///
+    /// Resumes a single stopped thread, carrying on with its step if it
+    /// was interrupted in the middle of one, and delivering the signal it
+    /// stopped with, if it still has one.
+    ///
+    /// A thread resumed other than to trace system calls won't stop as the
+    /// call it is in returns, so it is no longer considered to be in one.
+    /// One that stopped on a call trapped by the seccomp filter is traced
+    /// until it returns.
+    ///
+    /// A thread in a group-stop is only listened to, so it stays stopped
+    /// until the process is continued, keeping any signal until then.
+    ///
+    fn resume(&mut self, tid: i32) -> Result<()> {
+        let trace_syscalls = self.trace_syscalls;
+        let seccomp = !self.seccomp.is_empty();
+        match self.threads.get_mut(tid) {
+            Some(thread) if thread.group_stopped && !thread.stepping => trace::listen(tid),
+            Some(thread) if thread.stepping => {
+                thread.syscall = None;
+                trace::step(tid, thread.signal.take())
+            }
+            Some(thread) if trace_syscalls || seccomp && thread.syscall.is_some() => {
+                trace::syscall(tid, thread.signal.take())
+            }
+            Some(thread) => {
+                thread.syscall = None;
+                trace::proceed(tid, thread.signal.take())
+            }
+            None if trace_syscalls => trace::syscall(tid, None),
+            None => trace::proceed(tid, None),
+        }.map_err(DebugError::TraceFailure)?;
+        if let Some(thread) = self.threads.get_mut(tid) {
+            thread.stopped = false;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Deals with a thread reporting a `PTRACE_EVENT_STOP` after its first:
+    /// an interrupt of ours it's only just got to, or the process being
+    /// stopped or continued by a job control signal. The thread is resumed,
+    /// which for a group-stop leaves it stopped until the process is
+    /// continued, just as it would be without us. Returns the stop to
+    /// report, if we were asked to interrupt the process in the meantime.
+    ///
+    fn event_stopped(&mut self, tid: i32, sig: Signal) -> Result<Option<Stop>> {
+        self.thread_stopped(tid);
+        self.group_stop_changed(tid, sig);
+        if let Some(thread) = self.threads.get_mut(tid) {
+            thread.stop_expected = false;
+        }
+        // the stop is how we're interrupted when asked to, which also works
+        // on a process stopped by job control.
+        if interrupt::take_request() {
+            return Ok(Some(Stop::Interrupted));
+        }
+        self.resume(tid)?;
+        Ok(None)
+    }
+
+    ///
+    /// Records whether a thread that reported a `PTRACE_EVENT_STOP` with the
+    /// given signal is in a group-stop, noting when the process as a whole
+    /// is stopped or continued by job control.
+    ///
+    fn group_stop_changed(&mut self, tid: i32, sig: Signal) {
+        let stopped = is_stop_signal(sig);
+        if let Some(thread) = self.threads.get_mut(tid) {
+            thread.group_stopped = stopped;
+        }
+        if stopped && self.stopped_by.is_none() {
+            info!("process {} was stopped by {}", self.pid, sig);
+            self.stopped_by = Some(sig);
+        } else if !stopped && self.stopped_by.take().is_some() {
+            info!("process {} was continued", self.pid);
+        }
+    }
+
+    ///
+    /// Adds a thread that has just been created, waiting for its initial
+    /// stop if it hasn't already been seen. The new thread is left stopped.
+    ///
+    fn thread_created(&mut self, tid: i32) -> Result<()> {
+        if self.threads.get(tid).is_none() {
+            waitpid(Pid::from_raw(tid), Some(WaitPidFlag::__WALL))?;
+            let id = self.threads.add(tid);
+            info!("new thread {} ({})", id, tid);
+        }
+        Ok(())
+    }
+
+    fn thread_stopped(&mut self, tid: i32) {
+        if let Some(thread) = self.threads.get_mut(tid) {
+            thread.stopped = true;
+            thread.stepping = false;
+            thread.siginfo = None;
+        }
+    }
+
+    ///
+    /// Records the signal a thread has stopped with, to be delivered when it
+    /// is resumed, and reads the details of it.
+    ///
+    fn signal_stopped(&mut self, tid: i32, sig: Signal) -> SignalInfo {
+        let info = match trace::get_siginfo(tid) {
+            Ok(raw) => SignalInfo::from_raw(sig, &raw),
+            Err(e) => {
+                warn!("failed to read the details of signal {} in thread {}: {}", sig, tid, e);
+                SignalInfo::new(sig)
+            }
+        };
+        if let Some(thread) = self.threads.get_mut(tid) {
+            thread.signal = Some(sig);
+            thread.siginfo = Some(info);
+        }
+        info
+    }
+
+    ///
+    /// Works out whether a thread stopped on entry to a system call or as it
+    /// returns, remembering the call until it does. Stops on entry and exit
+    /// alternate, but a thread may also return from a call it entered before
+    /// its system calls were traced, which is told apart by its registers.
+    ///
+    fn syscall_stopped(&mut self, tid: i32) -> Result<SyscallInfo> {
+        let regs = trace::get_registers(tid).map_err(DebugError::TraceFailure)?;
+        let thread = match self.threads.get_mut(tid) {
+            Some(thread) => thread,
+            None => return Ok(SyscallInfo::from_registers(&regs)),
+        };
+
+        let info = match thread.syscall.take() {
+            Some(entry) => entry.returned(regs.rax as i64),
+            None => SyscallInfo::from_registers(&regs),
+        };
+        if info.is_entry() {
+            thread.syscall = Some(info);
+        }
+        Ok(info)
+    }
+
+    ///
+    /// Deals with a thread stopping on a call trapped by the seccomp filter,
+    /// which it does before entering the call. If the thread has already
+    /// been seen entering the call, because every call is being traced, it
+    /// isn't reported again.
+    ///
+    fn seccomp_stopped(&mut self, tid: i32) -> Result<Option<SyscallInfo>> {
+        if self.threads.get(tid).is_some_and(|thread| thread.syscall.is_some()) {
+            return Ok(None);
+        }
+        self.syscall_stopped(tid).map(Some)
+    }
+
+    fn thread_exited(&mut self, tid: i32) {
+        if let Some(thread) = self.threads.remove(tid) {
+            info!("thread {} ({}) exited", thread.id, tid);
+        }
+        if self.current == tid {
+            self.current = self.threads.tids().first().copied().unwrap_or(self.pid);
+        }
+    }
+
+    ///
+    /// Stops the signal a thread stopped with from being delivered to it
+    /// when it's resumed.
+    ///
+    pub fn discard_signal(&mut self, tid: i32) {
+        if let Some(thread) = self.threads.get_mut(tid) {
+            thread.signal = None;
+        }
+    }
+
+    ///
+    /// The threads of the process.
+    ///
+    pub fn threads(&self) -> &Threads {
+        &self.threads
+    }
+
+    ///
+    /// The thread whose registers are read and written.
+    ///
+    pub fn current_thread(&self) -> &Thread {
+        self.threads.get(self.current)
+            .or_else(|| self.threads.iter().next())
+            .expect("process has no threads")
+    }
+
+    ///
+    /// Selects the thread whose registers are read and written, by the
+    /// identifier shown to the user.
+    ///
+    pub fn select_thread(&mut self, id: u32) -> Result<&Thread> {
+        let thread = self.threads.by_id(id).ok_or(DebugError::InvalidOperation(Reason::NoSuchThread))?;
+        self.current = thread.tid;
+        Ok(thread)
+    }
+
+    ///
+    /// The thread memory is accessed through, which has to be stopped.
+    /// That's the current thread, unless it is running in non-stop mode.
+    ///
+    fn memory_tid(&self) -> i32 {
+        match self.threads.get(self.current) {
+            Some(thread) if !thread.stopped => {
+                self.threads.iter().find(|thread| thread.stopped).map_or(self.current, |thread| thread.tid)
+            }
+            _ => self.current,
+        }
+    }
+
+    ///
+    /// Reads the general purpose registers of the current thread.
+    ///
+    pub fn registers(&self) -> Result<Registers> {
+        self.current_thread().registers()
+    }
+
+    ///
+    /// Forgets about the breakpoint sites in a range of memory that has
+    /// been unmapped from the process.
+    ///
+    pub fn forget_sites(&mut self, start: u64, end: u64) {
+        self.sites.forget(start, end)
+    }
+
+    ///
+    /// Removes every breakpoint site from the process, for example before
+    /// detaching. The addresses of any sites that could not be removed are
+    /// returned along with the reason.
+    ///
+    pub fn remove_all_sites(&mut self) -> Vec<(u64, DebugError)> {
+        self.sites.remove_all(self.memory_tid())
+    }
+
+    ///
+    /// Reads the program counter of the current thread.
     ///
-    pub fn read(&self, addr: u64) -> Result<i64> {
-        trace::read_text(self.pid, addr).map_err(DebugError::TraceFailure)
+    pub fn pc(&self) -> Result<u64> {
+        self.current_thread().pc()
     }
 
     ///
-    /// Writes a single word to the given address in the process memory.
+    /// Moves the program counter of the current thread to the given address.
     ///
-    pub fn write(&self, addr: u64, data: u64) -> Result<()> {
-        trace::write_text(self.pid, addr, data).map_err(DebugError::TraceFailure)
+    pub fn set_pc(&mut self, addr: u64) -> Result<()> {
+        self.current_thread().set_pc(addr)
     }
 
-    fn do_start<F>(path: PathBuf, args: Vec<String>, env: Option<Vec<String>>, pre_exec: F) -> Result<Process>
-        where F: Fn() -> i64 {
+    ///
+    /// After a trap, the program counter sits just past the breakpoint
+    /// instruction. If that's one of our sites, wind it back so that the
+    /// thread is left at the breakpoint address, exactly as if the
+    /// breakpoint had never been there. Returns the address of the site.
+    ///
+    /// Only a trap raised by the breakpoint instruction itself counts, not
+    /// e.g. a SIGTRAP sent to a thread which happens to be just past a site.
+    ///
+    fn rewind_breakpoint(&mut self, tid: i32, sig: Signal) -> Result<Option<u64>> {
+        let thread = match self.threads.get(tid) {
+            Some(thread) if sig == Signal::SIGTRAP => thread,
+            _ => return Ok(None),
+        };
+
+        let addr = thread.pc()?.wrapping_sub(1);
+        if self.sites.get(addr).is_none() {
+            return Ok(None);
+        }
+        let code = trace::get_siginfo(tid).map_err(DebugError::TraceFailure)?.si_code;
+        if code != signals::SI_KERNEL && code != signals::TRAP_BRKPT {
+            return Ok(None);
+        }
+
+        thread.set_pc(addr)?;
+        Ok(Some(addr))
+    }
+
+    ///
+    /// Single steps the original instruction at a breakpoint site, if the
+    /// thread is stopped at one. If the step ends in anything other than the
+    /// expected trap (e.g. the process exits), that status is returned.
+    ///
+    /// Only the stepping thread runs, so no other thread can slip past the
+    /// site while it is suspended.
+    ///
+    fn step_over_site(&mut self, tid: i32) -> Result<Option<WaitStatus>> {
+        let pc = match self.threads.get(tid) {
+            Some(thread) if thread.stopped => thread.pc()?,
+            _ => return Ok(None),
+        };
+        if self.sites.get(pc).is_none() {
+            return Ok(None);
+        }
+
+        self.sites.suspend(tid, pc)?;
+        if let Some(thread) = self.threads.get_mut(tid) {
+            thread.syscall = None;
+        }
+        trace::step(tid, None).map_err(DebugError::TraceFailure)?;
+        let status = waitpid(Pid::from_raw(tid), Some(WaitPidFlag::__WALL))?;
+
+        match status {
+            WaitStatus::Stopped(_, Signal::SIGTRAP) => {
+                self.sites.resume(tid, pc)?;
+                Ok(None)
+            }
+            WaitStatus::Exited(..) | WaitStatus::Signaled(..) => Ok(Some(status)),
+            _ => {
+                self.sites.resume(tid, pc)?;
+                Ok(Some(status))
+            }
+        }
+    }
+
+    ///
+    /// Writes a single word to the given address in the process memory,
+    /// which needn't be aligned. Breakpoint sites within the word stay
+    /// inserted, unless they're suspended for a vfork child, and the written
+    /// bytes become their original bytes.
+    ///
+    pub fn write(&mut self, addr: u64, data: u64) -> Result<()> {
+        let tid = self.memory_tid();
+        self.sites.write(tid, addr, &data.to_ne_bytes(), self.sites_suspended)
+    }
+
+    ///
+    /// The breakpoint sites inserted into this process.
+    ///
+    pub fn sites(&self) -> &Sites {
+        &self.sites
+    }
+
+    ///
+    /// Inserts a breakpoint instruction at the given address, or takes another
+    /// reference on the site if one already exists there.
+    ///
+    pub fn insert_site(&mut self, addr: u64) -> Result<()> {
+        self.sites.insert(self.memory_tid(), addr)
+    }
+
+    ///
+    /// Releases a reference on the breakpoint site at the given address,
+    /// restoring the original byte once it is no longer used.
+    ///
+    pub fn remove_site(&mut self, addr: u64) -> Result<()> {
+        self.sites.remove(self.memory_tid(), addr)
+    }
+
+    ///
+    /// Forks a child which runs `pre_exec` and then executes the program.
+    /// If either fails, the child sends why down `failed` and exits there
+    /// and then: it's a copy of the whole debugger, and none of that may
+    /// run again in it.
+    ///
+    fn do_start<F>(path: PathBuf, args: Vec<String>, env: Option<Vec<String>>, failed: RawFd, pre_exec: F) -> Result<Process>
+        where F: Fn() -> std::result::Result<(), ChildStep> {
         let pid = unsafe { libc::fork() };
         match pid {
             x if x < 0 => Err(DebugError::Sys(std::io::Error::last_os_error())),
@@ -108,7 +1327,7 @@ impl Process {
                 // parent
                 Ok(Process::new(x))
             }
-            x if x == 0 => {
+            0 => {
                 let path = path.into_os_string().into_string().unwrap();
 
                 let mut args = args;
@@ -124,21 +1343,253 @@ impl Process {
                 };
 
                 // child
-                if pre_exec() < 0 {
-                    panic!("pre-exec routine failed!");
+                if let Err(step) = pre_exec() {
+                    child_failed(failed, step);
                 }
 
                 unsafe {
                     libc::execve(path, args, env);
-                    let error = CString::from_raw(libc::strerror(*errno_location()));
-                    unreachable!("execve failed!: {}", error.into_string().unwrap());
                 }
+                child_failed(failed, ChildStep::Exec)
             }
             _ => unreachable!()
         }
     }
 }
 
+impl Inspect for Process {
+    fn pid(&self) -> i32 {
+        self.pid
+    }
+
+    ///
+    /// Reads a single word from the given address in the process memory.
+    /// Any breakpoint instructions are hidden, and the original bytes are
+    /// returned in their place.
+    ///
+    fn read(&self, addr: u64) -> Result<i64> {
+        let word = trace::read_text(self.memory_tid(), addr).map_err(DebugError::TraceFailure)?;
+        let mut bytes = word.to_ne_bytes();
+        self.sites.unpatch(addr, &mut bytes);
+        Ok(i64::from_ne_bytes(bytes))
+    }
+
+    ///
+    /// Reads an arbitrary number of bytes from the given address in the
+    /// process memory, with breakpoint instructions hidden as in `read`.
+    ///
+    /// Memory is read a whole word at a time, so the words are lined up to
+    /// keep the last of them from running past the end of the mapping the
+    /// bytes are in.
+    ///
+    fn read_memory(&self, addr: u64, len: usize) -> Result<Vec<u8>> {
+        if len == 0 {
+            return Ok(Vec::new());
+        }
+        let word_size = std::mem::size_of::<u64>() as u64;
+        let start = addr & !(word_size - 1);
+        let end = addr.checked_add(len as u64).ok_or(DebugError::InvalidOperation(Reason::NoMemory(addr)))?;
+        let mut bytes = Vec::with_capacity(len + 2 * word_size as usize);
+        let mut current = start;
+        while current < end {
+            let word = trace::read_text(self.memory_tid(), current).map_err(DebugError::TraceFailure)?;
+            bytes.extend_from_slice(&word.to_ne_bytes());
+            current += word_size;
+        }
+        bytes.drain(..(addr - start) as usize);
+        bytes.truncate(len);
+        self.sites.unpatch(addr, &mut bytes);
+        Ok(bytes)
+    }
+
+    fn maps(&self) -> Result<Vec<MemoryMap>> {
+        maps::read(self.pid)
+    }
+
+    fn auxv(&self) -> Result<Vec<(u64, u64)>> {
+        auxv::read(self.pid)
+    }
+
+    fn thread_ids(&self) -> Vec<(u32, i32)> {
+        self.threads.iter().map(|thread| (thread.id, thread.tid)).collect()
+    }
+
+    fn current_tid(&self) -> i32 {
+        self.current_thread().tid
+    }
+
+    fn switch_thread(&mut self, id: u32) -> Result<i32> {
+        self.select_thread(id).map(|thread| thread.tid)
+    }
+
+    fn thread_status(&self, tid: i32) -> Option<&'static str> {
+        match self.threads.get(tid) {
+            Some(thread) if thread.stopped => None,
+            Some(thread) if thread.group_stopped => Some("(stopped by job control)"),
+            _ => Some("(running)"),
+        }
+    }
+
+    fn thread_registers(&self, tid: i32) -> Result<Registers> {
+        let thread = self.threads.get(tid).ok_or(DebugError::InvalidOperation(Reason::NoSuchThread))?;
+        thread.registers()
+    }
+
+    fn thread_fp_registers(&self, tid: i32) -> Result<FpRegisters> {
+        trace::get_fp_registers(tid).map_err(DebugError::TraceFailure)
+    }
+
+    fn thread_siginfo(&self, tid: i32) -> Option<SignalInfo> {
+        self.threads.get(tid)?.siginfo
+    }
+}
+
+///
+/// Whether the given process is running without address space randomization.
+///
+fn randomization_disabled(pid: i32) -> bool {
+    std::fs::read_to_string(format!("/proc/{}/personality", pid)).ok()
+        .and_then(|persona| u64::from_str_radix(persona.trim(), 16).ok())
+        .is_some_and(|persona| persona & Persona::ADDR_NO_RANDOMIZE.bits() as u64 != 0)
+}
+
+///
+/// Whether the given id is a thread of the given process, rather than
+/// another process altogether.
+///
+fn is_thread(pid: i32, tid: i32) -> bool {
+    Path::new(&format!("/proc/{}/task/{}", pid, tid)).exists()
+}
+
+///
+/// Lists the threads of a process, main thread first.
+///
+fn task_ids(pid: i32) -> Result<Vec<i32>> {
+    let mut tids: Vec<i32> = fs::read_dir(format!("/proc/{}/task", pid)).map_err(DebugError::Sys)?
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+        .collect();
+    tids.sort_unstable_by_key(|tid| (*tid != pid, *tid));
+    Ok(tids)
+}
+
+///
+/// Whether the given signal stops the whole process under job control,
+/// unless it's handled.
+///
+fn is_stop_signal(sig: Signal) -> bool {
+    matches!(sig, Signal::SIGSTOP | Signal::SIGTSTP | Signal::SIGTTIN | Signal::SIGTTOU)
+}
+
+///
+/// The events we have the kernel report for every traced thread.
+///
+fn trace_options() -> Options {
+    Options::PTRACE_O_TRACECLONE | Options::PTRACE_O_TRACEFORK | Options::PTRACE_O_TRACEVFORK
+        | Options::PTRACE_O_TRACEVFORKDONE | Options::PTRACE_O_TRACEEXEC | Options::PTRACE_O_TRACESYSGOOD
+        | Options::PTRACE_O_TRACESECCOMP
+}
+
+///
+/// What a newly forked child was doing when it failed to become the
+/// program.
+///
+#[derive(Clone, Copy)]
+enum ChildStep {
+    /// Waiting to be released once it was traced.
+    Handshake,
+    Chdir,
+    /// Redirecting the standard stream with the given descriptor.
+    Redirect(i32),
+    /// Setting the resource limit at the given index of those set.
+    Limit(usize),
+    Exec,
+}
+
+impl ChildStep {
+    ///
+    /// The step, as sent down the pipe from the child, along with the
+    /// error it failed with.
+    ///
+    fn encode(self, errno: i32) -> [i32; 3] {
+        match self {
+            ChildStep::Handshake => [0, 0, errno],
+            ChildStep::Chdir => [1, 0, errno],
+            ChildStep::Redirect(fd) => [2, fd, errno],
+            ChildStep::Limit(idx) => [3, idx as i32, errno],
+            ChildStep::Exec => [4, 0, errno],
+        }
+    }
+
+    fn decode(message: [i32; 3]) -> Option<(Self, i32)> {
+        let step = match message[0] {
+            0 => ChildStep::Handshake,
+            1 => ChildStep::Chdir,
+            2 => ChildStep::Redirect(message[1]),
+            3 => ChildStep::Limit(message[1] as usize),
+            4 => ChildStep::Exec,
+            _ => return None,
+        };
+        Some((step, message[2]))
+    }
+
+    ///
+    /// Describes the failure, e.g. `chdir to /tmp/x: No such file or
+    /// directory`, in terms of how the program was being launched.
+    ///
+    fn error(self, path: &Path, launch: &Launch, errno: i32) -> DebugError {
+        let kind = io::Error::from_raw_os_error(errno).kind();
+        let streams = [(&launch.stdin, "stdin"), (&launch.stdout, "stdout"), (&launch.stderr, "stderr")];
+        let action = match self {
+            ChildStep::Handshake => String::from("waiting to be traced"),
+            ChildStep::Chdir => {
+                let cwd = launch.cwd.as_deref().unwrap_or_else(|| Path::new(""));
+                format!("chdir to {}", cwd.display())
+            }
+            ChildStep::Redirect(fd) => match streams.get(fd as usize) {
+                Some((Some(redirect), name)) => format!("cannot redirect {} to {}", name, redirect.path.display()),
+                _ => format!("cannot redirect descriptor {}", fd),
+            },
+            ChildStep::Limit(idx) => match launch.limits.keys().nth(idx) {
+                Some(limit) => format!("cannot set the {} limit", limit),
+                None => String::from("cannot set a resource limit"),
+            },
+            ChildStep::Exec => format!("cannot execute {}", path.display()),
+        };
+        DebugError::Sys(io::Error::new(kind, format!("{}: {}", action, Errno::from_i32(errno).desc())))
+    }
+}
+
+///
+/// Sends the step a newly forked child failed at back to the parent, and
+/// exits straight away, without unwinding or running any exit handlers:
+/// the child is a copy of the whole debugger, none of which may run in it.
+///
+fn child_failed(failed: RawFd, step: ChildStep) -> ! {
+    let errno = io::Error::last_os_error().raw_os_error().unwrap_or_default();
+    let message = step.encode(errno);
+    unsafe {
+        libc::write(failed, message.as_ptr() as *const libc::c_void, std::mem::size_of_val(&message));
+        libc::_exit(127);
+    }
+}
+
+///
+/// Reads why a newly forked child failed to become the program, if it
+/// said, once it has exited.
+///
+fn read_failure(failures: RawFd) -> Option<(ChildStep, i32)> {
+    let mut message = [0u8; 12];
+    if read(failures, &mut message) != Ok(message.len()) {
+        return None;
+    }
+    let word = |idx: usize| {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(&message[idx * 4..idx * 4 + 4]);
+        i32::from_ne_bytes(bytes)
+    };
+    ChildStep::decode([word(0), word(1), word(2)])
+}
+
 ///
 /// Helper method for converting a `String` into a raw c-style string
 /// pointer.
@@ -168,4 +1619,34 @@ fn string_vec_to_ptr(v: Vec<String>) -> *const *const c_char {
     // exec'ing in the new process.
     std::mem::forget(strings);
     p
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{last_page, Child};
+
+    #[test]
+    fn read_to_end_of_mapping() {
+        let page = last_page();
+        let child = Child::fork();
+        let process = Process::new(child.0);
+
+        let bytes = process.read_memory(page + 4093, 3).unwrap();
+        assert_eq!(bytes, [0xfd, 0xfe, 0xff]);
+        let bytes = process.read_memory(page + 4080, 16).unwrap();
+        assert_eq!(bytes, (0xf0..=0xff).collect::<Vec<u8>>());
+        assert!(process.read_memory(page + 4095, 2).is_err());
+        assert!(process.read_memory(page + 4095, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn read_hides_sites() {
+        let page = last_page();
+        let child = Child::fork();
+        let mut process = Process::new(child.0);
+
+        process.insert_site(page + 4094).unwrap();
+        let bytes = process.read_memory(page + 4090, 6).unwrap();
+        assert_eq!(bytes, [0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xff]);
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+
+use crate::error::{DebugError, Result};
+use crate::process::Stop;
+use crate::signals::SignalInfo;
+use crate::syscalls::SyscallInfo;
+use crate::trace;
+use crate::trace::{Registers, Signal};
+
+///
+/// A single thread of the traced process.
+///
+pub struct Thread {
+    /// The identifier shown to the user, handed out in the order threads
+    /// were first seen.
+    pub id: u32,
+    /// The kernel's identifier for the thread.
+    pub tid: i32,
+    /// Whether the thread is currently stopped under the tracer.
+    pub stopped: bool,
+    /// Whether the thread is single stepping an instruction.
+    pub(crate) stepping: bool,
+    /// We've interrupted the thread, which it hasn't reported yet, because
+    /// it stopped for some other reason first.
+    pub(crate) stop_expected: bool,
+    /// Whether the thread is in a group-stop, i.e. the process was stopped
+    /// by a job control signal, as with Ctrl-Z or `kill -STOP`. Rather than
+    /// running when resumed, it stays stopped until the process is continued.
+    pub group_stopped: bool,
+    /// Why the thread stopped while every thread was being stopped, which
+    /// is yet to be reported.
+    pub(crate) pending: Option<Stop>,
+    /// The signal the thread stopped with, which is delivered to it when
+    /// it's next resumed, unless it is discarded first.
+    pub(crate) signal: Option<Signal>,
+    /// The details of the signal the thread last stopped with, for as
+    /// long as it stays stopped.
+    pub siginfo: Option<SignalInfo>,
+    /// The system call the thread stopped on entry to, until it stops again
+    /// as the call returns.
+    pub(crate) syscall: Option<SyscallInfo>,
+}
+
+impl Thread {
+    ///
+    /// Reads the general purpose registers of the thread.
+    ///
+    pub fn registers(&self) -> Result<Registers> {
+        trace::get_registers(self.tid).map_err(DebugError::TraceFailure)
+    }
+
+    ///
+    /// Reads the program counter of the thread.
+    ///
+    pub fn pc(&self) -> Result<u64> {
+        Ok(self.registers()?.rip)
+    }
+
+    ///
+    /// Moves the program counter of the thread to the given address.
+    ///
+    pub fn set_pc(&self, addr: u64) -> Result<()> {
+        let mut regs = self.registers()?;
+        regs.rip = addr;
+        trace::set_registers(self.tid, regs).map_err(DebugError::TraceFailure)
+    }
+
+    ///
+    /// Interrupts the thread, which reports a `PTRACE_EVENT_STOP` once it
+    /// gets to it. Unlike a SIGSTOP, this doesn't touch the job control
+    /// state of the process.
+    ///
+    pub(crate) fn interrupt(&self) -> Result<()> {
+        trace::interrupt(self.tid).map_err(DebugError::TraceFailure)
+    }
+}
+
+///
+/// The table of every thread in the traced process, keyed by their
+/// kernel identifiers.
+///
+#[derive(Default)]
+pub struct Threads {
+    last_id: u32,
+    entries: BTreeMap<i32, Thread>,
+}
+
+impl Threads {
+    ///
+    /// Adds a newly created thread, which starts off stopped. Returns its
+    /// newly assigned identifier.
+    ///
+    pub fn add(&mut self, tid: i32) -> u32 {
+        self.last_id += 1;
+        self.entries.insert(tid, Thread {
+            id: self.last_id,
+            tid,
+            stopped: true,
+            stepping: false,
+            stop_expected: false,
+            group_stopped: false,
+            pending: None,
+            signal: None,
+            siginfo: None,
+            syscall: None,
+        });
+        self.last_id
+    }
+
+    pub fn remove(&mut self, tid: i32) -> Option<Thread> {
+        self.entries.remove(&tid)
+    }
+
+    pub fn get(&self, tid: i32) -> Option<&Thread> {
+        self.entries.get(&tid)
+    }
+
+    pub fn get_mut(&mut self, tid: i32) -> Option<&mut Thread> {
+        self.entries.get_mut(&tid)
+    }
+
+    ///
+    /// Finds a thread by the identifier shown to the user.
+    ///
+    pub fn by_id(&self, id: u32) -> Option<&Thread> {
+        self.entries.values().find(|thread| thread.id == id)
+    }
+
+    ///
+    /// Iterates over every thread, in order of kernel identifier.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item=&Thread> {
+        self.entries.values()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item=&mut Thread> {
+        self.entries.values_mut()
+    }
+
+    ///
+    /// The kernel identifiers of every thread.
+    ///
+    pub fn tids(&self) -> Vec<i32> {
+        self.entries.keys().copied().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
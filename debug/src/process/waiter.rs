@@ -0,0 +1,81 @@
+use std::mem;
+use std::os::unix::io::RawFd;
+use std::sync::mpsc::{channel, Sender};
+use std::sync::Mutex;
+use std::thread;
+
+use nix::fcntl::{fcntl, FcntlArg, OFlag};
+use nix::sys::signal::SigSet;
+use nix::unistd::{pipe2, read, write};
+
+use crate::error::Result;
+use crate::interrupt;
+
+/// What the waiting thread writes once there may be a status to collect.
+const WAKE_STATUS: u8 = b's';
+
+/// The one waiting thread, started the first time it's needed.
+static WAITER: Mutex<Option<Waiter>> = Mutex::new(None);
+
+///
+/// Watches for the traced processes to report something on a thread of
+/// its own, so that the thread tracing them can sleep until either they
+/// do, or it's asked to interrupt them, whichever comes first. The status
+/// is only looked at, and left for the tracing thread to collect, since
+/// only it can go on to make ptrace requests of the process.
+///
+struct Waiter {
+    /// Asks the thread to look out for the next status.
+    watches: Sender<()>,
+    /// Where the thread and interrupt requests wake us up.
+    wake: RawFd,
+    /// Whether the thread is still looking out for a status, which it
+    /// hasn't woken us up for yet.
+    watching: bool,
+}
+
+impl Waiter {
+    fn start() -> Result<Self> {
+        let (wake, woken) = pipe2(OFlag::O_CLOEXEC)?;
+        // a request comes from a signal handler, which mustn't block
+        fcntl(woken, FcntlArg::F_SETFL(OFlag::O_NONBLOCK))?;
+        let (watches, watched) = channel::<()>();
+        thread::spawn(move || {
+            // signals are left to the other threads, which are the ones
+            // that are interrupted by them
+            let _ = SigSet::all().thread_block();
+            while watched.recv().is_ok() {
+                let mut info: libc::siginfo_t = unsafe { mem::zeroed() };
+                let flags = libc::WEXITED | libc::WSTOPPED | libc::WNOWAIT | libc::__WALL;
+                while unsafe { libc::waitid(libc::P_ALL, 0, &mut info, flags) } < 0
+                    && nix::errno::errno() == libc::EINTR {}
+                let _ = write(woken, &[WAKE_STATUS]);
+            }
+        });
+        interrupt::wake_with(woken);
+        Ok(Self { watches, wake, watching: false })
+    }
+}
+
+///
+/// Sleeps until any of the traced processes may have something to report,
+/// or we're asked to interrupt them. Either may be a false alarm, which
+/// the caller finds out by checking for itself.
+///
+pub(crate) fn sleep() -> Result<()> {
+    let mut waiter = WAITER.lock().unwrap_or_else(|e| e.into_inner());
+    if waiter.is_none() {
+        *waiter = Some(Waiter::start()?);
+    }
+    let waiter = waiter.as_mut().unwrap();
+    if !waiter.watching && waiter.watches.send(()).is_ok() {
+        waiter.watching = true;
+    }
+
+    let mut byte = [0u8];
+    read(waiter.wake, &mut byte)?;
+    if byte[0] == WAKE_STATUS {
+        waiter.watching = false;
+    }
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use crate::trace::{FpRegisters, Registers};
+
+///
+/// The names of the general purpose registers, in the order they are
+/// usually displayed.
+///
+pub const NAMES: &[&str] = &[
+    "rax", "rbx", "rcx", "rdx", "rsi", "rdi", "rbp", "rsp",
+    "r8", "r9", "r10", "r11", "r12", "r13", "r14", "r15",
+    "rip", "eflags", "cs", "ss", "ds", "es", "fs", "gs",
+    "fs_base", "gs_base", "orig_rax",
+];
+
+///
+/// Whether there is a general purpose register with the given name.
+///
+pub fn exists(name: &str) -> bool {
+    name == "pc" || NAMES.contains(&name)
+}
+
+///
+/// Looks up a general purpose register by name, returning `None` if there
+/// is no such register.
+///
+pub fn get(regs: &Registers, name: &str) -> Option<u64> {
+    let value = match name {
+        "rax" => regs.rax,
+        "rbx" => regs.rbx,
+        "rcx" => regs.rcx,
+        "rdx" => regs.rdx,
+        "rsi" => regs.rsi,
+        "rdi" => regs.rdi,
+        "rbp" => regs.rbp,
+        "rsp" => regs.rsp,
+        "r8" => regs.r8,
+        "r9" => regs.r9,
+        "r10" => regs.r10,
+        "r11" => regs.r11,
+        "r12" => regs.r12,
+        "r13" => regs.r13,
+        "r14" => regs.r14,
+        "r15" => regs.r15,
+        "rip" | "pc" => regs.rip,
+        "eflags" => regs.eflags,
+        "cs" => regs.cs,
+        "ss" => regs.ss,
+        "ds" => regs.ds,
+        "es" => regs.es,
+        "fs" => regs.fs,
+        "gs" => regs.gs,
+        "fs_base" => regs.fs_base,
+        "gs_base" => regs.gs_base,
+        "orig_rax" => regs.orig_rax,
+        _ => return None,
+    };
+    Some(value)
+}
+
+///
+/// Looks up a floating point or SSE register by name: the x87 stack
+/// (`st0`-`st7`) and its control and status words, or the SSE registers
+/// (`xmm0`-`xmm15`) and `mxcsr`. The x87 registers are 80 bits wide, and
+/// the SSE ones 128. Returns `None` if there is no such register.
+///
+pub fn get_fp(regs: &FpRegisters, name: &str) -> Option<u128> {
+    // each register is stored as 32 bit lanes, least significant first
+    let lanes = |space: &[u32], idx: usize| {
+        space[idx * 4..idx * 4 + 4].iter().rev().fold(0u128, |value, lane| value << 32 | *lane as u128)
+    };
+    let index = |prefix: &str, count: usize| {
+        name.strip_prefix(prefix)?.parse::<usize>().ok().filter(|idx| *idx < count)
+    };
+
+    let value = match name {
+        "fctrl" => regs.cwd as u128,
+        "fstat" => regs.swd as u128,
+        "ftag" => regs.ftw as u128,
+        "fop" => regs.fop as u128,
+        "mxcsr" => regs.mxcsr as u128,
+        _ => match (index("st", 8), index("xmm", 16)) {
+            (Some(idx), _) => lanes(&regs.st_space, idx) & ((1 << 80) - 1),
+            (_, Some(idx)) => lanes(&regs.xmm_space, idx),
+            _ => return None,
+        },
+    };
+    Some(value)
+}
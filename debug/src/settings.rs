@@ -0,0 +1,108 @@
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+///
+/// Settings which change how the debugger behaves, as changed with the
+/// `set` command. They carry over from one process to the next.
+///
+pub struct Settings {
+    /// Only stop the thread which hit an event, leaving the others running,
+    /// rather than stopping every thread together.
+    pub non_stop: bool,
+    /// Which other threads may run while the current one is resumed.
+    pub scheduler_locking: SchedulerLocking,
+    /// Which process to keep debugging when the process forks.
+    pub follow_fork_mode: FollowForkMode,
+    /// Whether to detach from the process that isn't followed after a fork,
+    /// rather than holding it stopped.
+    pub detach_on_fork: bool,
+    /// Whether the processes we launched are killed when the debugger
+    /// exits, rather than detached from and left to run. Processes we
+    /// attached to are always detached from.
+    pub kill_on_exit: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            non_stop: false,
+            scheduler_locking: SchedulerLocking::default(),
+            follow_fork_mode: FollowForkMode::default(),
+            detach_on_fork: true,
+            kill_on_exit: true,
+        }
+    }
+}
+
+///
+/// Whether other threads are kept stopped while the current thread runs,
+/// in all-stop mode.
+///
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum SchedulerLocking {
+    /// Every thread runs whenever the process is resumed.
+    #[default]
+    Off,
+    /// Only the current thread runs while it is being stepped, but every
+    /// thread runs when the process is continued.
+    Step,
+    /// Only the current thread ever runs.
+    On,
+}
+
+impl FromStr for SchedulerLocking {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(SchedulerLocking::Off),
+            "step" => Ok(SchedulerLocking::Step),
+            "on" => Ok(SchedulerLocking::On),
+            _ => Err(format!("expected `on`, `step` or `off`, found {}", s)),
+        }
+    }
+}
+
+impl Display for SchedulerLocking {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SchedulerLocking::Off => write!(f, "off"),
+            SchedulerLocking::Step => write!(f, "step"),
+            SchedulerLocking::On => write!(f, "on"),
+        }
+    }
+}
+
+///
+/// Which process the debugger follows when the process forks.
+///
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum FollowForkMode {
+    /// Keep debugging the process that called fork.
+    #[default]
+    Parent,
+    /// Switch to debugging the newly forked child.
+    Child,
+}
+
+impl FromStr for FollowForkMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "parent" => Ok(FollowForkMode::Parent),
+            "child" => Ok(FollowForkMode::Child),
+            _ => Err(format!("expected `parent` or `child`, found {}", s)),
+        }
+    }
+}
+
+impl Display for FollowForkMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FollowForkMode::Parent => write!(f, "parent"),
+            FollowForkMode::Child => write!(f, "child"),
+        }
+    }
+}
@@ -0,0 +1,362 @@
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use crate::trace::SigInfo;
+
+pub use crate::trace::Signal;
+
+///
+/// What the debugger does when the process receives a signal.
+///
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SignalPolicy {
+    /// Stop the process and return to the prompt.
+    pub stop: bool,
+    /// Say that the signal was received, even if the process isn't stopped.
+    pub print: bool,
+    /// Deliver the signal to the process when it is resumed, rather than
+    /// discarding it.
+    pub pass: bool,
+}
+
+impl SignalPolicy {
+    const DEFAULT: Self = Self { stop: true, print: true, pass: true };
+    /// For signals programs use routinely, which would get in the way if
+    /// the debugger stopped for them.
+    const QUIET: Self = Self { stop: false, print: false, pass: true };
+    /// For signals the debugger itself uses, or which are normally meant
+    /// for the debugger rather than the program.
+    const DEBUGGER: Self = Self { stop: true, print: true, pass: false };
+
+    ///
+    /// Changes the policy as the `handle` command asks. Stopping implies
+    /// printing, so turning one on or the other off changes both.
+    ///
+    pub fn apply(&mut self, action: SignalAction) {
+        match action {
+            SignalAction::Stop => {
+                self.stop = true;
+                self.print = true;
+            }
+            SignalAction::NoStop => self.stop = false,
+            SignalAction::Print => self.print = true,
+            SignalAction::NoPrint => {
+                self.print = false;
+                self.stop = false;
+            }
+            SignalAction::Pass => self.pass = true,
+            SignalAction::NoPass => self.pass = false,
+        }
+    }
+}
+
+///
+/// A change to the policy for a signal, as given to the `handle` command.
+///
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SignalAction {
+    Stop,
+    NoStop,
+    Print,
+    NoPrint,
+    Pass,
+    NoPass,
+}
+
+impl FromStr for SignalAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stop" => Ok(SignalAction::Stop),
+            "nostop" => Ok(SignalAction::NoStop),
+            "print" => Ok(SignalAction::Print),
+            "noprint" => Ok(SignalAction::NoPrint),
+            "pass" | "noignore" => Ok(SignalAction::Pass),
+            "nopass" | "ignore" => Ok(SignalAction::NoPass),
+            _ => Err(format!("expected one of stop, nostop, print, noprint, pass or nopass, found {}", s)),
+        }
+    }
+}
+
+///
+/// The policy for every signal. Signals start off with the same defaults
+/// as gdb: stopping for most of them, while the ones programs use routinely
+/// (e.g. SIGCHLD, SIGALRM) are passed on silently. Unlike gdb, so are the
+/// job control signals, so that the process can be stopped and continued
+/// from the shell just as it would be without us.
+///
+pub struct Signals {
+    /// Keyed by signal number, so that they're listed in the usual order.
+    policies: BTreeMap<i32, (Signal, SignalPolicy)>,
+}
+
+impl Default for Signals {
+    fn default() -> Self {
+        let policies = Signal::iterator().map(|sig| {
+            let policy = match sig {
+                Signal::SIGALRM | Signal::SIGURG | Signal::SIGCHLD | Signal::SIGWINCH | Signal::SIGIO
+                | Signal::SIGVTALRM | Signal::SIGPROF => SignalPolicy::QUIET,
+                Signal::SIGSTOP | Signal::SIGTSTP | Signal::SIGTTIN | Signal::SIGTTOU
+                | Signal::SIGCONT => SignalPolicy::QUIET,
+                Signal::SIGINT | Signal::SIGTRAP => SignalPolicy::DEBUGGER,
+                _ => SignalPolicy::DEFAULT,
+            };
+            (sig as i32, (sig, policy))
+        }).collect();
+        Self { policies }
+    }
+}
+
+impl Signals {
+    pub fn get(&self, sig: Signal) -> SignalPolicy {
+        self.policies.get(&(sig as i32)).map_or(SignalPolicy::DEFAULT, |(_, policy)| *policy)
+    }
+
+    pub fn get_mut(&mut self, sig: Signal) -> &mut SignalPolicy {
+        &mut self.policies.entry(sig as i32).or_insert((sig, SignalPolicy::DEFAULT)).1
+    }
+
+    ///
+    /// Iterates over the policy of every signal, in signal number order.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item=(Signal, SignalPolicy)> + '_ {
+        self.policies.values().copied()
+    }
+}
+
+impl Display for SignalPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let yes_no = |b: bool| if b { "Yes" } else { "No" };
+        write!(f, "{:<6} {:<8} {}", yes_no(self.stop), yes_no(self.print), yes_no(self.pass))
+    }
+}
+
+///
+/// Parses a signal given by the user, by its name with or without the
+/// `SIG` prefix (`SIGINT`, `int`), or by its number.
+///
+pub fn parse_signal(s: &str) -> Result<Signal, String> {
+    if let Ok(n) = s.parse::<i32>() {
+        return Signal::try_from(n).map_err(|_| format!("invalid signal number: {}", s));
+    }
+
+    let name = s.to_uppercase();
+    let name = if name.starts_with("SIG") { name } else { format!("SIG{}", name) };
+    name.parse::<Signal>().map_err(|_| format!("unknown signal: {}", s))
+}
+
+///
+/// The details of a signal the process received, from `siginfo_t`: why it
+/// was raised, and where from.
+///
+#[derive(Clone, Copy)]
+pub struct SignalInfo {
+    pub signal: Signal,
+    pub errno: i32,
+    /// Why the signal was raised, which depends on the signal, e.g. whether
+    /// a SIGSEGV was for an unmapped address or a protection fault.
+    pub code: i32,
+    /// The address that caused a fault, for signals raised by the hardware.
+    pub addr: Option<u64>,
+    /// The pid and user of the process that sent the signal, or of the
+    /// child a SIGCHLD is about.
+    pub sender: Option<(i32, u32)>,
+    /// The exit status or signal of the child a SIGCHLD is about.
+    pub status: Option<i32>,
+}
+
+/// `si_code` values shared by every signal, for signals sent by a process.
+const SI_USER: i32 = 0;
+pub(crate) const SI_KERNEL: i32 = 0x80;
+const SI_QUEUE: i32 = -1;
+const SI_TIMER: i32 = -2;
+const SI_MESGQ: i32 = -3;
+const SI_ASYNCIO: i32 = -4;
+const SI_SIGIO: i32 = -5;
+const SI_TKILL: i32 = -6;
+/// The `si_code` of a SIGTRAP raised by a breakpoint instruction, which
+/// some kernels report as `SI_KERNEL` instead.
+pub(crate) const TRAP_BRKPT: i32 = 1;
+
+impl SignalInfo {
+    ///
+    /// Decodes the raw `siginfo_t` read from a thread stopped with `signal`.
+    ///
+    pub fn from_raw(signal: Signal, raw: &SigInfo) -> Self {
+        let mut info = Self::new(signal);
+        info.errno = raw.si_errno;
+        info.code = raw.si_code;
+
+        // which parts of the union are valid depends on the signal and the
+        // code, with anything sent by another process carrying its pid.
+        match signal {
+            Signal::SIGSEGV | Signal::SIGBUS | Signal::SIGFPE | Signal::SIGILL | Signal::SIGTRAP if info.code > 0 => {
+                info.addr = Some(unsafe { raw.si_addr() } as u64);
+            }
+            Signal::SIGCHLD if info.code > 0 => {
+                info.sender = Some(unsafe { (raw.si_pid(), raw.si_uid()) });
+                info.status = Some(unsafe { raw.si_status() });
+            }
+            _ if info.code <= 0 => {
+                info.sender = Some(unsafe { (raw.si_pid(), raw.si_uid()) });
+            }
+            _ => {}
+        }
+        info
+    }
+
+    ///
+    /// The details of a signal where nothing is known beyond the signal.
+    ///
+    pub fn new(signal: Signal) -> Self {
+        Self {
+            signal,
+            errno: 0,
+            code: SI_USER,
+            addr: None,
+            sender: None,
+            status: None,
+        }
+    }
+
+    ///
+    /// The name of the signal's code, along with what it means.
+    ///
+    pub fn code_name(&self) -> Option<(&'static str, &'static str)> {
+        let name = match (self.signal, self.code) {
+            (_, SI_USER) => ("SI_USER", "sent by kill"),
+            (_, SI_KERNEL) => ("SI_KERNEL", "sent by the kernel"),
+            (_, SI_QUEUE) => ("SI_QUEUE", "sent by sigqueue"),
+            (_, SI_TIMER) => ("SI_TIMER", "POSIX timer expired"),
+            (_, SI_MESGQ) => ("SI_MESGQ", "POSIX message queue state changed"),
+            (_, SI_ASYNCIO) => ("SI_ASYNCIO", "AIO completed"),
+            (_, SI_SIGIO) => ("SI_SIGIO", "queued SIGIO"),
+            (_, SI_TKILL) => ("SI_TKILL", "sent by tkill"),
+            (Signal::SIGSEGV, 1) => ("SEGV_MAPERR", "address not mapped to object"),
+            (Signal::SIGSEGV, 2) => ("SEGV_ACCERR", "invalid permissions for mapped object"),
+            (Signal::SIGSEGV, 3) => ("SEGV_BNDERR", "failed address bound checks"),
+            (Signal::SIGSEGV, 4) => ("SEGV_PKUERR", "access denied by memory protection keys"),
+            (Signal::SIGBUS, 1) => ("BUS_ADRALN", "invalid address alignment"),
+            (Signal::SIGBUS, 2) => ("BUS_ADRERR", "nonexistent physical address"),
+            (Signal::SIGBUS, 3) => ("BUS_OBJERR", "object specific hardware error"),
+            (Signal::SIGBUS, 4) => ("BUS_MCEERR_AR", "hardware memory error consumed on a machine check"),
+            (Signal::SIGBUS, 5) => ("BUS_MCEERR_AO", "hardware memory error detected in the process"),
+            (Signal::SIGFPE, 1) => ("FPE_INTDIV", "integer divide by zero"),
+            (Signal::SIGFPE, 2) => ("FPE_INTOVF", "integer overflow"),
+            (Signal::SIGFPE, 3) => ("FPE_FLTDIV", "floating point divide by zero"),
+            (Signal::SIGFPE, 4) => ("FPE_FLTOVF", "floating point overflow"),
+            (Signal::SIGFPE, 5) => ("FPE_FLTUND", "floating point underflow"),
+            (Signal::SIGFPE, 6) => ("FPE_FLTRES", "floating point inexact result"),
+            (Signal::SIGFPE, 7) => ("FPE_FLTINV", "floating point invalid operation"),
+            (Signal::SIGFPE, 8) => ("FPE_FLTSUB", "subscript out of range"),
+            (Signal::SIGILL, 1) => ("ILL_ILLOPC", "illegal opcode"),
+            (Signal::SIGILL, 2) => ("ILL_ILLOPN", "illegal operand"),
+            (Signal::SIGILL, 3) => ("ILL_ILLADR", "illegal addressing mode"),
+            (Signal::SIGILL, 4) => ("ILL_ILLTRP", "illegal trap"),
+            (Signal::SIGILL, 5) => ("ILL_PRVOPC", "privileged opcode"),
+            (Signal::SIGILL, 6) => ("ILL_PRVREG", "privileged register"),
+            (Signal::SIGILL, 7) => ("ILL_COPROC", "coprocessor error"),
+            (Signal::SIGILL, 8) => ("ILL_BADSTK", "internal stack error"),
+            (Signal::SIGTRAP, TRAP_BRKPT) => ("TRAP_BRKPT", "process breakpoint"),
+            (Signal::SIGTRAP, 2) => ("TRAP_TRACE", "process trace trap"),
+            (Signal::SIGTRAP, 3) => ("TRAP_BRANCH", "process taken branch trap"),
+            (Signal::SIGTRAP, 4) => ("TRAP_HWBKPT", "hardware breakpoint or watchpoint"),
+            (Signal::SIGCHLD, 1) => ("CLD_EXITED", "child has exited"),
+            (Signal::SIGCHLD, 2) => ("CLD_KILLED", "child was killed"),
+            (Signal::SIGCHLD, 3) => ("CLD_DUMPED", "child terminated abnormally"),
+            (Signal::SIGCHLD, 4) => ("CLD_TRAPPED", "traced child has trapped"),
+            (Signal::SIGCHLD, 5) => ("CLD_STOPPED", "child has stopped"),
+            (Signal::SIGCHLD, 6) => ("CLD_CONTINUED", "stopped child has continued"),
+            _ => return None,
+        };
+        Some(name)
+    }
+
+    ///
+    /// Reads one of the fields of the `siginfo_t`, by its C name, as far as
+    /// they're valid for this signal.
+    ///
+    pub fn field(&self, name: &str) -> Option<u64> {
+        match name {
+            "si_signo" => Some(self.signal as i32 as u64),
+            "si_errno" => Some(self.errno as u64),
+            "si_code" => Some(self.code as u64),
+            "si_addr" => self.addr,
+            "si_pid" => self.sender.map(|(pid, _)| pid as u64),
+            "si_uid" => self.sender.map(|(_, uid)| uid as u64),
+            "si_status" => self.status.map(|status| status as u64),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Describes everything known about the signal, a line at a time.
+    ///
+    pub fn details(&self) -> Vec<String> {
+        let mut lines = vec![format!("signal:  {} ({})", self.signal, self.signal as i32)];
+        match self.code_name() {
+            Some((name, meaning)) => lines.push(format!("code:    {} ({}), {}", name, self.code, meaning)),
+            None => lines.push(format!("code:    {}", self.code)),
+        }
+        if self.errno != 0 {
+            lines.push(format!("errno:   {}", self.errno));
+        }
+        if let Some(addr) = self.addr {
+            lines.push(format!("address: 0x{:x}", addr));
+        }
+        if let Some((pid, uid)) = self.sender {
+            let who = if self.signal == Signal::SIGCHLD { "child" } else { "sender" };
+            lines.push(format!("{}:  {} (uid {})", who, pid, uid));
+        }
+        if let Some(status) = self.status {
+            lines.push(format!("status:  {}", status));
+        }
+        lines
+    }
+
+    ///
+    /// Formats the fields of the `siginfo_t` which are valid for this signal,
+    /// as the `$_siginfo` value.
+    ///
+    pub fn fields(&self) -> String {
+        let mut fields = format!("si_signo = {}, si_errno = {}, si_code = {}", self.signal as i32, self.errno, self.code);
+        if let Some(addr) = self.addr {
+            fields.push_str(&format!(", si_addr = 0x{:x}", addr));
+        }
+        if let Some((pid, uid)) = self.sender {
+            fields.push_str(&format!(", si_pid = {}, si_uid = {}", pid, uid));
+        }
+        if let Some(status) = self.status {
+            fields.push_str(&format!(", si_status = {}", status));
+        }
+        format!("{{{}}}", fields)
+    }
+}
+
+impl Display for SignalInfo {
+    ///
+    /// Summarises the signal, e.g. `SIGSEGV (address not mapped to object,
+    /// at address 0x0)`.
+    ///
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.signal)?;
+        let mut reasons = Vec::new();
+        if let Some((_, meaning)) = self.code_name().filter(|_| self.code > 0) {
+            reasons.push(meaning.to_string());
+        }
+        if let Some(addr) = self.addr {
+            reasons.push(format!("at address 0x{:x}", addr));
+        }
+        if let Some((pid, _)) = self.sender {
+            reasons.push(format!("from process {}", pid));
+        }
+        if !reasons.is_empty() {
+            write!(f, " ({})", reasons.join(", "))?;
+        }
+        Ok(())
+    }
+}
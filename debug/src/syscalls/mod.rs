@@ -0,0 +1,649 @@
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use log::{info, warn};
+
+use nix::errno::Errno;
+
+use crate::inspect::Inspect;
+use crate::process::Process;
+use crate::signals::Signal;
+use crate::trace::Registers;
+
+use self::table::SYSCALLS;
+
+pub mod seccomp;
+mod table;
+
+/// How many bytes of a buffer, or entries of an array, are shown before
+/// the rest is left out.
+const MAX_SHOWN: usize = 32;
+
+/// What `-ENOSYS` is as a register, which is in `rax` on entry to every
+/// system call.
+const ENTRY_RAX: u64 = -(libc::ENOSYS as i64) as u64;
+
+///
+/// The groups system calls are sorted into, so that a catchpoint can be
+/// set on, e.g., every call that deals with the network.
+///
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Group {
+    /// Calls that take the path of a file.
+    File,
+    /// Calls that create, replace, wait for or end processes.
+    Process,
+    Network,
+    Signal,
+    /// Calls for System V and POSIX IPC, and pipes.
+    Ipc,
+    /// Calls that map or change the memory of the process.
+    Memory,
+    /// Calls that take or create a file descriptor.
+    Descriptor,
+}
+
+impl FromStr for Group {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "file" => Ok(Group::File),
+            "process" => Ok(Group::Process),
+            "network" => Ok(Group::Network),
+            "signal" => Ok(Group::Signal),
+            "ipc" => Ok(Group::Ipc),
+            "memory" => Ok(Group::Memory),
+            "descriptor" => Ok(Group::Descriptor),
+            _ => Err(format!("unknown syscall group: {}", s)),
+        }
+    }
+}
+
+///
+/// How to show an argument of a system call.
+///
+#[derive(Clone, Copy)]
+pub enum Arg {
+    Int,
+    UInt,
+    /// Flags or a value that is most readable in hex.
+    Hex,
+    /// A pointer, shown as `NULL` when it is one.
+    Ptr,
+    /// A file descriptor, which may be `AT_FDCWD` for the `*at` calls.
+    Fd,
+    /// A nul terminated string, e.g. a path.
+    Str,
+    /// A null terminated array of strings, e.g. the arguments to `execve`.
+    StrArray,
+    /// File permissions, in octal.
+    Mode,
+    Sig,
+    /// The flags to `open`, including the access mode.
+    OpenFlags,
+    /// The protection of a memory mapping.
+    Prot,
+    /// The flags to `mmap`.
+    MapFlags,
+    /// A buffer passed in to the call, with its length in the given argument.
+    InBuf(usize),
+    /// A buffer filled in by the call, with as many bytes as it returns.
+    /// It's only shown once the call has returned.
+    OutBuf(usize),
+    /// A `struct stat` filled in by the call.
+    Stat,
+    /// A `struct timespec` passed in to the call.
+    Timespec,
+    /// A `struct timespec` filled in by the call.
+    OutTimespec,
+    /// A socket address passed in to the call, with its length in the
+    /// given argument.
+    Sockaddr(usize),
+}
+
+///
+/// How to show the value a system call returns.
+///
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Ret {
+    Int,
+    /// An address, e.g. from `mmap`.
+    Hex,
+    /// The call never returns, e.g. `exit`.
+    Never,
+}
+
+///
+/// An entry in the system call table.
+///
+pub struct Syscall {
+    pub number: u64,
+    pub name: &'static str,
+    pub args: &'static [Arg],
+    pub ret: Ret,
+    pub groups: &'static [Group],
+}
+
+const fn sys(number: u64, name: &'static str, args: &'static [Arg], groups: &'static [Group]) -> Syscall {
+    Syscall { number, name, args, ret: Ret::Int, groups }
+}
+
+impl Syscall {
+    const fn returns(self, ret: Ret) -> Self {
+        Syscall { ret, ..self }
+    }
+}
+
+///
+/// Looks up a system call by number.
+///
+pub fn lookup(number: u64) -> Option<&'static Syscall> {
+    SYSCALLS.binary_search_by_key(&number, |syscall| syscall.number).ok().map(|idx| &SYSCALLS[idx])
+}
+
+///
+/// The name of a system call, or its number if it isn't one we know of.
+///
+pub fn name(number: u64) -> String {
+    match lookup(number) {
+        Some(syscall) => syscall.name.to_string(),
+        None => format!("syscall_{}", number),
+    }
+}
+
+///
+/// Parses a system call from its name or number, or a group of them, as
+/// `group:NAME` or `g:NAME`, into the numbers of the calls.
+///
+pub fn parse_syscall(s: &str) -> Result<Vec<u64>, String> {
+    if let Some(group) = s.strip_prefix("group:").or_else(|| s.strip_prefix("g:")) {
+        let group = group.parse::<Group>()?;
+        return Ok(SYSCALLS.iter().filter(|syscall| syscall.groups.contains(&group)).map(|syscall| syscall.number).collect());
+    }
+
+    if let Ok(number) = s.parse::<u64>() {
+        return Ok(vec![number]);
+    }
+    SYSCALLS.iter()
+        .find(|syscall| syscall.name == s)
+        .map(|syscall| vec![syscall.number])
+        .ok_or_else(|| format!("unknown syscall: {}", s))
+}
+
+///
+/// A set of system calls, as asked for by name, number or group.
+///
+#[derive(Clone, Default)]
+pub struct SyscallFilter {
+    /// The names, numbers and groups (as `group:NAME`) that were asked for,
+    /// which is every call if there are none.
+    pub filters: Vec<String>,
+    numbers: Vec<u64>,
+}
+
+impl SyscallFilter {
+    pub fn parse(filters: &[String]) -> Result<Self, String> {
+        let mut numbers = Vec::new();
+        for filter in filters {
+            numbers.extend(parse_syscall(filter)?);
+        }
+        Ok(Self { filters: filters.to_vec(), numbers })
+    }
+
+    pub fn matches(&self, number: u64) -> bool {
+        self.filters.is_empty() || self.numbers.contains(&number)
+    }
+
+    ///
+    /// The numbers of the calls in the set, or `None` if it's every call.
+    ///
+    pub fn numbers(&self) -> Option<&[u64]> {
+        if self.filters.is_empty() {
+            None
+        } else {
+            Some(&self.numbers)
+        }
+    }
+}
+
+impl Display for SyscallFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.filters.len() {
+            0 => write!(f, "any syscall"),
+            1 => write!(f, "syscall {}", self.filters[0]),
+            _ => write!(f, "syscalls {}", self.filters.join(", ")),
+        }
+    }
+}
+
+///
+/// Logs the system calls the process makes as it runs, without stopping
+/// it, to the console or to a file.
+///
+pub struct SyscallTrace {
+    pub filter: SyscallFilter,
+    output: Option<(PathBuf, File)>,
+}
+
+impl SyscallTrace {
+    ///
+    /// Traces the given system calls, writing them to a new file at the
+    /// given path if there is one.
+    ///
+    pub fn new(filter: SyscallFilter, output: Option<PathBuf>) -> io::Result<Self> {
+        let output = match output {
+            Some(path) => {
+                let file = File::create(&path)?;
+                Some((path, file))
+            }
+            None => None,
+        };
+        Ok(Self { filter, output })
+    }
+
+    ///
+    /// The path of the file the calls are written to, if they aren't logged
+    /// to the console.
+    ///
+    pub fn path(&self) -> Option<&Path> {
+        self.output.as_ref().map(|(path, _)| path.as_path())
+    }
+
+    ///
+    /// Logs a call a thread stopped on, if it's one being traced. Calls are
+    /// logged once they return, so that what they return and fill in is
+    /// known, apart from those which never return, or which replace the
+    /// memory their arguments were in.
+    ///
+    pub fn log(&mut self, process: &Process, tid: i32, info: &SyscallInfo) {
+        if !self.filter.matches(info.number) {
+            return;
+        }
+
+        let exec = info.is_exec();
+        let never = lookup(info.number).is_some_and(|syscall| syscall.ret == Ret::Never);
+        let line = match info.ret {
+            None if never => info.describe(process) + " = ?",
+            None if exec => info.describe(process) + " <unfinished ...>",
+            None => return,
+            Some(_) if exec => format!("<... {} resumed>{}", info.name(), info.describe_ret(Ret::Int)),
+            Some(_) => info.describe(process),
+        };
+
+        match self.output.as_mut() {
+            Some((path, file)) => {
+                if let Err(e) = writeln!(file, "[pid {}] {}", tid, line) {
+                    warn!("failed to write to {}: {}", path.display(), e);
+                }
+            }
+            None => info!("[pid {}] {}", tid, line),
+        }
+    }
+}
+
+///
+/// A system call a thread stopped on, either on entry to it, or as it
+/// returns.
+///
+#[derive(Clone, Copy)]
+pub struct SyscallInfo {
+    pub number: u64,
+    pub args: [u64; 6],
+    /// The value returned, once the call has returned.
+    pub ret: Option<i64>,
+}
+
+impl SyscallInfo {
+    ///
+    /// Reads the system call a thread has stopped on from its registers.
+    /// The kernel puts `-ENOSYS` in `rax` before it stops on entry, so
+    /// anything else there is the value being returned.
+    ///
+    pub fn from_registers(regs: &Registers) -> Self {
+        Self {
+            number: regs.orig_rax,
+            args: [regs.rdi, regs.rsi, regs.rdx, regs.r10, regs.r8, regs.r9],
+            ret: if regs.rax == ENTRY_RAX { None } else { Some(regs.rax as i64) },
+        }
+    }
+
+    ///
+    /// The same call, having returned the given value.
+    ///
+    pub fn returned(self, ret: i64) -> Self {
+        Self { ret: Some(ret), ..self }
+    }
+
+    pub fn is_entry(&self) -> bool {
+        self.ret.is_none()
+    }
+
+    pub fn name(&self) -> String {
+        name(self.number)
+    }
+
+    ///
+    /// Whether the call replaces the program the process is running.
+    ///
+    fn is_exec(&self) -> bool {
+        self.number == libc::SYS_execve as u64 || self.number == libc::SYS_execveat as u64
+    }
+
+    ///
+    /// Shows the call with its arguments decoded, reading anything they
+    /// point to from the process, e.g. `openat(AT_FDCWD, "/etc/hosts",
+    /// O_RDONLY) = 3`, with the value it returned if it has.
+    ///
+    pub fn describe(&self, process: &Process) -> String {
+        let syscall = match lookup(self.number) {
+            Some(syscall) => syscall,
+            None => {
+                let args: Vec<String> = self.args.iter().map(|arg| format!("0x{:x}", arg)).collect();
+                return format!("syscall_{}({}){}", self.number, args.join(", "), self.describe_ret(Ret::Int));
+            }
+        };
+
+        // a successful exec has replaced the memory the arguments were in
+        let args = if self.is_exec() && self.ret == Some(0) {
+            String::from("...")
+        } else {
+            let args: Vec<String> = syscall.args.iter()
+                .zip(self.args.iter())
+                .map(|(arg, value)| self.describe_arg(process, *arg, *value))
+                .collect();
+            args.join(", ")
+        };
+        format!("{}({}){}", syscall.name, args, self.describe_ret(syscall.ret))
+    }
+
+    fn describe_arg(&self, process: &Process, arg: Arg, value: u64) -> String {
+        let pointer = || if value == 0 { String::from("NULL") } else { format!("0x{:x}", value) };
+        match arg {
+            Arg::Int => (value as i64).to_string(),
+            Arg::UInt => value.to_string(),
+            Arg::Hex => format!("0x{:x}", value),
+            Arg::Ptr => pointer(),
+            Arg::Fd if value as i32 == libc::AT_FDCWD => String::from("AT_FDCWD"),
+            Arg::Fd => (value as i32).to_string(),
+            Arg::Str if value == 0 => pointer(),
+            Arg::Str => process.read_string(value).map_or_else(|_| pointer(), |s| quote(s.as_bytes(), usize::MAX)),
+            Arg::StrArray if value == 0 => pointer(),
+            Arg::StrArray => read_str_array(process, value).unwrap_or_else(pointer),
+            Arg::Mode if value == 0 => String::from("0"),
+            Arg::Mode => format!("0{:o}", value),
+            Arg::Sig => match Signal::try_from(value as i32) {
+                Ok(signal) => signal.as_str().to_string(),
+                Err(_) => (value as i32).to_string(),
+            },
+            Arg::OpenFlags => open_flags(value),
+            Arg::Prot => flags(value, PROT_FLAGS, "PROT_NONE"),
+            Arg::MapFlags => flags(value, MAP_FLAGS, "0"),
+            Arg::InBuf(len) => read_buffer(process, value, self.args[len] as usize).unwrap_or_else(pointer),
+            Arg::OutBuf(_) => match self.ret {
+                Some(len) if len >= 0 => read_buffer(process, value, len as usize).unwrap_or_else(pointer),
+                _ => pointer(),
+            },
+            Arg::Stat | Arg::OutTimespec if value == 0 || self.ret.is_none_or(|ret| ret < 0) => pointer(),
+            Arg::Timespec | Arg::Sockaddr(_) if value == 0 => pointer(),
+            Arg::Stat => read_struct(process, value, STAT_SIZE, stat).unwrap_or_else(pointer),
+            Arg::Timespec | Arg::OutTimespec => read_struct(process, value, TIMESPEC_SIZE, timespec).unwrap_or_else(pointer),
+            Arg::Sockaddr(len) => {
+                read_struct(process, value, (self.args[len] as usize).min(SOCKADDR_SIZE), sockaddr).unwrap_or_else(pointer)
+            }
+        }
+    }
+
+    fn describe_ret(&self, kind: Ret) -> String {
+        let ret = match self.ret {
+            Some(ret) => ret,
+            None => return String::new(),
+        };
+
+        match ret {
+            -4095..=-1 => {
+                let errno = -ret as i32;
+                match restart_name(errno) {
+                    Some(name) => format!(" = ? {} (to be restarted)", name),
+                    None => {
+                        let error = Errno::from_i32(errno);
+                        format!(" = -1 {:?} ({})", error, error.desc())
+                    }
+                }
+            }
+            _ if kind == Ret::Hex => format!(" = 0x{:x}", ret),
+            _ if kind == Ret::Never => String::from(" = ?"),
+            _ => format!(" = {}", ret),
+        }
+    }
+}
+
+///
+/// The kernel's internal errors for a call interrupted by a signal, which
+/// the process never sees, since the call is restarted.
+///
+fn restart_name(errno: i32) -> Option<&'static str> {
+    match errno {
+        512 => Some("ERESTARTSYS"),
+        513 => Some("ERESTARTNOINTR"),
+        514 => Some("ERESTARTNOHAND"),
+        516 => Some("ERESTART_RESTARTBLOCK"),
+        _ => None,
+    }
+}
+
+const OPEN_FLAGS: &[(u64, &str)] = &[
+    (libc::O_TMPFILE as u64, "O_TMPFILE"),
+    (libc::O_SYNC as u64, "O_SYNC"),
+    (libc::O_CREAT as u64, "O_CREAT"),
+    (libc::O_EXCL as u64, "O_EXCL"),
+    (libc::O_NOCTTY as u64, "O_NOCTTY"),
+    (libc::O_TRUNC as u64, "O_TRUNC"),
+    (libc::O_APPEND as u64, "O_APPEND"),
+    (libc::O_NONBLOCK as u64, "O_NONBLOCK"),
+    (libc::O_DSYNC as u64, "O_DSYNC"),
+    (libc::O_ASYNC as u64, "O_ASYNC"),
+    (libc::O_DIRECT as u64, "O_DIRECT"),
+    (libc::O_LARGEFILE as u64, "O_LARGEFILE"),
+    (libc::O_DIRECTORY as u64, "O_DIRECTORY"),
+    (libc::O_NOFOLLOW as u64, "O_NOFOLLOW"),
+    (libc::O_NOATIME as u64, "O_NOATIME"),
+    (libc::O_CLOEXEC as u64, "O_CLOEXEC"),
+    (libc::O_PATH as u64, "O_PATH"),
+];
+
+const PROT_FLAGS: &[(u64, &str)] = &[
+    (libc::PROT_READ as u64, "PROT_READ"),
+    (libc::PROT_WRITE as u64, "PROT_WRITE"),
+    (libc::PROT_EXEC as u64, "PROT_EXEC"),
+];
+
+const MAP_FLAGS: &[(u64, &str)] = &[
+    (libc::MAP_SHARED_VALIDATE as u64, "MAP_SHARED_VALIDATE"),
+    (libc::MAP_SHARED as u64, "MAP_SHARED"),
+    (libc::MAP_PRIVATE as u64, "MAP_PRIVATE"),
+    (libc::MAP_FIXED as u64, "MAP_FIXED"),
+    (libc::MAP_ANONYMOUS as u64, "MAP_ANONYMOUS"),
+    (libc::MAP_GROWSDOWN as u64, "MAP_GROWSDOWN"),
+    (libc::MAP_DENYWRITE as u64, "MAP_DENYWRITE"),
+    (libc::MAP_EXECUTABLE as u64, "MAP_EXECUTABLE"),
+    (libc::MAP_LOCKED as u64, "MAP_LOCKED"),
+    (libc::MAP_NORESERVE as u64, "MAP_NORESERVE"),
+    (libc::MAP_POPULATE as u64, "MAP_POPULATE"),
+    (libc::MAP_NONBLOCK as u64, "MAP_NONBLOCK"),
+    (libc::MAP_STACK as u64, "MAP_STACK"),
+    (libc::MAP_HUGETLB as u64, "MAP_HUGETLB"),
+    (libc::MAP_FIXED_NOREPLACE as u64, "MAP_FIXED_NOREPLACE"),
+];
+
+///
+/// Shows a set of flags by name, joined with `|`, with any bits we have no
+/// name for left over in hex. Flags made up of several bits come first in
+/// the table, so they're used in preference to their parts.
+///
+fn flags(value: u64, names: &[(u64, &str)], none: &str) -> String {
+    let mut rest = value;
+    let mut shown = Vec::new();
+    for (bits, name) in names {
+        if *bits != 0 && rest & bits == *bits {
+            shown.push(name.to_string());
+            rest &= !bits;
+        }
+    }
+    if rest != 0 {
+        shown.push(format!("0x{:x}", rest));
+    }
+
+    if shown.is_empty() {
+        none.to_string()
+    } else {
+        shown.join("|")
+    }
+}
+
+fn open_flags(value: u64) -> String {
+    let access = match value & libc::O_ACCMODE as u64 {
+        0 => "O_RDONLY",
+        1 => "O_WRONLY",
+        2 => "O_RDWR",
+        _ => "O_ACCMODE",
+    };
+    match flags(value & !(libc::O_ACCMODE as u64), OPEN_FLAGS, "") {
+        rest if rest.is_empty() => access.to_string(),
+        rest => format!("{}|{}", access, rest),
+    }
+}
+
+/// The sizes of the structures we decode, as laid out on x86_64.
+const STAT_SIZE: usize = 144;
+const TIMESPEC_SIZE: usize = 16;
+const SOCKADDR_SIZE: usize = 128;
+
+///
+/// Reads a structure from the process, and summarizes it.
+///
+fn read_struct(process: &Process, addr: u64, size: usize, show: fn(&[u8]) -> Option<String>) -> Option<String> {
+    show(&process.read_memory(addr, size).ok()?)
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_ne_bytes(bytes.get(offset..offset + 2)?.try_into().ok()?))
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_ne_bytes(bytes.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn i64_at(bytes: &[u8], offset: usize) -> Option<i64> {
+    Some(i64::from_ne_bytes(bytes.get(offset..offset + 8)?.try_into().ok()?))
+}
+
+///
+/// Summarizes a `struct stat` by the type, permissions and size of the file.
+///
+fn stat(bytes: &[u8]) -> Option<String> {
+    let mode = u32_at(bytes, 24)?;
+    let size = i64_at(bytes, 48)?;
+    let kind = match mode & libc::S_IFMT {
+        libc::S_IFREG => "S_IFREG",
+        libc::S_IFDIR => "S_IFDIR",
+        libc::S_IFLNK => "S_IFLNK",
+        libc::S_IFCHR => "S_IFCHR",
+        libc::S_IFBLK => "S_IFBLK",
+        libc::S_IFIFO => "S_IFIFO",
+        libc::S_IFSOCK => "S_IFSOCK",
+        _ => "0",
+    };
+    Some(format!("{{st_mode={}|0{:o}, st_size={}, ...}}", kind, mode & 0o7777, size))
+}
+
+fn timespec(bytes: &[u8]) -> Option<String> {
+    Some(format!("{{tv_sec={}, tv_nsec={}}}", i64_at(bytes, 0)?, i64_at(bytes, 8)?))
+}
+
+///
+/// Summarizes a socket address by its family and where it points, for
+/// the families that are common enough to be worth decoding.
+///
+fn sockaddr(bytes: &[u8]) -> Option<String> {
+    let family = u16_at(bytes, 0)? as i32;
+    let summary = match family {
+        libc::AF_INET => {
+            let port = u16::from_be(u16_at(bytes, 2)?);
+            let addr: [u8; 4] = bytes.get(4..8)?.try_into().ok()?;
+            format!("AF_INET, {}", SocketAddr::from((addr, port)))
+        }
+        libc::AF_INET6 => {
+            let port = u16::from_be(u16_at(bytes, 2)?);
+            let addr: [u8; 16] = bytes.get(8..24)?.try_into().ok()?;
+            format!("AF_INET6, {}", SocketAddr::from((addr, port)))
+        }
+        libc::AF_UNIX => {
+            let path = bytes.get(2..)?;
+            // an abstract socket's name starts with a nul, rather than ending with one
+            let path = match path.first() {
+                Some(0) => path,
+                _ => &path[..path.iter().position(|b| *b == 0).unwrap_or(path.len())],
+            };
+            format!("AF_UNIX, {}", quote(path, usize::MAX))
+        }
+        _ => format!("sa_family={}", family),
+    };
+    Some(format!("{{{}}}", summary))
+}
+
+///
+/// Reads up to `MAX_SHOWN` bytes of a buffer, quoted like a C string.
+///
+fn read_buffer(process: &Process, addr: u64, len: usize) -> Option<String> {
+    let bytes = process.read_memory(addr, len.min(MAX_SHOWN)).ok()?;
+    Some(quote(&bytes, len))
+}
+
+fn read_str_array(process: &Process, addr: u64) -> Option<String> {
+    let word_size = std::mem::size_of::<u64>() as u64;
+    let mut strings = Vec::new();
+    for idx in 0.. {
+        let ptr = process.read(addr + idx * word_size).ok()? as u64;
+        if ptr == 0 {
+            break;
+        }
+        if strings.len() == MAX_SHOWN {
+            strings.push(String::from("..."));
+            break;
+        }
+        strings.push(quote(process.read_string(ptr).ok()?.as_bytes(), usize::MAX));
+    }
+    Some(format!("[{}]", strings.join(", ")))
+}
+
+///
+/// Quotes bytes like a C string, escaping anything unprintable, and
+/// marking it with `...` if the whole `len` bytes weren't all there.
+///
+fn quote(bytes: &[u8], len: usize) -> String {
+    let mut quoted = String::from("\"");
+    for byte in bytes {
+        match byte {
+            b'\n' => quoted.push_str("\\n"),
+            b'\t' => quoted.push_str("\\t"),
+            b'\r' => quoted.push_str("\\r"),
+            b'"' => quoted.push_str("\\\""),
+            b'\\' => quoted.push_str("\\\\"),
+            0x20..=0x7e => quoted.push(*byte as char),
+            _ => quoted.push_str(&format!("\\x{:02x}", byte)),
+        }
+    }
+    quoted.push('"');
+    if len > bytes.len() && len != usize::MAX {
+        quoted.push_str("...");
+    }
+    quoted
+}
@@ -0,0 +1,213 @@
+use std::fs;
+use std::io;
+
+use libc::{sock_filter, sock_fprog, BPF_ABS, BPF_JEQ, BPF_JMP, BPF_K, BPF_LD, BPF_RET, BPF_W};
+
+/// The architecture seccomp reports for x86_64 system calls, from
+/// `linux/audit.h`.
+const AUDIT_ARCH_X86_64: u32 = 0xc000_003e;
+
+/// Where the system call number and architecture are in `seccomp_data`.
+const NR_OFFSET: u32 = 0;
+const ARCH_OFFSET: u32 = 4;
+
+///
+/// A seccomp-BPF filter which makes the kernel stop a traced thread with
+/// `PTRACE_EVENT_SECCOMP` on the given system calls, and lets every other
+/// call through without the tracer hearing about it at all.
+///
+/// Once installed, a filter can't be taken out again, so it lasts for the
+/// rest of the process's life and is inherited by its children, as is the
+/// no-new-privileges flag installing it requires, which stops setuid and
+/// setgid programs the process runs from gaining privileges.
+///
+pub struct SeccompFilter {
+    numbers: Vec<u64>,
+    program: Vec<sock_filter>,
+}
+
+impl SeccompFilter {
+    ///
+    /// Builds a filter for the given system calls, or returns `None` if
+    /// there's nothing worth filtering, or too many calls for a jump to get
+    /// over: the architecture check has to jump past every call's check,
+    /// plus the load of the call's number, in a single byte.
+    ///
+    /// The exec calls are always let through, since the filter is installed
+    /// before the process execs its program, which would otherwise stop on
+    /// the call before the launch sees it exec.
+    ///
+    pub fn new(numbers: &[u64]) -> Option<Self> {
+        let mut numbers: Vec<u64> = numbers.iter().copied()
+            .filter(|number| *number != libc::SYS_execve as u64 && *number != libc::SYS_execveat as u64)
+            .collect();
+        numbers.sort_unstable();
+        numbers.dedup();
+        if numbers.is_empty() || numbers.len() >= u8::MAX as usize {
+            return None;
+        }
+
+        // each check either jumps straight to the trace at the very end,
+        // or falls through to the next, with the last falling through to
+        // let the call through.
+        let count = numbers.len();
+        let mut program = vec![
+            stmt(BPF_LD | BPF_W | BPF_ABS, ARCH_OFFSET),
+            jump(BPF_JMP | BPF_JEQ | BPF_K, AUDIT_ARCH_X86_64, 0, count as u8 + 1),
+            stmt(BPF_LD | BPF_W | BPF_ABS, NR_OFFSET),
+        ];
+        for (i, number) in numbers.iter().enumerate() {
+            program.push(jump(BPF_JMP | BPF_JEQ | BPF_K, *number as u32, (count - i) as u8, 0));
+        }
+        program.push(stmt(BPF_RET | BPF_K, libc::SECCOMP_RET_ALLOW));
+        program.push(stmt(BPF_RET | BPF_K, libc::SECCOMP_RET_TRACE));
+
+        Some(Self { numbers, program })
+    }
+
+    ///
+    /// The system calls the filter stops threads on.
+    ///
+    pub fn numbers(&self) -> &[u64] {
+        &self.numbers
+    }
+
+    ///
+    /// Installs the filter in the calling process, which is expected to be
+    /// a child about to exec. It can't gain privileges on exec afterwards,
+    /// which the kernel requires of unprivileged processes using seccomp.
+    ///
+    pub fn install(&self) -> io::Result<()> {
+        let prog = sock_fprog {
+            len: self.program.len() as u16,
+            filter: self.program.as_ptr() as *mut sock_filter,
+        };
+        unsafe {
+            if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if libc::syscall(libc::SYS_seccomp, libc::SECCOMP_SET_MODE_FILTER, 0, &prog as *const sock_fprog) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+}
+
+///
+/// Whether the given child has more seccomp filters than we do, meaning
+/// it managed to install its own before it exec'd. Kernels too old to
+/// count filters are assumed not to have.
+///
+pub fn installed(pid: i32) -> bool {
+    match (filter_count("self"), filter_count(&pid.to_string())) {
+        (Some(ours), Some(theirs)) => theirs > ours,
+        _ => false,
+    }
+}
+
+fn filter_count(pid: &str) -> Option<u32> {
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    status.lines()
+        .find_map(|line| line.strip_prefix("Seccomp_filters:"))
+        .and_then(|count| count.trim().parse().ok())
+}
+
+fn stmt(code: u32, k: u32) -> sock_filter {
+    sock_filter { code: code as u16, jt: 0, jf: 0, k }
+}
+
+fn jump(code: u32, k: u32, jt: u8, jf: u8) -> sock_filter {
+    sock_filter { code: code as u16, jt, jf, k }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///
+    /// Runs a filter's program on a call the way the kernel would, as far
+    /// as the instructions it's made up of go.
+    ///
+    fn run(filter: &SeccompFilter, arch: u32, nr: u32) -> u32 {
+        let mut pc = 0;
+        let mut acc = 0;
+        loop {
+            let insn = &filter.program[pc];
+            pc += 1;
+            match insn.code as u32 {
+                code if code == BPF_LD | BPF_W | BPF_ABS => acc = if insn.k == ARCH_OFFSET { arch } else { nr },
+                code if code == BPF_JMP | BPF_JEQ | BPF_K => {
+                    pc += if acc == insn.k { insn.jt } else { insn.jf } as usize;
+                }
+                code if code == BPF_RET | BPF_K => return insn.k,
+                code => panic!("unexpected instruction {:#x}", code),
+            }
+        }
+    }
+
+    fn numbers(count: u64) -> Vec<u64> {
+        (1000..1000 + count).collect()
+    }
+
+    fn check(count: u64) {
+        let filter = SeccompFilter::new(&numbers(count)).unwrap();
+        for number in numbers(count) {
+            assert_eq!(run(&filter, AUDIT_ARCH_X86_64, number as u32), libc::SECCOMP_RET_TRACE);
+        }
+        assert_eq!(run(&filter, AUDIT_ARCH_X86_64, 999), libc::SECCOMP_RET_ALLOW);
+        assert_eq!(run(&filter, AUDIT_ARCH_X86_64, 1000 + count as u32), libc::SECCOMP_RET_ALLOW);
+        // another architecture's numbers mean something else entirely
+        assert_eq!(run(&filter, 0x4000_0003, 1000), libc::SECCOMP_RET_ALLOW);
+    }
+
+    #[test]
+    fn one_call() {
+        check(1);
+    }
+
+    #[test]
+    fn most_calls() {
+        check(254);
+        let filter = SeccompFilter::new(&numbers(254)).unwrap();
+        assert_eq!(filter.program[1].jf, u8::MAX);
+    }
+
+    #[test]
+    fn too_many_calls() {
+        assert!(SeccompFilter::new(&numbers(255)).is_none());
+    }
+
+    #[test]
+    fn untraced_calls_fail() {
+        let filter = SeccompFilter::new(&[libc::SYS_getppid as u64]).unwrap();
+        // the child has no tracer to stop on the call, as after a detach,
+        // and only does what's safe between fork and exec
+        let pid = unsafe { libc::fork() };
+        if pid == 0 {
+            let code = match filter.install() {
+                Ok(()) if unsafe { libc::syscall(libc::SYS_getppid) } == -1 => {
+                    match io::Error::last_os_error().raw_os_error() {
+                        Some(libc::ENOSYS) => 0,
+                        _ => 1,
+                    }
+                }
+                Ok(()) => 1,
+                Err(_) => 2,
+            };
+            unsafe { libc::_exit(code) };
+        }
+        let mut status = 0;
+        assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+        assert!(libc::WIFEXITED(status));
+        assert_eq!(libc::WEXITSTATUS(status), 0);
+    }
+
+    #[test]
+    fn exec_let_through() {
+        let filter = SeccompFilter::new(&[libc::SYS_execve as u64, libc::SYS_read as u64]).unwrap();
+        assert_eq!(filter.numbers(), &[libc::SYS_read as u64]);
+        assert_eq!(run(&filter, AUDIT_ARCH_X86_64, libc::SYS_execve as u32), libc::SECCOMP_RET_ALLOW);
+        assert!(SeccompFilter::new(&[libc::SYS_execve as u64]).is_none());
+    }
+}
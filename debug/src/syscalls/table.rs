@@ -0,0 +1,382 @@
+use super::{Arg::*, Group::*, Ret, Syscall, sys};
+
+///
+/// Every x86_64 system call, in order of number, with how to show the
+/// arguments each of them is called with.
+///
+pub(super) static SYSCALLS: &[Syscall] = &[
+    sys(0, "read", &[Fd, OutBuf(2), UInt], &[Descriptor]),
+    sys(1, "write", &[Fd, InBuf(2), UInt], &[Descriptor]),
+    sys(2, "open", &[Str, OpenFlags, Mode], &[File, Descriptor]),
+    sys(3, "close", &[Fd], &[Descriptor]),
+    sys(4, "stat", &[Str, Stat], &[File]),
+    sys(5, "fstat", &[Fd, Stat], &[Descriptor]),
+    sys(6, "lstat", &[Str, Stat], &[File]),
+    sys(7, "poll", &[Ptr, UInt, Int], &[Descriptor]),
+    sys(8, "lseek", &[Fd, Int, Int], &[Descriptor]),
+    sys(9, "mmap", &[Ptr, UInt, Prot, MapFlags, Fd, Hex], &[Memory, Descriptor]).returns(Ret::Hex),
+    sys(10, "mprotect", &[Ptr, UInt, Prot], &[Memory]),
+    sys(11, "munmap", &[Ptr, UInt], &[Memory]),
+    sys(12, "brk", &[Ptr], &[Memory]).returns(Ret::Hex),
+    sys(13, "rt_sigaction", &[Sig, Ptr, Ptr, UInt], &[Signal]),
+    sys(14, "rt_sigprocmask", &[Int, Ptr, Ptr, UInt], &[Signal]),
+    sys(15, "rt_sigreturn", &[], &[Signal]),
+    sys(16, "ioctl", &[Fd, Hex, Hex], &[Descriptor]),
+    sys(17, "pread64", &[Fd, OutBuf(2), UInt, Int], &[Descriptor]),
+    sys(18, "pwrite64", &[Fd, InBuf(2), UInt, Int], &[Descriptor]),
+    sys(19, "readv", &[Fd, Ptr, Int], &[Descriptor]),
+    sys(20, "writev", &[Fd, Ptr, Int], &[Descriptor]),
+    sys(21, "access", &[Str, Int], &[File]),
+    sys(22, "pipe", &[Ptr], &[Descriptor, Ipc]),
+    sys(23, "select", &[Int, Ptr, Ptr, Ptr, Ptr], &[Descriptor]),
+    sys(24, "sched_yield", &[], &[]),
+    sys(25, "mremap", &[Ptr, UInt, UInt, Hex, Ptr], &[Memory]).returns(Ret::Hex),
+    sys(26, "msync", &[Ptr, UInt, Hex], &[Memory]),
+    sys(27, "mincore", &[Ptr, UInt, Ptr], &[Memory]),
+    sys(28, "madvise", &[Ptr, UInt, Int], &[Memory]),
+    sys(29, "shmget", &[Hex, UInt, Hex], &[Ipc]),
+    sys(30, "shmat", &[Int, Ptr, Hex], &[Ipc, Memory]).returns(Ret::Hex),
+    sys(31, "shmctl", &[Int, Int, Ptr], &[Ipc]),
+    sys(32, "dup", &[Fd], &[Descriptor]),
+    sys(33, "dup2", &[Fd, Fd], &[Descriptor]),
+    sys(34, "pause", &[], &[Signal]),
+    sys(35, "nanosleep", &[Timespec, Ptr], &[]),
+    sys(36, "getitimer", &[Int, Ptr], &[]),
+    sys(37, "alarm", &[UInt], &[]),
+    sys(38, "setitimer", &[Int, Ptr, Ptr], &[]),
+    sys(39, "getpid", &[], &[]),
+    sys(40, "sendfile", &[Fd, Fd, Ptr, UInt], &[Descriptor, Network]),
+    sys(41, "socket", &[Int, Int, Int], &[Network, Descriptor]),
+    sys(42, "connect", &[Fd, Sockaddr(2), UInt], &[Network]),
+    sys(43, "accept", &[Fd, Ptr, Ptr], &[Network, Descriptor]),
+    sys(44, "sendto", &[Fd, InBuf(2), UInt, Hex, Sockaddr(5), UInt], &[Network]),
+    sys(45, "recvfrom", &[Fd, OutBuf(2), UInt, Hex, Ptr, Ptr], &[Network]),
+    sys(46, "sendmsg", &[Fd, Ptr, Hex], &[Network]),
+    sys(47, "recvmsg", &[Fd, Ptr, Hex], &[Network]),
+    sys(48, "shutdown", &[Fd, Int], &[Network]),
+    sys(49, "bind", &[Fd, Sockaddr(2), UInt], &[Network]),
+    sys(50, "listen", &[Fd, Int], &[Network]),
+    sys(51, "getsockname", &[Fd, Ptr, Ptr], &[Network]),
+    sys(52, "getpeername", &[Fd, Ptr, Ptr], &[Network]),
+    sys(53, "socketpair", &[Int, Int, Int, Ptr], &[Network, Descriptor]),
+    sys(54, "setsockopt", &[Fd, Int, Int, Ptr, UInt], &[Network]),
+    sys(55, "getsockopt", &[Fd, Int, Int, Ptr, Ptr], &[Network]),
+    sys(56, "clone", &[Hex, Ptr, Ptr, Ptr, Hex], &[Process]),
+    sys(57, "fork", &[], &[Process]),
+    sys(58, "vfork", &[], &[Process]),
+    sys(59, "execve", &[Str, StrArray, Ptr], &[File, Process]),
+    sys(60, "exit", &[Int], &[Process]).returns(Ret::Never),
+    sys(61, "wait4", &[Int, Ptr, Hex, Ptr], &[Process]),
+    sys(62, "kill", &[Int, Sig], &[Signal, Process]),
+    sys(63, "uname", &[Ptr], &[]),
+    sys(64, "semget", &[Hex, Int, Hex], &[Ipc]),
+    sys(65, "semop", &[Int, Ptr, UInt], &[Ipc]),
+    sys(66, "semctl", &[Int, Int, Int, Hex], &[Ipc]),
+    sys(67, "shmdt", &[Ptr], &[Ipc, Memory]),
+    sys(68, "msgget", &[Hex, Hex], &[Ipc]),
+    sys(69, "msgsnd", &[Int, Ptr, UInt, Hex], &[Ipc]),
+    sys(70, "msgrcv", &[Int, Ptr, UInt, Int, Hex], &[Ipc]),
+    sys(71, "msgctl", &[Int, Int, Ptr], &[Ipc]),
+    sys(72, "fcntl", &[Fd, Int, Hex], &[Descriptor]),
+    sys(73, "flock", &[Fd, Int], &[Descriptor]),
+    sys(74, "fsync", &[Fd], &[Descriptor]),
+    sys(75, "fdatasync", &[Fd], &[Descriptor]),
+    sys(76, "truncate", &[Str, Int], &[File]),
+    sys(77, "ftruncate", &[Fd, Int], &[Descriptor]),
+    sys(78, "getdents", &[Fd, Ptr, UInt], &[Descriptor]),
+    sys(79, "getcwd", &[Ptr, UInt], &[File]),
+    sys(80, "chdir", &[Str], &[File]),
+    sys(81, "fchdir", &[Fd], &[Descriptor]),
+    sys(82, "rename", &[Str, Str], &[File]),
+    sys(83, "mkdir", &[Str, Mode], &[File]),
+    sys(84, "rmdir", &[Str], &[File]),
+    sys(85, "creat", &[Str, Mode], &[File, Descriptor]),
+    sys(86, "link", &[Str, Str], &[File]),
+    sys(87, "unlink", &[Str], &[File]),
+    sys(88, "symlink", &[Str, Str], &[File]),
+    sys(89, "readlink", &[Str, Ptr, UInt], &[File]),
+    sys(90, "chmod", &[Str, Mode], &[File]),
+    sys(91, "fchmod", &[Fd, Mode], &[Descriptor]),
+    sys(92, "chown", &[Str, Int, Int], &[File]),
+    sys(93, "fchown", &[Fd, Int, Int], &[Descriptor]),
+    sys(94, "lchown", &[Str, Int, Int], &[File]),
+    sys(95, "umask", &[Mode], &[]),
+    sys(96, "gettimeofday", &[Ptr, Ptr], &[]),
+    sys(97, "getrlimit", &[Int, Ptr], &[]),
+    sys(98, "getrusage", &[Int, Ptr], &[]),
+    sys(99, "sysinfo", &[Ptr], &[]),
+    sys(100, "times", &[Ptr], &[]),
+    sys(101, "ptrace", &[Int, Int, Ptr, Hex], &[]),
+    sys(102, "getuid", &[], &[]),
+    sys(103, "syslog", &[Int, Ptr, Int], &[]),
+    sys(104, "getgid", &[], &[]),
+    sys(105, "setuid", &[Int], &[]),
+    sys(106, "setgid", &[Int], &[]),
+    sys(107, "geteuid", &[], &[]),
+    sys(108, "getegid", &[], &[]),
+    sys(109, "setpgid", &[Int, Int], &[]),
+    sys(110, "getppid", &[], &[]),
+    sys(111, "getpgrp", &[], &[]),
+    sys(112, "setsid", &[], &[]),
+    sys(113, "setreuid", &[Int, Int], &[]),
+    sys(114, "setregid", &[Int, Int], &[]),
+    sys(115, "getgroups", &[Int, Ptr], &[]),
+    sys(116, "setgroups", &[UInt, Ptr], &[]),
+    sys(117, "setresuid", &[Int, Int, Int], &[]),
+    sys(118, "getresuid", &[Ptr, Ptr, Ptr], &[]),
+    sys(119, "setresgid", &[Int, Int, Int], &[]),
+    sys(120, "getresgid", &[Ptr, Ptr, Ptr], &[]),
+    sys(121, "getpgid", &[Int], &[]),
+    sys(122, "setfsuid", &[Int], &[]),
+    sys(123, "setfsgid", &[Int], &[]),
+    sys(124, "getsid", &[Int], &[]),
+    sys(125, "capget", &[Ptr, Ptr], &[]),
+    sys(126, "capset", &[Ptr, Ptr], &[]),
+    sys(127, "rt_sigpending", &[Ptr, UInt], &[Signal]),
+    sys(128, "rt_sigtimedwait", &[Ptr, Ptr, Ptr, UInt], &[Signal]),
+    sys(129, "rt_sigqueueinfo", &[Int, Sig, Ptr], &[Signal]),
+    sys(130, "rt_sigsuspend", &[Ptr, UInt], &[Signal]),
+    sys(131, "sigaltstack", &[Ptr, Ptr], &[Signal]),
+    sys(132, "utime", &[Str, Ptr], &[File]),
+    sys(133, "mknod", &[Str, Mode, Hex], &[File]),
+    sys(134, "uselib", &[Str], &[File]),
+    sys(135, "personality", &[Hex], &[]),
+    sys(136, "ustat", &[Hex, Ptr], &[]),
+    sys(137, "statfs", &[Str, Ptr], &[File]),
+    sys(138, "fstatfs", &[Fd, Ptr], &[Descriptor]),
+    sys(139, "sysfs", &[Int, Hex, Hex], &[]),
+    sys(140, "getpriority", &[Int, Int], &[]),
+    sys(141, "setpriority", &[Int, Int, Int], &[]),
+    sys(142, "sched_setparam", &[Int, Ptr], &[]),
+    sys(143, "sched_getparam", &[Int, Ptr], &[]),
+    sys(144, "sched_setscheduler", &[Int, Int, Ptr], &[]),
+    sys(145, "sched_getscheduler", &[Int], &[]),
+    sys(146, "sched_get_priority_max", &[Int], &[]),
+    sys(147, "sched_get_priority_min", &[Int], &[]),
+    sys(148, "sched_rr_get_interval", &[Int, Ptr], &[]),
+    sys(149, "mlock", &[Ptr, UInt], &[Memory]),
+    sys(150, "munlock", &[Ptr, UInt], &[Memory]),
+    sys(151, "mlockall", &[Hex], &[Memory]),
+    sys(152, "munlockall", &[], &[Memory]),
+    sys(153, "vhangup", &[], &[]),
+    sys(154, "modify_ldt", &[Int, Ptr, UInt], &[]),
+    sys(155, "pivot_root", &[Str, Str], &[File]),
+    sys(156, "_sysctl", &[Ptr], &[]),
+    sys(157, "prctl", &[Int, Hex, Hex, Hex, Hex], &[]),
+    sys(158, "arch_prctl", &[Hex, Hex], &[]),
+    sys(159, "adjtimex", &[Ptr], &[]),
+    sys(160, "setrlimit", &[Int, Ptr], &[]),
+    sys(161, "chroot", &[Str], &[File]),
+    sys(162, "sync", &[], &[]),
+    sys(163, "acct", &[Str], &[File]),
+    sys(164, "settimeofday", &[Ptr, Ptr], &[]),
+    sys(165, "mount", &[Str, Str, Str, Hex, Ptr], &[File]),
+    sys(166, "umount2", &[Str, Hex], &[File]),
+    sys(167, "swapon", &[Str, Hex], &[File]),
+    sys(168, "swapoff", &[Str], &[File]),
+    sys(169, "reboot", &[Hex, Hex, Hex, Ptr], &[]),
+    sys(170, "sethostname", &[InBuf(1), UInt], &[]),
+    sys(171, "setdomainname", &[InBuf(1), UInt], &[]),
+    sys(172, "iopl", &[Int], &[]),
+    sys(173, "ioperm", &[UInt, UInt, Int], &[]),
+    sys(174, "create_module", &[Str, UInt], &[]),
+    sys(175, "init_module", &[Ptr, UInt, Str], &[]),
+    sys(176, "delete_module", &[Str, Hex], &[]),
+    sys(177, "get_kernel_syms", &[Ptr], &[]),
+    sys(178, "query_module", &[Str, Int, Ptr, UInt, Ptr], &[]),
+    sys(179, "quotactl", &[Hex, Str, Int, Ptr], &[File]),
+    sys(180, "nfsservctl", &[Int, Ptr, Ptr], &[]),
+    sys(181, "getpmsg", &[], &[]),
+    sys(182, "putpmsg", &[], &[]),
+    sys(183, "afs_syscall", &[], &[]),
+    sys(184, "tuxcall", &[], &[]),
+    sys(185, "security", &[], &[]),
+    sys(186, "gettid", &[], &[]),
+    sys(187, "readahead", &[Fd, Int, UInt], &[Descriptor]),
+    sys(188, "setxattr", &[Str, Str, Ptr, UInt, Hex], &[File]),
+    sys(189, "lsetxattr", &[Str, Str, Ptr, UInt, Hex], &[File]),
+    sys(190, "fsetxattr", &[Fd, Str, Ptr, UInt, Hex], &[Descriptor]),
+    sys(191, "getxattr", &[Str, Str, Ptr, UInt], &[File]),
+    sys(192, "lgetxattr", &[Str, Str, Ptr, UInt], &[File]),
+    sys(193, "fgetxattr", &[Fd, Str, Ptr, UInt], &[Descriptor]),
+    sys(194, "listxattr", &[Str, Ptr, UInt], &[File]),
+    sys(195, "llistxattr", &[Str, Ptr, UInt], &[File]),
+    sys(196, "flistxattr", &[Fd, Ptr, UInt], &[Descriptor]),
+    sys(197, "removexattr", &[Str, Str], &[File]),
+    sys(198, "lremovexattr", &[Str, Str], &[File]),
+    sys(199, "fremovexattr", &[Fd, Str], &[Descriptor]),
+    sys(200, "tkill", &[Int, Sig], &[Signal, Process]),
+    sys(201, "time", &[Ptr], &[]),
+    sys(202, "futex", &[Ptr, Int, Int, Ptr, Ptr, Int], &[]),
+    sys(203, "sched_setaffinity", &[Int, UInt, Ptr], &[]),
+    sys(204, "sched_getaffinity", &[Int, UInt, Ptr], &[]),
+    sys(205, "set_thread_area", &[Ptr], &[]),
+    sys(206, "io_setup", &[UInt, Ptr], &[]),
+    sys(207, "io_destroy", &[Hex], &[]),
+    sys(208, "io_getevents", &[Hex, Int, Int, Ptr, Ptr], &[]),
+    sys(209, "io_submit", &[Hex, Int, Ptr], &[]),
+    sys(210, "io_cancel", &[Hex, Ptr, Ptr], &[]),
+    sys(211, "get_thread_area", &[Ptr], &[]),
+    sys(212, "lookup_dcookie", &[Hex, Ptr, UInt], &[]),
+    sys(213, "epoll_create", &[Int], &[Descriptor]),
+    sys(214, "epoll_ctl_old", &[], &[]),
+    sys(215, "epoll_wait_old", &[], &[]),
+    sys(216, "remap_file_pages", &[Ptr, UInt, Prot, UInt, Hex], &[Memory]),
+    sys(217, "getdents64", &[Fd, Ptr, UInt], &[Descriptor]),
+    sys(218, "set_tid_address", &[Ptr], &[]),
+    sys(219, "restart_syscall", &[], &[]),
+    sys(220, "semtimedop", &[Int, Ptr, UInt, Ptr], &[Ipc]),
+    sys(221, "fadvise64", &[Fd, Int, Int, Int], &[Descriptor]),
+    sys(222, "timer_create", &[Int, Ptr, Ptr], &[]),
+    sys(223, "timer_settime", &[Int, Hex, Ptr, Ptr], &[]),
+    sys(224, "timer_gettime", &[Int, Ptr], &[]),
+    sys(225, "timer_getoverrun", &[Int], &[]),
+    sys(226, "timer_delete", &[Int], &[]),
+    sys(227, "clock_settime", &[Int, Ptr], &[]),
+    sys(228, "clock_gettime", &[Int, OutTimespec], &[]),
+    sys(229, "clock_getres", &[Int, OutTimespec], &[]),
+    sys(230, "clock_nanosleep", &[Int, Hex, Timespec, Ptr], &[]),
+    sys(231, "exit_group", &[Int], &[Process]).returns(Ret::Never),
+    sys(232, "epoll_wait", &[Fd, Ptr, Int, Int], &[Descriptor]),
+    sys(233, "epoll_ctl", &[Fd, Int, Fd, Ptr], &[Descriptor]),
+    sys(234, "tgkill", &[Int, Int, Sig], &[Signal, Process]),
+    sys(235, "utimes", &[Str, Ptr], &[File]),
+    sys(236, "vserver", &[], &[]),
+    sys(237, "mbind", &[Ptr, UInt, Int, Ptr, UInt, Hex], &[Memory]),
+    sys(238, "set_mempolicy", &[Int, Ptr, UInt], &[Memory]),
+    sys(239, "get_mempolicy", &[Ptr, Ptr, UInt, Ptr, Hex], &[Memory]),
+    sys(240, "mq_open", &[Str, OpenFlags, Mode, Ptr], &[Ipc, Descriptor]),
+    sys(241, "mq_unlink", &[Str], &[Ipc]),
+    sys(242, "mq_timedsend", &[Fd, InBuf(2), UInt, UInt, Ptr], &[Ipc]),
+    sys(243, "mq_timedreceive", &[Fd, OutBuf(2), UInt, Ptr, Ptr], &[Ipc]),
+    sys(244, "mq_notify", &[Fd, Ptr], &[Ipc]),
+    sys(245, "mq_getsetattr", &[Fd, Ptr, Ptr], &[Ipc]),
+    sys(246, "kexec_load", &[Hex, UInt, Ptr, Hex], &[]),
+    sys(247, "waitid", &[Int, Int, Ptr, Hex, Ptr], &[Process]),
+    sys(248, "add_key", &[Str, Str, Ptr, UInt, Int], &[]),
+    sys(249, "request_key", &[Str, Str, Str, Int], &[]),
+    sys(250, "keyctl", &[Int, Hex, Hex, Hex, Hex], &[]),
+    sys(251, "ioprio_set", &[Int, Int, Int], &[]),
+    sys(252, "ioprio_get", &[Int, Int], &[]),
+    sys(253, "inotify_init", &[], &[Descriptor]),
+    sys(254, "inotify_add_watch", &[Fd, Str, Hex], &[Descriptor, File]),
+    sys(255, "inotify_rm_watch", &[Fd, Int], &[Descriptor]),
+    sys(256, "migrate_pages", &[Int, UInt, Ptr, Ptr], &[Memory]),
+    sys(257, "openat", &[Fd, Str, OpenFlags, Mode], &[File, Descriptor]),
+    sys(258, "mkdirat", &[Fd, Str, Mode], &[File, Descriptor]),
+    sys(259, "mknodat", &[Fd, Str, Mode, Hex], &[File, Descriptor]),
+    sys(260, "fchownat", &[Fd, Str, Int, Int, Hex], &[File, Descriptor]),
+    sys(261, "futimesat", &[Fd, Str, Ptr], &[File, Descriptor]),
+    sys(262, "newfstatat", &[Fd, Str, Stat, Hex], &[File, Descriptor]),
+    sys(263, "unlinkat", &[Fd, Str, Hex], &[File, Descriptor]),
+    sys(264, "renameat", &[Fd, Str, Fd, Str], &[File, Descriptor]),
+    sys(265, "linkat", &[Fd, Str, Fd, Str, Hex], &[File, Descriptor]),
+    sys(266, "symlinkat", &[Str, Fd, Str], &[File, Descriptor]),
+    sys(267, "readlinkat", &[Fd, Str, Ptr, UInt], &[File, Descriptor]),
+    sys(268, "fchmodat", &[Fd, Str, Mode], &[File, Descriptor]),
+    sys(269, "faccessat", &[Fd, Str, Int], &[File, Descriptor]),
+    sys(270, "pselect6", &[Int, Ptr, Ptr, Ptr, Ptr, Ptr], &[Descriptor]),
+    sys(271, "ppoll", &[Ptr, UInt, Ptr, Ptr, UInt], &[Descriptor]),
+    sys(272, "unshare", &[Hex], &[Process]),
+    sys(273, "set_robust_list", &[Ptr, UInt], &[]),
+    sys(274, "get_robust_list", &[Int, Ptr, Ptr], &[]),
+    sys(275, "splice", &[Fd, Ptr, Fd, Ptr, UInt, Hex], &[Descriptor]),
+    sys(276, "tee", &[Fd, Fd, UInt, Hex], &[Descriptor]),
+    sys(277, "sync_file_range", &[Fd, Int, Int, Hex], &[Descriptor]),
+    sys(278, "vmsplice", &[Fd, Ptr, UInt, Hex], &[Descriptor]),
+    sys(279, "move_pages", &[Int, UInt, Ptr, Ptr, Ptr, Hex], &[Memory]),
+    sys(280, "utimensat", &[Fd, Str, Ptr, Hex], &[File, Descriptor]),
+    sys(281, "epoll_pwait", &[Fd, Ptr, Int, Int, Ptr, UInt], &[Descriptor]),
+    sys(282, "signalfd", &[Fd, Ptr, UInt], &[Descriptor, Signal]),
+    sys(283, "timerfd_create", &[Int, Hex], &[Descriptor]),
+    sys(284, "eventfd", &[UInt], &[Descriptor]),
+    sys(285, "fallocate", &[Fd, Hex, Int, Int], &[Descriptor]),
+    sys(286, "timerfd_settime", &[Fd, Hex, Ptr, Ptr], &[Descriptor]),
+    sys(287, "timerfd_gettime", &[Fd, Ptr], &[Descriptor]),
+    sys(288, "accept4", &[Fd, Ptr, Ptr, Hex], &[Network, Descriptor]),
+    sys(289, "signalfd4", &[Fd, Ptr, UInt, Hex], &[Descriptor, Signal]),
+    sys(290, "eventfd2", &[UInt, Hex], &[Descriptor]),
+    sys(291, "epoll_create1", &[Hex], &[Descriptor]),
+    sys(292, "dup3", &[Fd, Fd, Hex], &[Descriptor]),
+    sys(293, "pipe2", &[Ptr, Hex], &[Descriptor, Ipc]),
+    sys(294, "inotify_init1", &[Hex], &[Descriptor]),
+    sys(295, "preadv", &[Fd, Ptr, Int, Int], &[Descriptor]),
+    sys(296, "pwritev", &[Fd, Ptr, Int, Int], &[Descriptor]),
+    sys(297, "rt_tgsigqueueinfo", &[Int, Int, Sig, Ptr], &[Signal, Process]),
+    sys(298, "perf_event_open", &[Ptr, Int, Int, Fd, Hex], &[Descriptor]),
+    sys(299, "recvmmsg", &[Fd, Ptr, UInt, Hex, Ptr], &[Network]),
+    sys(300, "fanotify_init", &[Hex, Hex], &[Descriptor]),
+    sys(301, "fanotify_mark", &[Fd, Hex, Hex, Fd, Str], &[Descriptor, File]),
+    sys(302, "prlimit64", &[Int, Int, Ptr, Ptr], &[]),
+    sys(303, "name_to_handle_at", &[Fd, Str, Ptr, Ptr, Hex], &[File, Descriptor]),
+    sys(304, "open_by_handle_at", &[Fd, Ptr, OpenFlags], &[Descriptor]),
+    sys(305, "clock_adjtime", &[Int, Ptr], &[]),
+    sys(306, "syncfs", &[Fd], &[Descriptor]),
+    sys(307, "sendmmsg", &[Fd, Ptr, UInt, Hex], &[Network]),
+    sys(308, "setns", &[Fd, Hex], &[Descriptor, Process]),
+    sys(309, "getcpu", &[Ptr, Ptr, Ptr], &[]),
+    sys(310, "process_vm_readv", &[Int, Ptr, UInt, Ptr, UInt, Hex], &[]),
+    sys(311, "process_vm_writev", &[Int, Ptr, UInt, Ptr, UInt, Hex], &[]),
+    sys(312, "kcmp", &[Int, Int, Int, Hex, Hex], &[]),
+    sys(313, "finit_module", &[Fd, Str, Hex], &[Descriptor]),
+    sys(314, "sched_setattr", &[Int, Ptr, Hex], &[]),
+    sys(315, "sched_getattr", &[Int, Ptr, UInt, Hex], &[]),
+    sys(316, "renameat2", &[Fd, Str, Fd, Str, Hex], &[File, Descriptor]),
+    sys(317, "seccomp", &[Int, Hex, Ptr], &[]),
+    sys(318, "getrandom", &[Ptr, UInt, Hex], &[]),
+    sys(319, "memfd_create", &[Str, Hex], &[Descriptor]),
+    sys(320, "kexec_file_load", &[Fd, Fd, UInt, Str, Hex], &[Descriptor]),
+    sys(321, "bpf", &[Int, Ptr, UInt], &[Descriptor]),
+    sys(322, "execveat", &[Fd, Str, StrArray, Ptr, Hex], &[File, Process, Descriptor]),
+    sys(323, "userfaultfd", &[Hex], &[Descriptor]),
+    sys(324, "membarrier", &[Int, Hex, Int], &[]),
+    sys(325, "mlock2", &[Ptr, UInt, Hex], &[Memory]),
+    sys(326, "copy_file_range", &[Fd, Ptr, Fd, Ptr, UInt, Hex], &[Descriptor]),
+    sys(327, "preadv2", &[Fd, Ptr, Int, Int, Int, Hex], &[Descriptor]),
+    sys(328, "pwritev2", &[Fd, Ptr, Int, Int, Int, Hex], &[Descriptor]),
+    sys(329, "pkey_mprotect", &[Ptr, UInt, Prot, Int], &[Memory]),
+    sys(330, "pkey_alloc", &[Hex, Hex], &[Memory]),
+    sys(331, "pkey_free", &[Int], &[Memory]),
+    sys(332, "statx", &[Fd, Str, Hex, Hex, Ptr], &[File, Descriptor]),
+    sys(333, "io_pgetevents", &[Hex, Int, Int, Ptr, Ptr, Ptr], &[]),
+    sys(334, "rseq", &[Ptr, UInt, Hex, Hex], &[]),
+    sys(424, "pidfd_send_signal", &[Fd, Sig, Ptr, Hex], &[Descriptor, Signal, Process]),
+    sys(425, "io_uring_setup", &[UInt, Ptr], &[Descriptor]),
+    sys(426, "io_uring_enter", &[Fd, UInt, UInt, Hex, Ptr, UInt], &[Descriptor]),
+    sys(427, "io_uring_register", &[Fd, Int, Ptr, UInt], &[Descriptor]),
+    sys(428, "open_tree", &[Fd, Str, Hex], &[File, Descriptor]),
+    sys(429, "move_mount", &[Fd, Str, Fd, Str, Hex], &[File, Descriptor]),
+    sys(430, "fsopen", &[Str, Hex], &[File, Descriptor]),
+    sys(431, "fsconfig", &[Fd, Int, Str, Ptr, Int], &[File, Descriptor]),
+    sys(432, "fsmount", &[Fd, Hex, Hex], &[File, Descriptor]),
+    sys(433, "fspick", &[Fd, Str, Hex], &[File, Descriptor]),
+    sys(434, "pidfd_open", &[Int, Hex], &[Descriptor, Process]),
+    sys(435, "clone3", &[Ptr, UInt], &[Process]),
+    sys(436, "close_range", &[UInt, UInt, Hex], &[Descriptor]),
+    sys(437, "openat2", &[Fd, Str, Ptr, UInt], &[File, Descriptor]),
+    sys(438, "pidfd_getfd", &[Fd, Int, Hex], &[Descriptor]),
+    sys(439, "faccessat2", &[Fd, Str, Int, Hex], &[File, Descriptor]),
+    sys(440, "process_madvise", &[Fd, Ptr, UInt, Int, Hex], &[Memory, Descriptor]),
+    sys(441, "epoll_pwait2", &[Fd, Ptr, Int, Ptr, Ptr, UInt], &[Descriptor]),
+    sys(442, "mount_setattr", &[Fd, Str, Hex, Ptr, UInt], &[File, Descriptor]),
+    sys(443, "quotactl_fd", &[Fd, Hex, Int, Ptr], &[Descriptor]),
+    sys(444, "landlock_create_ruleset", &[Ptr, UInt, Hex], &[Descriptor]),
+    sys(445, "landlock_add_rule", &[Fd, Int, Ptr, Hex], &[Descriptor]),
+    sys(446, "landlock_restrict_self", &[Fd, Hex], &[Descriptor]),
+    sys(447, "memfd_secret", &[Hex], &[Memory, Descriptor]),
+    sys(448, "process_mrelease", &[Fd, Hex], &[Descriptor, Memory]),
+    sys(449, "futex_waitv", &[Ptr, UInt, Hex, Ptr, Int], &[]),
+    sys(450, "set_mempolicy_home_node", &[Ptr, UInt, UInt, Hex], &[Memory]),
+    sys(451, "cachestat", &[Fd, Ptr, Ptr, Hex], &[Descriptor]),
+    sys(452, "fchmodat2", &[Fd, Str, Mode, Hex], &[File, Descriptor]),
+    sys(453, "map_shadow_stack", &[Ptr, UInt, Hex], &[Memory]).returns(Ret::Hex),
+    sys(454, "futex_wake", &[Ptr, Hex, Int, Hex], &[]),
+    sys(455, "futex_wait", &[Ptr, Hex, Hex, Hex, Ptr, Int], &[]),
+    sys(456, "futex_requeue", &[Ptr, Hex, Int, Int], &[]),
+    sys(457, "statmount", &[Ptr, Ptr, UInt, Hex], &[]),
+    sys(458, "listmount", &[Ptr, Ptr, UInt, Hex], &[]),
+    sys(459, "lsm_get_self_attr", &[UInt, Ptr, Ptr, Hex], &[]),
+    sys(460, "lsm_set_self_attr", &[UInt, Ptr, UInt, Hex], &[]),
+    sys(461, "lsm_list_modules", &[Ptr, Ptr, Hex], &[]),
+    sys(462, "mseal", &[Ptr, UInt, Hex], &[Memory]),
+];
@@ -0,0 +1,53 @@
+use std::ptr;
+
+///
+/// A stopped child traced by us, which shares our memory layout, so that
+/// anything we have mapped can be looked at in it too. It's killed once
+/// dropped.
+///
+pub struct Child(pub i32);
+
+impl Child {
+    pub fn fork() -> Self {
+        let pid = unsafe { libc::fork() };
+        if pid == 0 {
+            unsafe {
+                libc::ptrace(libc::PTRACE_TRACEME, 0, 0, 0);
+                libc::raise(libc::SIGSTOP);
+                libc::_exit(0);
+            }
+        }
+        let mut status = 0;
+        assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+        assert!(libc::WIFSTOPPED(status));
+        Self(pid)
+    }
+}
+
+impl Drop for Child {
+    fn drop(&mut self) {
+        unsafe {
+            libc::kill(self.0, libc::SIGKILL);
+            libc::waitpid(self.0, ptr::null_mut(), 0);
+        }
+    }
+}
+
+///
+/// Maps a page with nothing mapped after it, with each byte holding the
+/// low byte of its offset into the page.
+///
+pub fn last_page() -> u64 {
+    let size = 4096;
+    unsafe {
+        let page = libc::mmap(ptr::null_mut(), size * 2, libc::PROT_READ | libc::PROT_WRITE,
+                              libc::MAP_PRIVATE | libc::MAP_ANONYMOUS, -1, 0);
+        assert_ne!(page, libc::MAP_FAILED);
+        let page = page as *mut u8;
+        libc::munmap(page.add(size) as *mut libc::c_void, size);
+        for i in 0..size {
+            *page.add(i) = i as u8;
+        }
+        page as u64
+    }
+}
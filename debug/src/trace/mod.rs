@@ -2,6 +2,7 @@
 mod tracer;
 
 use tracer::Tracer;
+pub use tracer::{FpRegisters, Options, Registers, SigInfo, Signal};
 use std::os::raw::c_long;
 use std::fmt::{Display, Formatter};
 use std::fmt;
@@ -47,32 +48,76 @@ trait ProcessTracer {
     fn write_data(pid: i32, addr: u64, data: u64) -> Result<()>;
 
     ///
-    /// Continue execution of the process, until the next signal
+    /// Continue execution of the process, until the next signal, delivering
+    /// the given signal to it
     ///
-    fn proceed(pid: i32) -> Result<()>;
+    fn proceed(pid: i32, signal: Option<Signal>) -> Result<()>;
 
     ///
-    /// Indicate that this process is waiting to be traced.
-    /// Typically used before exec.
+    /// Continue execution of the process, as with `proceed`, but also stop
+    /// on entry to and exit from every system call
     ///
-    fn trace_me() -> Result<()>;
+    fn syscall(pid: i32, signal: Option<Signal>) -> Result<()>;
 
     ///
-    /// Step a single instruction.
+    /// Step a single instruction, delivering the given signal first
     ///
-    /// TODO: add signal delivery
+    fn step(pid: i32, signal: Option<Signal>) -> Result<()>;
+
+    ///
+    /// Attach to a given PID with the given tracing options, without
+    /// stopping it or otherwise disturbing its job control state
+    ///
+    fn seize(pid: i32, options: Options) -> Result<()>;
+
+    ///
+    /// Stop a seized process, which reports the stop as a
+    /// `PTRACE_EVENT_STOP` once it gets to it
+    ///
+    fn interrupt(pid: i32) -> Result<()>;
+
+    ///
+    /// Leave a process that reported a group-stop stopped, but have it report
+    /// again once it's continued (or gets another signal), rather than
+    /// holding it in the stop until it's resumed
+    ///
+    fn listen(pid: i32) -> Result<()>;
+
+    ///
+    /// Detach from a given PID, delivering the given signal to it
+    ///
+    fn detach(pid: i32, signal: Option<Signal>) -> Result<()>;
+
+    ///
+    /// Read the general purpose registers of the process
     ///
-    fn step(pid: i32) -> Result<()>;
+    fn get_registers(pid: i32) -> Result<Registers>;
 
     ///
-    /// Attach to a given PID
+    /// Overwrite the general purpose registers of the process
     ///
-    fn attach(pid: i32) -> Result<()>;
+    fn set_registers(pid: i32, regs: Registers) -> Result<()>;
 
     ///
-    /// Detach from a given PID
+    /// Read the floating point and SSE registers of the process
     ///
-    fn detach(pid: i32) -> Result<()>;
+    fn get_fp_registers(pid: i32) -> Result<FpRegisters>;
+
+    ///
+    /// Set the tracing options of the process, e.g. which events to report
+    ///
+    fn set_options(pid: i32, options: Options) -> Result<()>;
+
+    ///
+    /// Read the message attached to the event the process last stopped with,
+    /// e.g. the id of a newly created thread
+    ///
+    fn get_event_message(pid: i32) -> Result<u64>;
+
+    ///
+    /// Read the details of the signal the process is stopped with
+    ///
+    fn get_siginfo(pid: i32) -> Result<SigInfo>;
 }
 
 ///
@@ -104,37 +149,99 @@ pub fn write_data(pid: i32, addr: u64, data: u64) -> Result<()> {
 }
 
 ///
-/// Continue execution of the process, until the next signal
+/// Continue execution of the process, until the next signal, delivering
+/// the given signal to it
+///
+pub fn proceed(pid: i32, signal: Option<Signal>) -> Result<()> {
+    Tracer::proceed(pid, signal)
+}
+
+///
+/// Continue execution of the process, as with `proceed`, but also stop
+/// on entry to and exit from every system call
+///
+pub fn syscall(pid: i32, signal: Option<Signal>) -> Result<()> {
+    Tracer::syscall(pid, signal)
+}
+
+///
+/// Step a single instruction, delivering the given signal first
+///
+pub fn step(pid: i32, signal: Option<Signal>) -> Result<()> {
+    Tracer::step(pid, signal)
+}
+
+///
+/// Attach to a given PID with the given tracing options, without
+/// stopping it or otherwise disturbing its job control state
+///
+pub fn seize(pid: i32, options: Options) -> Result<()> {
+    Tracer::seize(pid, options)
+}
+
+///
+/// Stop a seized process, which reports the stop as a
+/// `PTRACE_EVENT_STOP` once it gets to it
+///
+pub fn interrupt(pid: i32) -> Result<()> {
+    Tracer::interrupt(pid)
+}
+
+///
+/// Leave a process that reported a group-stop stopped, but have it report
+/// again once it's continued (or gets another signal), rather than
+/// holding it in the stop until it's resumed
+///
+pub fn listen(pid: i32) -> Result<()> {
+    Tracer::listen(pid)
+}
+
+///
+/// Detach from a given PID, delivering the given signal to it
+///
+pub fn detach(pid: i32, signal: Option<Signal>) -> Result<()> {
+    Tracer::detach(pid, signal)
+}
+
+///
+/// Read the general purpose registers of the process
+///
+pub fn get_registers(pid: i32) -> Result<Registers> {
+    Tracer::get_registers(pid)
+}
+
+///
+/// Overwrite the general purpose registers of the process
 ///
-pub fn proceed(pid: i32) -> Result<()> {
-    Tracer::proceed(pid)
+pub fn set_registers(pid: i32, regs: Registers) -> Result<()> {
+    Tracer::set_registers(pid, regs)
 }
 
 ///
-/// Indicate that this process is waiting to be traced.
-/// Typically used before exec.
+/// Read the floating point and SSE registers of the process
 ///
-pub fn trace_me() -> Result<()> {
-    Tracer::trace_me()
+pub fn get_fp_registers(pid: i32) -> Result<FpRegisters> {
+    Tracer::get_fp_registers(pid)
 }
 
 ///
-/// Step a single instruction.
+/// Set the tracing options of the process, e.g. which events to report
 ///
-pub fn step(pid: i32) -> Result<()> {
-    Tracer::step(pid)
+pub fn set_options(pid: i32, options: Options) -> Result<()> {
+    Tracer::set_options(pid, options)
 }
 
 ///
-/// Attach to a given PID
+/// Read the message attached to the event the process last stopped with,
+/// e.g. the id of a newly created thread
 ///
-pub fn attach(pid: i32) -> Result<()> {
-    Tracer::attach(pid)
+pub fn get_event_message(pid: i32) -> Result<u64> {
+    Tracer::get_event_message(pid)
 }
 
 ///
-/// Detach from a given PID
+/// Read the details of the signal the process is stopped with
 ///
-pub fn detach(pid: i32) -> Result<()> {
-    Tracer::detach(pid)
+pub fn get_siginfo(pid: i32) -> Result<SigInfo> {
+    Tracer::get_siginfo(pid)
 }
@@ -1,6 +1,7 @@
 use std::os::raw::c_long;
 
 use nix::Error;
+use nix::errno::Errno;
 use nix::sys::ptrace::AddressType;
 use nix::unistd::Pid;
 
@@ -8,6 +9,16 @@ use crate::trace::{ProcessTracer, Result, TraceError};
 
 pub struct Tracer;
 
+pub type Registers = libc::user_regs_struct;
+
+pub type FpRegisters = libc::user_fpregs_struct;
+
+pub type Options = nix::sys::ptrace::Options;
+
+pub type Signal = nix::sys::signal::Signal;
+
+pub type SigInfo = libc::siginfo_t;
+
 impl From<nix::Error> for TraceError {
     fn from(n: nix::Error) -> Self {
         match n {
@@ -38,23 +49,62 @@ impl ProcessTracer for Tracer {
         Tracer::write_text(pid, addr, data)
     }
 
-    fn proceed(pid: i32) -> Result<()> {
-        nix::sys::ptrace::cont(Pid::from_raw(pid), None).map_err(|e| e.into())
+    fn proceed(pid: i32, signal: Option<Signal>) -> Result<()> {
+        nix::sys::ptrace::cont(Pid::from_raw(pid), signal).map_err(|e| e.into())
+    }
+
+    fn syscall(pid: i32, signal: Option<Signal>) -> Result<()> {
+        nix::sys::ptrace::syscall(Pid::from_raw(pid), signal).map_err(|e| e.into())
+    }
+
+    fn step(pid: i32, signal: Option<Signal>) -> Result<()> {
+        nix::sys::ptrace::step(Pid::from_raw(pid), signal).map_err(|e| e.into())
+    }
+
+    fn seize(pid: i32, options: Options) -> Result<()> {
+        nix::sys::ptrace::seize(Pid::from_raw(pid), options).map_err(|e| e.into())
     }
 
-    fn trace_me() -> Result<()> {
-        nix::sys::ptrace::traceme().map_err(|e| e.into())
+    fn interrupt(pid: i32) -> Result<()> {
+        // nix has no wrapper for the seize-only requests
+        let result = unsafe { libc::ptrace(libc::PTRACE_INTERRUPT, pid, 0, 0) };
+        Errno::result(result).map(drop).map_err(|e| e.into())
     }
 
-    fn step(pid: i32) -> Result<()> {
-        nix::sys::ptrace::step(Pid::from_raw(pid), None).map_err(|e| e.into())
+    fn listen(pid: i32) -> Result<()> {
+        let result = unsafe { libc::ptrace(libc::PTRACE_LISTEN, pid, 0, 0) };
+        Errno::result(result).map(drop).map_err(|e| e.into())
     }
 
-    fn attach(pid: i32) -> Result<()> {
-        nix::sys::ptrace::attach(Pid::from_raw(pid)).map_err(|e| e.into())
+    fn detach(pid: i32, signal: Option<Signal>) -> Result<()> {
+        nix::sys::ptrace::detach(Pid::from_raw(pid), signal).map_err(|e| e.into())
     }
 
-    fn detach(pid: i32) -> Result<()> {
-        nix::sys::ptrace::detach(Pid::from_raw(pid), None).map_err(|e| e.into())
+    fn get_registers(pid: i32) -> Result<Registers> {
+        nix::sys::ptrace::getregs(Pid::from_raw(pid)).map_err(|e| e.into())
     }
-}
\ No newline at end of file
+
+    fn set_registers(pid: i32, regs: Registers) -> Result<()> {
+        nix::sys::ptrace::setregs(Pid::from_raw(pid), regs).map_err(|e| e.into())
+    }
+
+    fn get_fp_registers(pid: i32) -> Result<FpRegisters> {
+        // nix has no wrapper for reading the floating point registers
+        let mut regs = std::mem::MaybeUninit::<FpRegisters>::uninit();
+        let result = unsafe { libc::ptrace(libc::PTRACE_GETFPREGS, pid, 0, regs.as_mut_ptr()) };
+        Errno::result(result).map_err(TraceError::from)?;
+        Ok(unsafe { regs.assume_init() })
+    }
+
+    fn set_options(pid: i32, options: Options) -> Result<()> {
+        nix::sys::ptrace::setoptions(Pid::from_raw(pid), options).map_err(|e| e.into())
+    }
+
+    fn get_event_message(pid: i32) -> Result<u64> {
+        nix::sys::ptrace::getevent(Pid::from_raw(pid)).map(|msg| msg as u64).map_err(|e| e.into())
+    }
+
+    fn get_siginfo(pid: i32) -> Result<SigInfo> {
+        nix::sys::ptrace::getsiginfo(Pid::from_raw(pid)).map_err(|e| e.into())
+    }
+}
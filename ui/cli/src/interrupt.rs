@@ -0,0 +1,17 @@
+use std::os::raw::c_int;
+
+use nix::sys::signal::{sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal};
+
+extern "C" fn forward(_signal: c_int) {
+    debug::interrupt::request();
+}
+
+///
+/// Makes Ctrl-C stop the process while it runs, rather than the debugger,
+/// which goes back to the prompt once the process has stopped. At the
+/// prompt itself, the line editor reads Ctrl-C as a key instead.
+///
+pub fn install() -> nix::Result<()> {
+    let action = SigAction::new(SigHandler::Handler(forward), SaFlags::SA_RESTART, SigSet::empty());
+    unsafe { sigaction(Signal::SIGINT, &action) }.map(|_| ())
+}
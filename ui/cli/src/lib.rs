@@ -1,20 +1,32 @@
 extern crate commands;
 extern crate debug;
 extern crate log;
+extern crate nix;
 extern crate rustyline;
 
 use std::error::Error;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
 
+use rustyline::error::ReadlineError;
 use rustyline::{Cmd, CompletionType, Config, EditMode, Editor, KeyEvent};
 
 use commands::Commands;
 use common::cli::{Clap, CommandLine};
 use debug::Debugger;
+use debug::process::list;
 use std::io::Write;
 
+mod interrupt;
 mod logger;
+mod terminate;
 
-const PROMPT: &'static str = "sdbg>> ";
+const PROMPT: &str = "sdbg>> ";
+
+/// How often we check for being asked to go away while the prompt is up.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 pub struct App {
     history_file: String,
@@ -36,8 +48,19 @@ impl App {
     pub fn run(&self) -> Result<(), Box<dyn Error>> {
         logger::init_logger().unwrap();
 
-        let cmdline = CommandLine::parse_from(std::env::args());
+        let mut cmdline = CommandLine::parse_from(std::env::args());
+        if let Some(name) = cmdline.attach_name.take() {
+            match self.choose_process(&name) {
+                Ok(pid) => cmdline.pid = pid,
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        // Ctrl-C gives up on waiting for a process to start, rather than
+        // killing us, so the handler goes in before the debugger is set up
+        interrupt::install()?;
         let mut debugger = Debugger::from_config(cmdline);
+        terminate::install()?;
+        let terminal = terminate::Terminal::save();
 
         let config = Config::builder()
             .history_ignore_space(true)
@@ -49,14 +72,19 @@ impl App {
         rl.load_history(self.history_file.as_str())?;
         rl.bind_sequence(KeyEvent::ctrl('r'), Cmd::HistorySearchForward);
         rl.bind_sequence(KeyEvent::ctrl('l'), Cmd::ClearScreen);
+        let reader = Reader::spawn(rl);
 
         loop {
-            match rl.readline(PROMPT) {
-                Ok(line) => {
+            // in non-stop mode, threads can stop while we're at the prompt
+            if let Err(e) = debugger.poll_events() {
+                eprintln!("{}", e);
+            }
+
+            match reader.read_line() {
+                Some(Ok(line)) => {
                     if line.is_empty() {
                         continue;
                     }
-                    rl.add_history_entry(line.as_str());
 
                     if line == "exit" {
                         match self.handle_exit(&mut debugger) {
@@ -69,6 +97,7 @@ impl App {
                                 eprintln!("{}", e);
                             }
                         }
+                        continue;
                     }
 
                     let error = match Commands::parse_line(line) {
@@ -76,36 +105,97 @@ impl App {
                         None => Ok(())
                     };
 
+                    // the command may have been cut short by the signal
+                    if terminate::received().is_some() {
+                        break;
+                    }
                     if let Err(error) = error {
                         eprintln!("{}", error);
                     }
                 }
-                Err(e) => {
+                Some(Err(e)) => {
                     use rustyline::error::ReadlineError::*;
                     match e {
-                        Eof | Interrupted => { break; }
+                        Eof => { break; }
+                        // Ctrl-C just throws away the line
+                        Interrupted => continue,
                         _ => eprintln!("input error: {:?}", e)
                     };
                 }
+                None => break,
             }
         }
 
+        if let Some(signal) = terminate::received() {
+            // the reader is stuck waiting on a line that won't come, so its
+            // history is lost along with it.
+            terminal.restore();
+            eprintln!("received {}, letting go of every process", signal);
+            debugger.quit()?;
+            return Ok(());
+        }
+
+        debugger.quit()?;
+        let mut rl = reader.finish();
         rl.append_history(self.history_file.as_str())?;
         Ok(())
     }
 
+    ///
+    /// Finds the process to attach to by name, asking which one is meant if
+    /// there are several. Returns `None` if none of them is picked.
+    ///
+    fn choose_process(&self, name: &str) -> Result<Option<i32>, Box<dyn Error>> {
+        let entries = list::named(name)?;
+        match entries.as_slice() {
+            [] => return Err(format!("no process named {}", name).into()),
+            [entry] => return Ok(Some(entry.pid)),
+            _ => {}
+        }
+
+        println!("several processes are named {}:", name);
+        for (i, entry) in entries.iter().enumerate() {
+            println!("  [{}] {:<8} {}", i + 1, entry.pid, entry.command_line());
+        }
+        let mut choice = String::new();
+        loop {
+            print!("attach to which one? (1-{}, or n for none) ", entries.len());
+            choice.clear();
+            std::io::stdout().flush()?;
+            if std::io::stdin().read_line(&mut choice)? == 0 {
+                return Ok(None);
+            }
+            match choice.trim() {
+                "N" | "n" | "no" => return Ok(None),
+                input => match input.parse::<usize>() {
+                    Ok(n) if (1..=entries.len()).contains(&n) => return Ok(Some(entries[n - 1].pid)),
+                    _ => continue,
+                },
+            }
+        }
+    }
+
     fn handle_exit(&self, dbg: &mut Debugger) -> Result<bool, Box<dyn Error>> {
         if dbg.process_is_running() {
             let mut confirm = String::new();
+            let action = if dbg.kills_on_exit() { "kill" } else { "detach" };
 
             loop {
-                print!("process is still running! detach? (Y/n) ");
+                print!("process is still running! {}? (Y/n, or k to kill, d to detach) ", action);
                 confirm.clear();
                 std::io::stdout().flush()?;
                 std::io::stdin().read_line(&mut confirm)?;
                 let input = confirm.strip_suffix("\n").unwrap_or_default();
                 match input {
-                    "Y" | "y" | "yes" => {
+                    "" | "Y" | "y" | "yes" => {
+                        dbg.quit()?;
+                        return Ok(true);
+                    }
+                    "K" | "k" | "kill" => {
+                        dbg.kill_all()?;
+                        return Ok(true);
+                    }
+                    "D" | "d" | "detach" => {
                         dbg.detach()?;
                         return Ok(true);
                     }
@@ -119,3 +209,60 @@ impl App {
         Ok(true)
     }
 }
+
+///
+/// Reads lines on a thread of its own, one each time it's asked to, so that
+/// the main thread, which traces the processes, can notice being asked to go
+/// away while the prompt is up.
+///
+struct Reader {
+    prompts: Sender<()>,
+    lines: Receiver<Result<String, ReadlineError>>,
+    thread: JoinHandle<Editor<()>>,
+}
+
+impl Reader {
+    fn spawn(mut rl: Editor<()>) -> Self {
+        let (prompts, requests) = channel();
+        let (results, lines) = channel();
+        let thread = thread::spawn(move || {
+            let _ = terminate::block();
+            while requests.recv().is_ok() {
+                let line = rl.readline(PROMPT);
+                if let Ok(line) = &line {
+                    if !line.is_empty() {
+                        rl.add_history_entry(line.as_str());
+                    }
+                }
+                if results.send(line).is_err() {
+                    break;
+                }
+            }
+            rl
+        });
+        Self { prompts, lines, thread }
+    }
+
+    ///
+    /// Prompts for the next line, returning `None` if we're asked to go
+    /// away before it comes.
+    ///
+    fn read_line(&self) -> Option<Result<String, ReadlineError>> {
+        self.prompts.send(()).ok()?;
+        loop {
+            match self.lines.recv_timeout(POLL_INTERVAL) {
+                Ok(line) => return Some(line),
+                Err(RecvTimeoutError::Timeout) if terminate::received().is_none() => continue,
+                Err(_) => return None,
+            }
+        }
+    }
+
+    ///
+    /// Stops the reader, handing back its editor.
+    ///
+    fn finish(self) -> Editor<()> {
+        drop(self.prompts);
+        self.thread.join().expect("the line reader panicked")
+    }
+}
@@ -0,0 +1,69 @@
+use std::convert::TryFrom;
+use std::io;
+use std::os::raw::c_int;
+use std::os::unix::io::AsRawFd;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use nix::sys::signal::{sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal};
+use nix::sys::termios::{tcgetattr, tcsetattr, SetArg, Termios};
+
+/// The signal asking us to go away, once one has come.
+static RECEIVED: AtomicI32 = AtomicI32::new(0);
+
+extern "C" fn record(signal: c_int) {
+    RECEIVED.store(signal, Ordering::SeqCst);
+}
+
+///
+/// Installs handlers for the signals asking the debugger to go away, so
+/// that it can let go of its processes first, rather than leaving them
+/// stopped. The handlers only note that a signal came, and since system
+/// calls aren't restarted after them, a wait for the process to stop is
+/// cut short too.
+///
+pub fn install() -> nix::Result<()> {
+    let action = SigAction::new(SigHandler::Handler(record), SaFlags::empty(), SigSet::empty());
+    for signal in &[Signal::SIGTERM, Signal::SIGHUP] {
+        unsafe { sigaction(*signal, &action)? };
+    }
+    Ok(())
+}
+
+///
+/// Keeps the signals for the main thread, which is the one tracing the
+/// processes, for threads that only wait on other things.
+///
+pub fn block() -> nix::Result<()> {
+    let mut signals = SigSet::empty();
+    signals.add(Signal::SIGTERM);
+    signals.add(Signal::SIGHUP);
+    signals.thread_block()
+}
+
+///
+/// The signal asking us to go away, if one has come.
+///
+pub fn received() -> Option<Signal> {
+    match RECEIVED.load(Ordering::SeqCst) {
+        0 => None,
+        signal => Signal::try_from(signal).ok(),
+    }
+}
+
+///
+/// The terminal settings from before the line editor changed them, which
+/// are put back if we go away in the middle of reading a line.
+///
+pub struct Terminal(Option<Termios>);
+
+impl Terminal {
+    pub fn save() -> Self {
+        Self(tcgetattr(io::stdin().as_raw_fd()).ok())
+    }
+
+    pub fn restore(&self) {
+        if let Some(termios) = &self.0 {
+            let _ = tcsetattr(io::stdin().as_raw_fd(), SetArg::TCSANOW, termios);
+        }
+    }
+}
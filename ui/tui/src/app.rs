@@ -15,7 +15,7 @@ use unicode_width::UnicodeWidthStr;
 
 use crate::event::{Event, Events};
 
-const DEFAULT_HIST_FILE: &'static str = ".sdbg_history";
+const DEFAULT_HIST_FILE: &str = ".sdbg_history";
 
 type AppResult = Result<(), Box<dyn Error>>;
 